@@ -0,0 +1,105 @@
+use ark_mnt4_753::Fr as MNT4BigFr;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use puzzle_gamma_ray::{
+    batch_verify, build_tree, generate_leaves, nullifier, poseidon_parameters, prove, setup,
+    verify, SpendCircuit, Zeroizing, LEGACY_DOMAIN,
+};
+
+const HEIGHTS: [usize; 3] = [2, 4, 6];
+const BATCH_SIZES: [usize; 2] = [2, 8];
+
+fn spend_circuit(height: usize, rng: &mut StdRng) -> SpendCircuit {
+    let leaf_crh_params = poseidon_parameters::poseidon_parameters();
+    let two_to_one_crh_params = leaf_crh_params.clone();
+
+    let (leaves, secrets) = generate_leaves(1 << height, rng);
+    let secret = secrets[0];
+
+    let (tree, _) = build_tree(&leaf_crh_params, &two_to_one_crh_params, &leaves, height).unwrap();
+    let nullifier = nullifier(&leaf_crh_params, secret, LEGACY_DOMAIN).unwrap();
+
+    SpendCircuit::new(
+        leaf_crh_params,
+        two_to_one_crh_params,
+        tree.root(),
+        tree.generate_proof(0).unwrap(),
+        Zeroizing::new(secret),
+        nullifier,
+    )
+}
+
+fn bench_spend(c: &mut Criterion) {
+    let mut group = c.benchmark_group("spend");
+    group.sample_size(10);
+
+    for height in HEIGHTS {
+        let rng = &mut StdRng::seed_from_u64(0u64);
+        let circuit = spend_circuit(height, rng);
+        let (pk, vk) = setup(circuit.clone(), rng).unwrap();
+
+        group.bench_with_input(
+            BenchmarkId::new("synthesize", height),
+            &circuit,
+            |b, circuit| {
+                b.iter(|| {
+                    let cs = ConstraintSystem::<MNT4BigFr>::new_ref();
+                    circuit.clone().generate_constraints(cs).unwrap();
+                })
+            },
+        );
+
+        group.bench_with_input(BenchmarkId::new("prove", height), &circuit, |b, circuit| {
+            b.iter(|| prove(&pk, circuit.clone(), rng).unwrap())
+        });
+
+        let proof = prove(&pk, circuit.clone(), rng).unwrap();
+        group.bench_with_input(
+            BenchmarkId::new("verify", height),
+            &circuit,
+            |b, circuit| {
+                b.iter(|| assert!(verify(&vk, &circuit.root, &circuit.nullifier, &proof).unwrap()))
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn bench_batch_verify(c: &mut Criterion) {
+    let mut group = c.benchmark_group("batch_verify");
+    group.sample_size(10);
+
+    let rng = &mut StdRng::seed_from_u64(0u64);
+    let circuit = spend_circuit(HEIGHTS[0], rng);
+    let (pk, vk) = setup(circuit.clone(), rng).unwrap();
+
+    for size in BATCH_SIZES {
+        let batch: Vec<_> = (0..size)
+            .map(|_| {
+                let proof = prove(&pk, circuit.clone(), rng).unwrap();
+                (proof, vec![circuit.root, circuit.nullifier])
+            })
+            .collect();
+
+        group.bench_with_input(BenchmarkId::new("each", size), &batch, |b, batch| {
+            b.iter(|| {
+                for (proof, inputs) in batch {
+                    assert!(verify(&vk, &inputs[0], &inputs[1], proof).unwrap());
+                }
+            })
+        });
+
+        group.bench_with_input(BenchmarkId::new("batched", size), &batch, |b, batch| {
+            b.iter(|| assert!(batch_verify(&vk, batch, rng)))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_spend, bench_batch_verify);
+criterion_main!(benches);
@@ -0,0 +1,60 @@
+//! Generates leaves, keys and a proof in memory and verifies it, without any
+//! of the fixture files.
+//!
+//!     cargo run --release --example end_to_end
+
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+use std::time::Instant;
+
+use puzzle_gamma_ray::{
+    build_tree, generate_leaves, nullifier, poseidon_parameters, prove, setup, verify, Mnt753Cycle,
+    SpendCircuit, Zeroizing, LEGACY_DOMAIN,
+};
+
+const HEIGHT: usize = 4;
+const INDEX: usize = 2;
+
+fn main() {
+    let rng = &mut StdRng::seed_from_u64(0u64);
+
+    let leaf_crh_params = poseidon_parameters::poseidon_parameters();
+    let two_to_one_crh_params = leaf_crh_params.clone();
+
+    let start = Instant::now();
+    let (leaves, secrets) = generate_leaves(1 << HEIGHT, rng);
+    let (tree, _) = build_tree(&leaf_crh_params, &two_to_one_crh_params, &leaves, HEIGHT)
+        .expect("the leaves fit in the tree");
+    println!(
+        "built a tree of {} leaves in {:?}",
+        leaves.len(),
+        start.elapsed()
+    );
+
+    let secret = secrets[INDEX];
+    let nullifier = nullifier(&leaf_crh_params, secret, LEGACY_DOMAIN).unwrap();
+    let root = tree.root();
+
+    let circuit = SpendCircuit::<Mnt753Cycle>::new(
+        leaf_crh_params,
+        two_to_one_crh_params,
+        root,
+        tree.generate_proof(INDEX).unwrap(),
+        Zeroizing::new(secret),
+        nullifier,
+    );
+
+    let start = Instant::now();
+    let (pk, vk) = setup(circuit.clone(), rng).unwrap();
+    println!("ran setup in {:?}", start.elapsed());
+
+    let start = Instant::now();
+    let proof = prove(&pk, circuit, rng).unwrap();
+    println!("proved in {:?}", start.elapsed());
+
+    let start = Instant::now();
+    let valid = verify(&vk, &root, &nullifier, &proof).unwrap();
+    println!("verified in {:?}", start.elapsed());
+
+    println!("proof is {}", if valid { "valid" } else { "invalid" });
+}
@@ -0,0 +1,79 @@
+//! Runs the double spend against the legacy circuit, then against the strict
+//! circuit that fixes it, with leaves and keys generated in memory.
+
+use ark_mnt4_753::Fr as MNT4BigFr;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+use puzzle_gamma_ray::{
+    build_tree, check_satisfied, fr_to_scalar, generate_leaves, nullifier, poseidon_parameters,
+    prove, scalar_to_fr, setup, verify, MntMerkleTree, SpendCircuit, Zeroizing, LEGACY_DOMAIN,
+};
+
+const HEIGHT: usize = 2;
+const INDEX: usize = 1;
+
+fn spend(tree: &MntMerkleTree, secret: MNT4BigFr, strict: bool) -> SpendCircuit {
+    let params = poseidon_parameters::poseidon_parameters();
+    let nullifier = nullifier(&params, secret, LEGACY_DOMAIN).unwrap();
+
+    SpendCircuit::new(
+        params.clone(),
+        params,
+        tree.root(),
+        tree.generate_proof(INDEX).unwrap(),
+        Zeroizing::new(secret),
+        nullifier,
+    )
+    .with_strict_nullifier(strict)
+}
+
+/// Proves `circuits` under keys generated for the first one, and prints each
+/// nullifier with whether its proof verifies. A witness that doesn't satisfy
+/// the circuit has no proof to verify, so it is reported as rejected.
+fn run(name: &str, circuits: [SpendCircuit; 2], rng: &mut StdRng) {
+    println!("{}:", name);
+    let (pk, vk) = setup(circuits[0].clone(), rng).unwrap();
+
+    for (label, circuit) in ["secret", "r - secret"].into_iter().zip(circuits) {
+        println!("  {:<10} nullifier {}", label, circuit.nullifier);
+        if !check_satisfied(&circuit).unwrap() {
+            println!("  {:<10} does not satisfy the circuit, rejected", "");
+            continue;
+        }
+
+        let (root, nullifier) = (circuit.root, circuit.nullifier);
+        let proof = prove(&pk, circuit, rng).unwrap();
+        let valid = verify(&vk, &root, &nullifier, &proof).unwrap();
+        println!(
+            "  {:<10} satisfies the circuit, proof {}",
+            "",
+            if valid { "accepted" } else { "rejected" }
+        );
+    }
+}
+
+fn main() {
+    let rng = &mut StdRng::seed_from_u64(0u64);
+
+    let params = poseidon_parameters::poseidon_parameters();
+    let (leaves, secrets) = generate_leaves(1 << HEIGHT, rng);
+    let (tree, _) =
+        build_tree(&params, &params, &leaves, HEIGHT).expect("the leaves fit in the tree");
+
+    // `r - secret` for the MNT6-753 scalar modulus `r` gives the negated
+    // public key, with the same x-coordinate and so the same leaf. `r` is
+    // below the MNT4-753 modulus, so it is also a valid base field witness.
+    let secret = secrets[INDEX];
+    let alias = scalar_to_fr(-fr_to_scalar(secret).unwrap());
+
+    run(
+        "legacy circuit",
+        [spend(&tree, secret, false), spend(&tree, alias, false)],
+        rng,
+    );
+    run(
+        "strict circuit",
+        [spend(&tree, secret, true), spend(&tree, alias, true)],
+        rng,
+    );
+}
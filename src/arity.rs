@@ -0,0 +1,361 @@
+use ark_crypto_primitives::crh::{CRHScheme, CRHSchemeGadget};
+use ark_crypto_primitives::merkle_tree::Path;
+use ark_ff::PrimeField;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{Namespace, SynthesisError};
+use ark_std::borrow::Borrow;
+use ark_std::marker::PhantomData;
+
+use crate::hash_family::{HashFamily, PoseidonFamily};
+use crate::tree::TreeError;
+use crate::{path_height, ConstraintF, MerklePathVar, MerkleTreeParams};
+
+/// Children per inner node of the tree a [`crate::SpendCircuit`] opens a leaf
+/// of, and the path that opens it natively and in-circuit.
+///
+/// arkworks' `Config` and `PathVar` only describe binary trees, so
+/// [`Binary`], the default, is the arkworks tree over
+/// [`MerkleTreeParams`], and [`Wide`] is this crate's tree with more
+/// children per node.
+pub trait Arity<F: PrimeField, H: HashFamily<F>>: Clone + 'static {
+    const ARITY: usize;
+
+    type Path: Clone;
+    type PathVar: AllocVar<Self::Path, F>;
+
+    /// The height of the tree `path` opens a leaf of, counted as in
+    /// [`path_height`].
+    fn path_height(path: &Self::Path) -> usize;
+
+    /// The index of the opened leaf as little-endian bits.
+    fn leaf_position(path: &Self::PathVar) -> Result<Vec<Boolean<F>>, SynthesisError>;
+
+    fn verify_membership(
+        path: &Self::PathVar,
+        leaf_params: &H::ParametersVar,
+        two_to_one_params: &H::ParametersVar,
+        root: &FpVar<F>,
+        leaf: &[FpVar<F>],
+    ) -> Result<Boolean<F>, SynthesisError>;
+}
+
+/// Two children per node: the arkworks tree, which the puzzle fixtures use.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Binary;
+
+impl<F: PrimeField, H: HashFamily<F>> Arity<F, H> for Binary {
+    const ARITY: usize = 2;
+
+    type Path = Path<MerkleTreeParams<F, H>>;
+    type PathVar = MerklePathVar<F, H>;
+
+    fn path_height(path: &Self::Path) -> usize {
+        path_height(path)
+    }
+
+    fn leaf_position(path: &Self::PathVar) -> Result<Vec<Boolean<F>>, SynthesisError> {
+        Ok(path.get_leaf_position())
+    }
+
+    fn verify_membership(
+        path: &Self::PathVar,
+        leaf_params: &H::ParametersVar,
+        two_to_one_params: &H::ParametersVar,
+        root: &FpVar<F>,
+        leaf: &[FpVar<F>],
+    ) -> Result<Boolean<F>, SynthesisError> {
+        path.verify_membership(leaf_params, two_to_one_params, root, leaf)
+    }
+}
+
+/// `N` children per node, in a [`WideMerkleTree`]. `N` must be a power of
+/// two, so that each level's position is a whole number of bits.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Wide<const N: usize>;
+
+impl<F: PrimeField, H: HashFamily<F>, const N: usize> Arity<F, H> for Wide<N> {
+    const ARITY: usize = N;
+
+    type Path = WidePath<F, N>;
+    type PathVar = WidePathVar<F, N>;
+
+    fn path_height(path: &Self::Path) -> usize {
+        path.siblings.len() + 1
+    }
+
+    fn leaf_position(path: &Self::PathVar) -> Result<Vec<Boolean<F>>, SynthesisError> {
+        Ok(path.positions.concat())
+    }
+
+    fn verify_membership(
+        path: &Self::PathVar,
+        leaf_params: &H::ParametersVar,
+        two_to_one_params: &H::ParametersVar,
+        root: &FpVar<F>,
+        leaf: &[FpVar<F>],
+    ) -> Result<Boolean<F>, SynthesisError> {
+        path.verify_membership::<H>(leaf_params, two_to_one_params, root, leaf)
+    }
+}
+
+/// The position bits of one level of a [`Wide`] tree.
+const fn level_bits(arity: usize) -> usize {
+    arity.trailing_zeros() as usize
+}
+
+fn check_arity(arity: usize) -> Result<(), TreeError> {
+    if arity < 2 || !arity.is_power_of_two() {
+        return Err(TreeError::UnsupportedArity { arity });
+    }
+    Ok(())
+}
+
+/// An inner node of a [`Wide`] tree: the leaf hash, under the two-to-one
+/// parameters, of its children behind the arity as a domain tag. Without the
+/// tag a node would also be the digest of the leaf made of its children, and
+/// as absorbing a zero leaves a sponge unchanged, of that leaf less its
+/// trailing zeros. With it, the leaf would have to start with the arity
+/// rather than a public key coordinate.
+fn inner_node<F: PrimeField, H: HashFamily<F>>(
+    params: &H::Parameters,
+    children: &[F],
+) -> Result<F, ark_crypto_primitives::Error> {
+    let mut input = vec![F::from(children.len() as u64)];
+    input.extend_from_slice(children);
+    H::Leaf::evaluate(params, input.as_slice())
+}
+
+fn inner_node_var<F: PrimeField, H: HashFamily<F>>(
+    params: &H::ParametersVar,
+    children: &[FpVar<F>],
+) -> Result<FpVar<F>, SynthesisError> {
+    let mut input = vec![FpVar::constant(F::from(children.len() as u64))];
+    input.extend_from_slice(children);
+    H::LeafGadget::evaluate(params, &input)
+}
+
+/// A Merkle tree whose inner nodes hash `N` children, dividing the depth of
+/// the binary [`crate::MntMerkleTree`] over the same leaves by `log2(N)`.
+/// Leaves hash as they do in the binary tree, and inner nodes hash their
+/// children behind the arity, which keeps them apart from leaf digests.
+pub struct WideMerkleTree<F: PrimeField = ConstraintF, const N: usize = 4, H = PoseidonFamily> {
+    /// `levels[0]` holds the leaf digests and the last level the root.
+    levels: Vec<Vec<F>>,
+    _hash: PhantomData<H>,
+}
+
+/// The `N - 1` siblings at each level, from the leaf up, and the leaf's
+/// index.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WidePath<F: PrimeField = ConstraintF, const N: usize = 4> {
+    pub index: usize,
+    pub siblings: Vec<Vec<F>>,
+}
+
+impl<F: PrimeField, const N: usize, H: HashFamily<F>> WideMerkleTree<F, N, H> {
+    /// Builds the tree of `leaves`, whose number must be a power of `N` of
+    /// at least `N`.
+    pub fn new(
+        leaf_params: &H::Parameters,
+        two_to_one_params: &H::Parameters,
+        leaves: &[Vec<F>],
+    ) -> Result<Self, TreeError> {
+        check_arity(N)?;
+        let mut size = leaves.len();
+        while size > 1 && size.is_multiple_of(N) {
+            size /= N;
+        }
+        if leaves.len() < N || size != 1 {
+            return Err(TreeError::UnpaddedWideLeafCount {
+                leaves: leaves.len(),
+                arity: N,
+            });
+        }
+
+        let digests = leaves
+            .iter()
+            .map(|leaf| H::Leaf::evaluate(leaf_params, leaf.as_slice()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(TreeError::Crypto)?;
+
+        let mut levels = vec![digests];
+        while levels.last().unwrap().len() > 1 {
+            let level = levels
+                .last()
+                .unwrap()
+                .chunks(N)
+                .map(|children| inner_node::<F, H>(two_to_one_params, children))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(TreeError::Crypto)?;
+            levels.push(level);
+        }
+
+        Ok(Self {
+            levels,
+            _hash: PhantomData,
+        })
+    }
+
+    pub fn root(&self) -> F {
+        self.levels.last().unwrap()[0]
+    }
+
+    /// Levels of inner nodes, so a tree of `N^depth` leaves has depth `depth`.
+    pub fn depth(&self) -> usize {
+        self.levels.len() - 1
+    }
+
+    pub fn generate_proof(&self, index: usize) -> Result<WidePath<F, N>, TreeError> {
+        let capacity = self.levels[0].len();
+        if index >= capacity {
+            return Err(TreeError::IndexOutOfRange { index, capacity });
+        }
+
+        let mut siblings = Vec::with_capacity(self.depth());
+        let mut position = index;
+        for level in &self.levels[..self.depth()] {
+            let first = position - position % N;
+            siblings.push(
+                level[first..first + N]
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| first + i != position)
+                    .map(|(_, node)| *node)
+                    .collect(),
+            );
+            position /= N;
+        }
+
+        Ok(WidePath { index, siblings })
+    }
+}
+
+/// The children of a node, with `node` at `position` among `siblings`, of
+/// which there must be at least `position`.
+fn children<T: Clone>(node: &T, siblings: &[T], position: usize) -> Vec<T> {
+    let mut children = siblings.to_vec();
+    children.insert(position, node.clone());
+    children
+}
+
+impl<F: PrimeField, const N: usize> WidePath<F, N> {
+    /// Whether the path leads from `leaf` to `root`. A path with a level of
+    /// other than `N - 1` siblings leads nowhere, as it does in-circuit.
+    pub fn verify<H: HashFamily<F>>(
+        &self,
+        leaf_params: &H::Parameters,
+        two_to_one_params: &H::Parameters,
+        root: &F,
+        leaf: &[F],
+    ) -> Result<bool, ark_crypto_primitives::Error> {
+        let mut node = H::Leaf::evaluate(leaf_params, leaf)?;
+        let mut position = self.index;
+        for siblings in &self.siblings {
+            if siblings.len() != N - 1 {
+                return Ok(false);
+            }
+            let children = children(&node, siblings, position % N);
+            node = inner_node::<F, H>(two_to_one_params, &children)?;
+            position /= N;
+        }
+
+        Ok(node == *root)
+    }
+}
+
+/// A [`WidePath`] allocated in-circuit, with each level's position as
+/// `log2(N)` little-endian bits.
+pub struct WidePathVar<F: PrimeField, const N: usize> {
+    positions: Vec<Vec<Boolean<F>>>,
+    siblings: Vec<Vec<FpVar<F>>>,
+}
+
+impl<F: PrimeField, const N: usize> AllocVar<WidePath<F, N>, F> for WidePathVar<F, N> {
+    fn new_variable<T: Borrow<WidePath<F, N>>>(
+        cs: impl Into<Namespace<F>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        check_arity(N).map_err(|_| SynthesisError::Unsatisfiable)?;
+        let ns = cs.into();
+        let cs = ns.cs();
+        let path = f()?;
+        let path = path.borrow();
+
+        let mut positions = Vec::with_capacity(path.siblings.len());
+        let mut siblings = Vec::with_capacity(path.siblings.len());
+        let mut position = path.index;
+        for level in &path.siblings {
+            if level.len() != N - 1 {
+                return Err(SynthesisError::Unsatisfiable);
+            }
+            let digit = position % N;
+            positions.push(
+                (0..level_bits(N))
+                    .map(|bit| {
+                        Boolean::new_variable(cs.clone(), || Ok((digit >> bit) & 1 == 1), mode)
+                    })
+                    .collect::<Result<_, _>>()?,
+            );
+            siblings.push(
+                level
+                    .iter()
+                    .map(|node| FpVar::new_variable(cs.clone(), || Ok(*node), mode))
+                    .collect::<Result<_, _>>()?,
+            );
+            position /= N;
+        }
+
+        Ok(Self {
+            positions,
+            siblings,
+        })
+    }
+}
+
+/// `options[i]` for the `i` with little-endian `bits`, as a tree of selects.
+fn select<F: PrimeField>(
+    bits: &[Boolean<F>],
+    options: &[FpVar<F>],
+) -> Result<FpVar<F>, SynthesisError> {
+    match bits.split_first() {
+        None => Ok(options[0].clone()),
+        Some((low, high)) => {
+            let halved = options
+                .chunks(2)
+                .map(|pair| low.select(&pair[1], &pair[0]))
+                .collect::<Result<Vec<_>, _>>()?;
+            select(high, &halved)
+        }
+    }
+}
+
+impl<F: PrimeField, const N: usize> WidePathVar<F, N> {
+    /// The in-circuit counterpart of [`WidePath::verify`].
+    pub fn verify_membership<H: HashFamily<F>>(
+        &self,
+        leaf_params: &H::ParametersVar,
+        two_to_one_params: &H::ParametersVar,
+        root: &FpVar<F>,
+        leaf: &[FpVar<F>],
+    ) -> Result<Boolean<F>, SynthesisError> {
+        let mut node = H::LeafGadget::evaluate(leaf_params, leaf)?;
+        for (bits, siblings) in self.positions.iter().zip(&self.siblings) {
+            // Every arrangement of the children, picked by the position bits
+            let arrangements: Vec<_> = (0..N)
+                .map(|position| children(&node, siblings, position))
+                .collect();
+            let children = (0..N)
+                .map(|i| {
+                    let options: Vec<_> = arrangements.iter().map(|a| a[i].clone()).collect();
+                    select(bits, &options)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            node = inner_node_var::<F, H>(two_to_one_params, &children)?;
+        }
+
+        node.is_eq(root)
+    }
+}
@@ -0,0 +1,108 @@
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{PrimeField, UniformRand, Zero};
+use ark_groth16::{Proof, VerifyingKey};
+use ark_std::rand::{CryptoRng, RngCore};
+
+#[cfg(feature = "prover")]
+use ark_groth16::ProvingKey;
+#[cfg(feature = "prover")]
+use ark_relations::r1cs::SynthesisError;
+
+#[cfg(feature = "prover")]
+use crate::{prove_with_seed, CurveCycle, HashFamily, SpendCircuit};
+
+/// Verifies every proof against `vk` with a single multi-pairing.
+///
+/// Each proof's equation `e(A, B) = e(alpha, beta) e(L, gamma) e(C, delta)`
+/// is scaled by a fresh random `r` and the equations are multiplied together,
+/// so the pairings with `beta`, `gamma` and `delta` are shared. A batch with an
+/// invalid proof only passes with negligible probability, provided `rng` is
+/// unpredictable to whoever produced the proofs.
+///
+/// Rejects the batch if any proof has the wrong number of public inputs. An
+/// empty batch is accepted.
+pub fn batch_verify<E: Pairing, R: RngCore + CryptoRng>(
+    vk: &VerifyingKey<E>,
+    proofs_and_inputs: &[(Proof<E>, Vec<E::ScalarField>)],
+    rng: &mut R,
+) -> bool {
+    let mut g1 = Vec::with_capacity(proofs_and_inputs.len() + 3);
+    let mut g2 = Vec::with_capacity(proofs_and_inputs.len() + 3);
+
+    let mut r_sum = E::ScalarField::zero();
+    let mut inputs_acc = E::G1::zero();
+    let mut c_acc = E::G1::zero();
+
+    for (proof, public_inputs) in proofs_and_inputs {
+        if public_inputs.len() + 1 != vk.gamma_abc_g1.len() {
+            return false;
+        }
+
+        let mut inputs = vk.gamma_abc_g1[0].into_group();
+        for (input, base) in public_inputs.iter().zip(&vk.gamma_abc_g1[1..]) {
+            inputs += base.mul_bigint(input.into_bigint());
+        }
+
+        let r = E::ScalarField::rand(rng);
+        r_sum += r;
+        inputs_acc += inputs * r;
+        c_acc += proof.c * r;
+
+        g1.push((proof.a * r).into_affine());
+        g2.push(proof.b);
+    }
+
+    // Everything but the `e(A, B)` terms moves to the left-hand side
+    g1.push((-inputs_acc).into_affine());
+    g2.push(vk.gamma_g2);
+    g1.push((-c_acc).into_affine());
+    g2.push(vk.delta_g2);
+    g1.push((vk.alpha_g1 * -r_sum).into_affine());
+    g2.push(vk.beta_g2);
+
+    E::multi_pairing(g1, g2).is_zero()
+}
+
+/// Proves `circuits[i]` with `seeds[i]` as [`prove_with_seed`] does, one
+/// after the other.
+///
+/// Panics if there are not as many seeds as circuits.
+#[cfg(feature = "prover")]
+pub fn prove_batch_sequential<C: CurveCycle, H: HashFamily<C::ConstraintF>>(
+    pk: &ProvingKey<C::Pairing>,
+    circuits: Vec<SpendCircuit<C, H>>,
+    seeds: &[u64],
+) -> Result<Vec<Proof<C::Pairing>>, SynthesisError> {
+    assert_eq!(circuits.len(), seeds.len(), "one seed per circuit");
+
+    circuits
+        .into_iter()
+        .zip(seeds)
+        .map(|(circuit, seed)| prove_with_seed(pk, circuit, *seed))
+        .collect()
+}
+
+/// Like [`prove_batch_sequential`], proving the circuits on the rayon thread
+/// pool. Each proof only depends on its own circuit and seed, so the proofs
+/// are the same as the sequential ones. The proving key is only read, and is
+/// shared by reference.
+#[cfg(feature = "parallel")]
+pub fn prove_batch<C: CurveCycle, H: HashFamily<C::ConstraintF>>(
+    pk: &ProvingKey<C::Pairing>,
+    circuits: Vec<SpendCircuit<C, H>>,
+    seeds: &[u64],
+) -> Result<Vec<Proof<C::Pairing>>, SynthesisError>
+where
+    SpendCircuit<C, H>: Send,
+{
+    use rayon::prelude::*;
+
+    assert_eq!(circuits.len(), seeds.len(), "one seed per circuit");
+
+    circuits
+        .into_par_iter()
+        .zip(seeds)
+        .map(|(circuit, seed)| prove_with_seed(pk, circuit, *seed))
+        .collect()
+}
@@ -0,0 +1,55 @@
+//! Verifies a proof written by `main prove`, for nodes that only verify.
+//!
+//! Only the verifying key, the proof file and the loading and verification
+//! functions are used. Built with just the `verifier` feature, the library
+//! leaves out the circuits, trees and proving behind `prover`, and the
+//! terminal and logging dependencies of `main`. The verifying key is checked
+//! to be made of valid curve points before anything is verified against it.
+//! Exits with 0 if the proof is accepted and 1 otherwise.
+
+use ark_mnt4_753::MNT4_753;
+
+use ark_groth16::VerifyingKey;
+
+use anyhow::Result;
+use clap::Parser;
+
+use std::process;
+
+use puzzle_gamma_ray::{
+    from_file_checked, load_proof, public_inputs_from_strings, verify_public_inputs,
+};
+
+#[derive(Parser)]
+#[command(about = "Verify a gamma-ray spend proof")]
+struct Cli {
+    /// A proof written by `main prove`
+    proof: String,
+    /// A verifying key serialized on its own, as `main write-vk` writes it
+    vk: String,
+    /// The public inputs as decimal integers, in allocation order: the root
+    /// and the nullifier of a spend
+    #[arg(required = true)]
+    public_inputs: Vec<String>,
+}
+
+fn main() {
+    match run(Cli::parse()) {
+        Ok(()) => println!("proof accepted"),
+        Err(e) => {
+            eprintln!("error: {:#}", e);
+            process::exit(1);
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<()> {
+    let vk: VerifyingKey<MNT4_753> = from_file_checked(&cli.vk)?;
+    // The inputs stored with the proof are ignored: the caller says what the
+    // proof has to be valid for
+    let (proof, _) = load_proof::<MNT4_753>(&cli.proof)?;
+
+    let public_inputs = public_inputs_from_strings(&cli.public_inputs)?;
+    verify_public_inputs(&vk, &public_inputs, &proof)?;
+    Ok(())
+}
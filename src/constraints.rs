@@ -0,0 +1,190 @@
+use ark_crypto_primitives::snark::SNARK;
+use ark_ec::pairing::Pairing;
+use ark_ff::{Field, PrimeField};
+use ark_groth16::{Groth16, ProvingKey, VerifyingKey};
+use ark_relations::lc;
+use ark_relations::r1cs::{
+    ConstraintLayer, ConstraintMatrices, ConstraintSynthesizer, ConstraintSystem,
+    ConstraintSystemRef, SynthesisError, Variable,
+};
+use ark_std::rand::{CryptoRng, RngCore};
+
+use tracing::instrument;
+use tracing_subscriber::layer::SubscriberExt;
+
+use std::fmt;
+
+use crate::{CurveCycle, HashFamily, SpendCircuit};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConstraintStats {
+    pub constraints: usize,
+    pub witness_variables: usize,
+    /// Excludes the constant `1` instance variable.
+    pub public_inputs: usize,
+}
+
+impl fmt::Display for ConstraintStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "constraints:       {}", self.constraints)?;
+        writeln!(f, "witness variables: {}", self.witness_variables)?;
+        write!(f, "public inputs:     {}", self.public_inputs)
+    }
+}
+
+/// Synthesizes a copy of `circuit` into a fresh constraint system and
+/// reports its size.
+#[instrument(skip_all, fields(constraints))]
+pub fn count_constraints<C: CurveCycle + Clone, H: HashFamily<C::ConstraintF>>(
+    circuit: &SpendCircuit<C, H>,
+) -> Result<ConstraintStats, SynthesisError> {
+    let cs = ConstraintSystem::<C::ConstraintF>::new_ref();
+    circuit.clone().generate_constraints(cs.clone())?;
+
+    tracing::Span::current().record("constraints", cs.num_constraints());
+
+    Ok(ConstraintStats {
+        constraints: cs.num_constraints(),
+        witness_variables: cs.num_witness_variables(),
+        public_inputs: cs.num_instance_variables() - 1,
+    })
+}
+
+/// Synthesizes a copy of `circuit` and reports whether its witness satisfies
+/// every constraint, without running any of the Groth16 setup or prover.
+pub fn check_satisfied<C: CurveCycle + Clone, H: HashFamily<C::ConstraintF>>(
+    circuit: &SpendCircuit<C, H>,
+) -> Result<bool, SynthesisError> {
+    let cs = ConstraintSystem::<C::ConstraintF>::new_ref();
+    circuit.clone().generate_constraints(cs.clone())?;
+    cs.is_satisfied()
+}
+
+/// Synthesizes a copy of `circuit` and returns the namespace path of the
+/// first unsatisfied constraint, such as `.../nullifier_check/...`, or `None` if
+/// every constraint holds. Namespaces are only recorded while a
+/// [`ConstraintLayer`] is installed, so this runs synthesis under one.
+pub fn synthesize_and_report<C: CurveCycle + Clone, H: HashFamily<C::ConstraintF>>(
+    circuit: &SpendCircuit<C, H>,
+) -> Result<Option<String>, SynthesisError> {
+    let subscriber = tracing_subscriber::registry().with(ConstraintLayer::default());
+
+    tracing::subscriber::with_default(subscriber, || {
+        let cs = ConstraintSystem::<C::ConstraintF>::new_ref();
+        circuit.clone().generate_constraints(cs.clone())?;
+        cs.which_is_unsatisfied()
+    })
+}
+
+/// Synthesizes a copy of `circuit` and returns its A, B and C matrices. Row
+/// `i` of each holds the `(coefficient, variable)` terms of constraint `i`,
+/// where variables below `num_instance_variables` are the instance, starting
+/// with the constant `1`, and the rest the witness.
+pub fn constraint_matrices<C: CurveCycle + Clone, H: HashFamily<C::ConstraintF>>(
+    circuit: &SpendCircuit<C, H>,
+) -> Result<ConstraintMatrices<C::ConstraintF>, SynthesisError> {
+    let cs = ConstraintSystem::<C::ConstraintF>::new_ref();
+    circuit.clone().generate_constraints(cs.clone())?;
+    // Inlines the symbolic linear combinations into the rows
+    cs.finalize();
+
+    Ok(cs
+        .to_matrices()
+        .expect("a fresh constraint system constructs matrices"))
+}
+
+/// Whether `instance` (starting with the constant `1`) and `witness` satisfy
+/// every constraint of `matrices`, as `ConstraintSystem::is_satisfied` would
+/// for the system they were taken from.
+pub fn matrices_satisfied<F: Field>(
+    matrices: &ConstraintMatrices<F>,
+    instance: &[F],
+    witness: &[F],
+) -> bool {
+    if instance.len() != matrices.num_instance_variables
+        || witness.len() != matrices.num_witness_variables
+    {
+        return false;
+    }
+
+    let assignment: Vec<F> = instance.iter().chain(witness).copied().collect();
+    let eval = |row: &[(F, usize)]| {
+        row.iter()
+            .map(|(coefficient, variable)| *coefficient * assignment[*variable])
+            .sum::<F>()
+    };
+
+    matrices
+        .a
+        .iter()
+        .zip(&matrices.b)
+        .zip(&matrices.c)
+        .all(|((a, b), c)| eval(a) * eval(b) == eval(c))
+}
+
+/// The matrices of a circuit, taken once with [`constraint_matrices`] and
+/// replayed for every setup after, so regenerating keys skips synthesizing
+/// the gadgets.
+///
+/// This relies on the shape of a circuit not depending on its witness, which
+/// holds for every circuit in this crate: the same options give the same
+/// constraints whatever the secret. A circuit that branched on its witness
+/// would need a new shape for each one, and keys from a stale shape would
+/// reject its proofs.
+#[derive(Clone)]
+pub struct CircuitShape<F: Field> {
+    matrices: ConstraintMatrices<F>,
+}
+
+impl<F: PrimeField> CircuitShape<F> {
+    pub fn of<C: CurveCycle<ConstraintF = F> + Clone, H: HashFamily<F>>(
+        circuit: &SpendCircuit<C, H>,
+    ) -> Result<Self, SynthesisError> {
+        Ok(Self {
+            matrices: constraint_matrices(circuit)?,
+        })
+    }
+
+    pub fn matrices(&self) -> &ConstraintMatrices<F> {
+        &self.matrices
+    }
+
+    /// Runs the Groth16 setup for the shape, giving the keys
+    /// [`crate::setup`] would for the circuit it was taken from.
+    pub fn setup<E: Pairing<ScalarField = F>, R: RngCore + CryptoRng>(
+        &self,
+        rng: &mut R,
+    ) -> Result<(ProvingKey<E>, VerifyingKey<E>), SynthesisError> {
+        Groth16::<E>::circuit_specific_setup(self.clone(), rng)
+    }
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for CircuitShape<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let matrices = self.matrices;
+
+        // Setup never reads the assignment, so every variable is zero
+        for _ in 1..matrices.num_instance_variables {
+            cs.new_input_variable(|| Ok(F::zero()))?;
+        }
+        for _ in 0..matrices.num_witness_variables {
+            cs.new_witness_variable(|| Ok(F::zero()))?;
+        }
+
+        let variable = |index: usize| match index {
+            0 => Variable::One,
+            index if index < matrices.num_instance_variables => Variable::Instance(index),
+            index => Variable::Witness(index - matrices.num_instance_variables),
+        };
+        let row = |terms: &[(F, usize)]| {
+            terms.iter().fold(lc!(), |lc, (coefficient, index)| {
+                lc + (*coefficient, variable(*index))
+            })
+        };
+
+        for ((a, b), c) in matrices.a.iter().zip(&matrices.b).zip(&matrices.c) {
+            cs.enforce_constraint(row(a), row(b), row(c))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,97 @@
+use ark_ff::{BigInteger, PrimeField};
+use ark_mnt4_753::Fr as MNT4BigFr;
+use ark_mnt6_753::Fr as MNT6BigFr;
+use ark_std::str::FromStr;
+use num_bigint::BigUint;
+
+use std::fmt;
+
+/// The value is not below the modulus of the field it was converted into.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NotRepresentable;
+
+impl fmt::Display for NotRepresentable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "value is not below the modulus of the target field")
+    }
+}
+
+impl std::error::Error for NotRepresentable {}
+
+/// The public input at `index` is not a canonical decimal field element.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidPublicInput {
+    pub index: usize,
+    pub value: String,
+}
+
+impl fmt::Display for InvalidPublicInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "public input {} is {:?}, which is not a canonical decimal field element",
+            self.index, self.value
+        )
+    }
+}
+
+impl std::error::Error for InvalidPublicInput {}
+
+/// Reads a constraint field element as the scalar with the same integer.
+///
+/// The MNT6-753 scalar modulus `r` is below the MNT4-753 modulus `p`, so this
+/// is lossless for `x < r` and fails for the `p - r` elements from `r` up.
+/// The circuit multiplies by the bits of `x` without reducing them, so such
+/// an `x` owns the same key as `x - r`: a wallet should reject secrets this
+/// refuses.
+pub fn fr_to_scalar(x: MNT4BigFr) -> Result<MNT6BigFr, NotRepresentable> {
+    MNT6BigFr::from_bigint(x.into_bigint()).ok_or(NotRepresentable)
+}
+
+/// `x mod r`, the scalar a multiplication by the bits of `x` computes with.
+/// Unlike [`fr_to_scalar`] this never fails, but two secrets `r` apart map
+/// to the same scalar.
+pub fn fr_to_scalar_reduced(x: MNT4BigFr) -> MNT6BigFr {
+    MNT6BigFr::from_le_bytes_mod_order(&x.into_bigint().to_bytes_le())
+}
+
+/// Reads a scalar as the constraint field element with the same integer,
+/// which is always lossless since `r < p`.
+pub fn scalar_to_fr(x: MNT6BigFr) -> MNT4BigFr {
+    MNT4BigFr::from_bigint(x.into_bigint()).expect("the scalar modulus is below the base modulus")
+}
+
+/// The public inputs as decimal integers, the form verifiers outside Rust
+/// usually exchange them in. [`public_inputs_from_strings`] reads them back.
+pub fn public_inputs_to_strings(public_inputs: &[MNT4BigFr]) -> Vec<String> {
+    public_inputs.iter().map(decimal).collect()
+}
+
+/// `element` as a decimal integer. `Display` on a field element prints zero
+/// as the empty string, so this is what every decimal encoding goes through.
+pub(crate) fn decimal(element: &MNT4BigFr) -> String {
+    BigUint::from(*element).to_string()
+}
+
+/// Parses public inputs written by [`public_inputs_to_strings`]. Only the
+/// canonical decimal form of each element is accepted, so a value at or above
+/// the modulus is refused rather than read as a different input.
+pub fn public_inputs_from_strings<S: AsRef<str>>(
+    strings: &[S],
+) -> Result<Vec<MNT4BigFr>, InvalidPublicInput> {
+    strings
+        .iter()
+        .enumerate()
+        .map(|(index, value)| {
+            let value = value.as_ref();
+            MNT4BigFr::from_str(value)
+                .ok()
+                // `from_str` silently reduces, so require the canonical form
+                .filter(|element| decimal(element) == value)
+                .ok_or_else(|| InvalidPublicInput {
+                    index,
+                    value: value.to_string(),
+                })
+        })
+        .collect()
+}
@@ -0,0 +1,75 @@
+use ark_crypto_primitives::sponge::Absorb;
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ff::PrimeField;
+use ark_mnt4_753::{Fr as MNT4BigFr, MNT4_753};
+use ark_mnt6_753::G1Affine;
+use ark_mnt6_753::{constraints::G1Var, Fr as MNT6BigFr};
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+
+use crate::PublicKeyVar;
+
+/// A pair of curves where the base field of `G1Affine` is the scalar field of
+/// `Pairing`, so public keys on `G1Affine` can be computed natively inside a
+/// Groth16 circuit over `Pairing`.
+pub trait CurveCycle {
+    type ConstraintF: PrimeField + Absorb;
+    type ScalarField: PrimeField;
+    type G1Affine: AffineRepr<BaseField = Self::ConstraintF, ScalarField = Self::ScalarField>;
+    type Pairing: Pairing<ScalarField = Self::ConstraintF>;
+
+    /// Native counterpart of [`Self::public_key_var`]. The secret's bits are
+    /// used as the scalar without reducing them into `ScalarField` first.
+    fn public_key(secret: Self::ConstraintF) -> Self::G1Affine {
+        Self::G1Affine::generator()
+            .mul_bigint(secret.into_bigint())
+            .into_affine()
+    }
+
+    /// Computes `G1Affine::generator() * secret_bits` in-circuit and returns
+    /// the affine `(x, y)` coordinates.
+    fn public_key_var(
+        cs: ConstraintSystemRef<Self::ConstraintF>,
+        secret_bits: &[Boolean<Self::ConstraintF>],
+    ) -> Result<PublicKeyVar<Self::ConstraintF>, SynthesisError>;
+}
+
+#[derive(Clone)]
+pub struct Mnt753Cycle;
+
+impl CurveCycle for Mnt753Cycle {
+    type ConstraintF = MNT4BigFr;
+    type ScalarField = MNT6BigFr;
+    type G1Affine = G1Affine;
+    type Pairing = MNT4_753;
+
+    fn public_key_var(
+        cs: ConstraintSystemRef<Self::ConstraintF>,
+        secret_bits: &[Boolean<Self::ConstraintF>],
+    ) -> Result<PublicKeyVar<Self::ConstraintF>, SynthesisError> {
+        let base = G1Var::new_constant(ark_relations::ns!(cs, "base"), G1Affine::generator())?;
+        let pk = base.scalar_mul_le(secret_bits.iter())?.to_affine()?;
+
+        Ok((pk.x, pk.y))
+    }
+}
+
+/// The public key whose x-coordinate is the leaf owned by `secret`.
+pub fn public_key(secret: MNT4BigFr) -> G1Affine {
+    Mnt753Cycle::public_key(secret)
+}
+
+/// The points a leaf could have come from. A leaf only commits to `pk.x`, so
+/// a key and its negation, which belong to `secret` and `-secret`, share it;
+/// this returns both such points, the single point with `y = 0`, or none if
+/// `x` is not on the curve.
+pub fn points_from_x(x: MNT4BigFr) -> Vec<G1Affine> {
+    match G1Affine::get_ys_from_x_unchecked(x) {
+        Some((y, neg_y)) if y == neg_y => vec![G1Affine::new_unchecked(x, y)],
+        Some((y, neg_y)) => vec![
+            G1Affine::new_unchecked(x, y),
+            G1Affine::new_unchecked(x, neg_y),
+        ],
+        None => Vec::new(),
+    }
+}
@@ -0,0 +1,94 @@
+use ark_crypto_primitives::crh::{
+    poseidon, CRHScheme, CRHSchemeGadget, TwoToOneCRHScheme, TwoToOneCRHSchemeGadget,
+};
+use ark_crypto_primitives::merkle_tree::constraints::DigestVarConverter;
+use ark_crypto_primitives::merkle_tree::{DigestConverter, IdentityDigestConverter};
+use ark_crypto_primitives::sponge::Absorb;
+use ark_ff::PrimeField;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+
+use crate::rescue::{
+    RescueCRH, RescueCRHGadget, RescueConfig, RescueConfigVar, RescueTwoToOneCRH,
+    RescueTwoToOneCRHGadget,
+};
+
+/// The hash behind the leaves, the inner nodes, the nullifier and note
+/// commitments, natively and in-circuit. Both hashes share one parameter
+/// type, as the puzzle hashes leaves and inner nodes with the same table.
+pub trait HashFamily<F: PrimeField>: Clone + 'static {
+    type Parameters: Clone + Send + Sync;
+    type ParametersVar: AllocVar<Self::Parameters, F> + Clone;
+
+    type Leaf: CRHScheme<Input = [F], Output = F, Parameters = Self::Parameters>;
+    type LeafGadget: CRHSchemeGadget<
+        Self::Leaf,
+        F,
+        InputVar = [FpVar<F>],
+        OutputVar = FpVar<F>,
+        ParametersVar = Self::ParametersVar,
+    >;
+
+    type Compress: TwoToOneCRHScheme<Input = F, Output = F, Parameters = Self::Parameters>;
+    type CompressGadget: TwoToOneCRHSchemeGadget<
+        Self::Compress,
+        F,
+        InputVar = FpVar<F>,
+        OutputVar = FpVar<F>,
+        ParametersVar = Self::ParametersVar,
+    >;
+}
+
+/// How a leaf digest becomes the input of the first inner hash, natively and
+/// in-circuit. Both digests are field elements here, so the only converter
+/// in use is [`IdentityConverter`]; this is the place for another one should
+/// a hash with a different digest type be swapped in.
+pub trait LeafConverter<F: PrimeField>: 'static {
+    type Native: DigestConverter<F, F>;
+    type Gadget: DigestVarConverter<FpVar<F>, FpVar<F>>;
+}
+
+/// Passes the leaf digest through unchanged, as the puzzle's tree does.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct IdentityConverter;
+
+impl<F: PrimeField> LeafConverter<F> for IdentityConverter {
+    type Native = IdentityDigestConverter<F>;
+    type Gadget = IdentityDigestConverter<FpVar<F>>;
+}
+
+/// The Poseidon sponge of [`crate::poseidon_parameters`], which the puzzle
+/// fixtures were produced with.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PoseidonFamily;
+
+impl<F: PrimeField + Absorb> HashFamily<F> for PoseidonFamily {
+    type Parameters = <poseidon::CRH<F> as CRHScheme>::Parameters;
+    type ParametersVar = <poseidon::constraints::CRHGadget<F> as CRHSchemeGadget<
+        poseidon::CRH<F>,
+        F,
+    >>::ParametersVar;
+
+    type Leaf = poseidon::CRH<F>;
+    type LeafGadget = poseidon::constraints::CRHGadget<F>;
+
+    type Compress = poseidon::TwoToOneCRH<F>;
+    type CompressGadget = poseidon::constraints::TwoToOneCRHGadget<F>;
+}
+
+/// The Rescue-Prime sponge of [`crate::rescue`], for comparing circuit
+/// costs. Its digests differ from Poseidon's, so it can't spend the puzzle
+/// fixtures.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RescueFamily;
+
+impl<F: PrimeField> HashFamily<F> for RescueFamily {
+    type Parameters = RescueConfig<F>;
+    type ParametersVar = RescueConfigVar<F>;
+
+    type Leaf = RescueCRH<F>;
+    type LeafGadget = RescueCRHGadget<F>;
+
+    type Compress = RescueTwoToOneCRH<F>;
+    type CompressGadget = RescueTwoToOneCRHGadget<F>;
+}
@@ -0,0 +1,208 @@
+use ark_crypto_primitives::crh::{CRHScheme, TwoToOneCRHScheme};
+use ark_crypto_primitives::merkle_tree::Path;
+
+use std::collections::VecDeque;
+
+use crate::tree::{empty_leaf, TreeError};
+use crate::{CompressH, ConstraintF, LeafH, MntMerkleTreeParams};
+
+type Params = <LeafH as CRHScheme>::Parameters;
+
+/// An append-only tree with the same shape and hashing as [`crate::build_tree`]
+/// that updates a single path per insertion, instead of rebuilding.
+///
+/// Only the nodes above inserted leaves are stored. The rest of each level
+/// is empty subtrees, which all have the same digest, so a tree of height 32
+/// costs no more than the leaves inserted into it.
+///
+/// The last `root_history` roots are kept, so a path generated against a
+/// recent root still verifies after later insertions.
+pub struct IncrementalTree {
+    leaf_params: Params,
+    two_to_one_params: Params,
+    /// `nodes[0]` holds the digests of the inserted leaves, and each level
+    /// above the nodes with an inserted leaf below them, up to the root.
+    nodes: Vec<Vec<ConstraintF>>,
+    /// `empty[level]` is the digest of an empty subtree at `level`.
+    empty: Vec<ConstraintF>,
+    capacity: usize,
+    next_index: usize,
+    roots: VecDeque<ConstraintF>,
+    root_history: usize,
+}
+
+impl IncrementalTree {
+    /// An empty tree with `2^height` positions, all holding
+    /// [`crate::tree::empty_leaf`]. At least one root is always kept.
+    pub fn new(
+        leaf_params: &Params,
+        two_to_one_params: &Params,
+        height: usize,
+        root_history: usize,
+    ) -> Result<Self, TreeError> {
+        if height == 0 {
+            return Err(TreeError::ZeroHeight);
+        }
+        let capacity = u32::try_from(height)
+            .ok()
+            .and_then(|h| 1usize.checked_shl(h))
+            .unwrap_or(usize::MAX);
+
+        let mut node =
+            <LeafH as CRHScheme>::evaluate(leaf_params, empty_leaf()).map_err(TreeError::Crypto)?;
+        let mut empty = vec![node];
+        for level in 1..=height {
+            node = hash_children(two_to_one_params, level, &node, &node)?;
+            empty.push(node);
+        }
+
+        let root_history = root_history.max(1);
+        let mut roots = VecDeque::with_capacity(root_history);
+        roots.push_back(node);
+
+        Ok(Self {
+            leaf_params: leaf_params.clone(),
+            two_to_one_params: two_to_one_params.clone(),
+            nodes: vec![Vec::new(); height + 1],
+            empty,
+            capacity,
+            next_index: 0,
+            roots,
+            root_history,
+        })
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The number of leaves inserted so far.
+    pub fn len(&self) -> usize {
+        self.next_index
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.next_index == 0
+    }
+
+    pub fn root(&self) -> ConstraintF {
+        *self.roots.back().expect("at least one root is kept")
+    }
+
+    /// The kept roots, oldest first.
+    pub fn recent_roots(&self) -> impl Iterator<Item = &ConstraintF> {
+        self.roots.iter()
+    }
+
+    /// Writes `leaf` to the next free position and returns its index.
+    pub fn insert(&mut self, leaf: &[ConstraintF]) -> Result<usize, TreeError> {
+        let index = self.next_index;
+        if index == self.capacity() {
+            return Err(TreeError::TooManyLeaves {
+                leaves: index + 1,
+                capacity: self.capacity(),
+            });
+        }
+
+        let digest =
+            <LeafH as CRHScheme>::evaluate(&self.leaf_params, leaf).map_err(TreeError::Crypto)?;
+        self.set(0, index, digest);
+        let mut position = index;
+        for level in 1..self.nodes.len() {
+            position /= 2;
+            let node = hash_children(
+                &self.two_to_one_params,
+                level,
+                &self.node(level - 1, 2 * position),
+                &self.node(level - 1, 2 * position + 1),
+            )?;
+            self.set(level, position, node);
+        }
+
+        self.next_index += 1;
+        if self.roots.len() == self.root_history {
+            self.roots.pop_front();
+        }
+        self.roots.push_back(self.node(self.nodes.len() - 1, 0));
+
+        Ok(index)
+    }
+
+    /// The path to position `index` under the current root, in the layout
+    /// `MntMerkleTree::generate_proof` produces.
+    pub fn generate_proof(&self, index: usize) -> Result<Path<MntMerkleTreeParams>, TreeError> {
+        if index >= self.capacity() {
+            return Err(TreeError::IndexOutOfRange {
+                index,
+                capacity: self.capacity(),
+            });
+        }
+
+        // Siblings of the inner nodes on the path, excluding the root
+        let mut position = index / 2;
+        let mut auth_path = Vec::with_capacity(self.nodes.len() - 2);
+        for level in 1..self.nodes.len() - 1 {
+            auth_path.push(self.node(level, position ^ 1));
+            position /= 2;
+        }
+        // arkworks orders the path from the root down
+        auth_path.reverse();
+
+        Ok(Path {
+            leaf_sibling_hash: self.node(0, index ^ 1),
+            auth_path,
+            leaf_index: index,
+        })
+    }
+
+    fn node(&self, level: usize, position: usize) -> ConstraintF {
+        self.nodes[level]
+            .get(position)
+            .copied()
+            .unwrap_or(self.empty[level])
+    }
+
+    /// Leaves are inserted in order, so a level's stored nodes only ever
+    /// change at their end or grow by one.
+    fn set(&mut self, level: usize, position: usize, node: ConstraintF) {
+        let nodes = &mut self.nodes[level];
+        if position == nodes.len() {
+            nodes.push(node);
+        } else {
+            nodes[position] = node;
+        }
+    }
+
+    /// Checks `path` for `leaf` against every kept root.
+    pub fn verify_membership(
+        &self,
+        path: &Path<MntMerkleTreeParams>,
+        leaf: &[ConstraintF],
+    ) -> Result<bool, TreeError> {
+        for root in &self.roots {
+            if path
+                .verify(&self.leaf_params, &self.two_to_one_params, root, leaf)
+                .map_err(TreeError::Crypto)?
+            {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+// Parents of leaves are hashed with `evaluate` and the levels above with
+// `compress`, as in the arkworks tree.
+fn hash_children(
+    params: &Params,
+    level: usize,
+    left: &ConstraintF,
+    right: &ConstraintF,
+) -> Result<ConstraintF, TreeError> {
+    if level == 1 {
+        <CompressH as TwoToOneCRHScheme>::evaluate(params, left, right)
+    } else {
+        <CompressH as TwoToOneCRHScheme>::compress(params, left, right)
+    }
+    .map_err(TreeError::Crypto)
+}
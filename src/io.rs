@@ -0,0 +1,843 @@
+#[cfg(feature = "prover")]
+use ark_crypto_primitives::crh::CRHScheme;
+use ark_ec::pairing::Pairing;
+use ark_groth16::{Proof, ProvingKey, VerifyingKey};
+use ark_relations::r1cs::{ConstraintMatrices, Matrix};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Validate, Write,
+};
+
+use ark_std::str::FromStr;
+
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, Cursor};
+
+use crate::convert::decimal;
+use crate::{ConstraintF, Keys};
+#[cfg(feature = "prover")]
+use crate::{LeafH, MntMerkleTree, TreeError, MAX_TREE_HEIGHT};
+
+/// Leading bytes of a proof file written by [`save_proof`].
+pub const PROOF_MAGIC: &[u8; 4] = b"GRPF";
+pub const PROOF_VERSION: u8 = 1;
+/// Version of proof files written by [`save_proof_compressed`].
+pub const PROOF_VERSION_COMPRESSED: u8 = 2;
+
+/// Leading bytes of a tree file written by [`save_tree`].
+pub const TREE_MAGIC: &[u8; 4] = b"GRTR";
+pub const TREE_VERSION: u8 = 1;
+
+/// Leading bytes of a root snapshot written by [`save_root_snapshot`].
+pub const ROOT_MAGIC: &[u8; 4] = b"GRRT";
+pub const ROOT_VERSION: u8 = 1;
+
+/// Leading bytes of a keys file written by [`save_keys`].
+pub const KEYS_MAGIC: &[u8; 4] = b"GRKY";
+/// Version 1 is the headerless `(pk, vk)` layout of `proof_keys.bin`, which
+/// [`load_keys`] still reads.
+pub const KEYS_VERSION: u8 = 2;
+/// Version of keys files written by [`save_keys_compressed`].
+pub const KEYS_VERSION_COMPRESSED: u8 = 3;
+
+/// Leading bytes of a constraint system file written by [`save_r1cs`].
+pub const R1CS_MAGIC: &[u8; 4] = b"GRCS";
+pub const R1CS_VERSION: u8 = 1;
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+    Empty {
+        path: String,
+    },
+    Deserialize {
+        path: String,
+        source: SerializationError,
+    },
+    BadHeader {
+        path: String,
+    },
+    UnsupportedVersion {
+        path: String,
+        version: u8,
+    },
+    Json {
+        path: String,
+        source: serde_json::Error,
+    },
+    InvalidFieldElement {
+        path: String,
+        value: String,
+    },
+    /// The header of a leaves file claims more leaves than the rest of the
+    /// file can hold.
+    LeafCountTooLarge {
+        path: String,
+        leaves: u64,
+        len: u64,
+    },
+    Tree {
+        path: String,
+        message: String,
+    },
+    /// The tree rebuilt from the stored digests has a different root.
+    RootMismatch {
+        path: String,
+    },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io { path, source } => write!(f, "could not read {}: {}", path, source),
+            LoadError::Empty { path } => write!(f, "{} is empty", path),
+            LoadError::Deserialize { path, source } => {
+                write!(f, "could not deserialize {}: {}", path, source)
+            }
+            LoadError::BadHeader { path } => write!(f, "{} has an unrecognized header", path),
+            LoadError::UnsupportedVersion { path, version } => {
+                write!(f, "{} has unsupported format version {}", path, version)
+            }
+            LoadError::Json { path, source } => write!(f, "could not parse {}: {}", path, source),
+            LoadError::InvalidFieldElement { path, value } => write!(
+                f,
+                "{} contains {:?}, which is not a canonical decimal field element",
+                path, value
+            ),
+            LoadError::LeafCountTooLarge { path, leaves, len } => write!(
+                f,
+                "{} claims {} leaves, more than its {} bytes can hold",
+                path, leaves, len
+            ),
+            LoadError::Tree { path, message } => {
+                write!(f, "could not rebuild the tree in {}: {}", path, message)
+            }
+            LoadError::RootMismatch { path } => {
+                write!(f, "the tree in {} does not match its stored root", path)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LoadError::Io { source, .. } => Some(source),
+            LoadError::Empty { .. } => None,
+            LoadError::Deserialize { source, .. } => Some(source),
+            LoadError::Json { source, .. } => Some(source),
+            LoadError::BadHeader { .. }
+            | LoadError::UnsupportedVersion { .. }
+            | LoadError::InvalidFieldElement { .. }
+            | LoadError::LeafCountTooLarge { .. }
+            | LoadError::Tree { .. }
+            | LoadError::RootMismatch { .. } => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+    Serialize {
+        path: String,
+        source: SerializationError,
+    },
+    Json {
+        path: String,
+        source: serde_json::Error,
+    },
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Io { path, source } => write!(f, "could not write {}: {}", path, source),
+            SaveError::Serialize { path, source } => {
+                write!(f, "could not serialize {}: {}", path, source)
+            }
+            SaveError::Json { path, source } => write!(f, "could not encode {}: {}", path, source),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SaveError::Io { source, .. } => Some(source),
+            SaveError::Serialize { source, .. } => Some(source),
+            SaveError::Json { source, .. } => Some(source),
+        }
+    }
+}
+
+fn read_file(path: &str) -> Result<Vec<u8>, LoadError> {
+    let io_err = |source| LoadError::Io {
+        path: path.to_string(),
+        source,
+    };
+
+    let mut file = File::open(path).map_err(io_err)?;
+    let mut buffer = Vec::new();
+    file.read_to_end(&mut buffer).map_err(io_err)?;
+    if buffer.is_empty() {
+        return Err(LoadError::Empty {
+            path: path.to_string(),
+        });
+    }
+
+    Ok(buffer)
+}
+
+pub fn from_file<T: CanonicalDeserialize>(path: &str) -> Result<T, LoadError> {
+    let buffer = read_file(path)?;
+
+    T::deserialize_uncompressed_unchecked(Cursor::new(&buffer)).map_err(|source| {
+        LoadError::Deserialize {
+            path: path.to_string(),
+            source,
+        }
+    })
+}
+
+/// Like [`from_file`], also checking that every point is on its curve and in
+/// the prime-order subgroup, for keys and proofs from an untrusted source.
+pub fn from_file_checked<T: CanonicalDeserialize>(path: &str) -> Result<T, LoadError> {
+    let buffer = read_file(path)?;
+
+    T::deserialize_uncompressed(Cursor::new(&buffer)).map_err(|source| LoadError::Deserialize {
+        path: path.to_string(),
+        source,
+    })
+}
+
+fn write_file(path: &str, buffer: &[u8]) -> Result<(), SaveError> {
+    File::create(path)
+        .and_then(|mut file| file.write_all(buffer))
+        .map_err(|source| SaveError::Io {
+            path: path.to_string(),
+            source,
+        })
+}
+
+pub fn to_file<T: CanonicalSerialize>(path: &str, value: &T) -> Result<(), SaveError> {
+    let mut buffer = Vec::new();
+    value
+        .serialize_uncompressed(&mut buffer)
+        .map_err(|source| SaveError::Serialize {
+            path: path.to_string(),
+            source,
+        })?;
+
+    write_file(path, &buffer)
+}
+
+/// Reads a value written by [`save_compressed`]. Like [`from_file`], the
+/// points are not checked to be in the prime-order subgroup.
+pub fn load_compressed<T: CanonicalDeserialize>(path: &str) -> Result<T, LoadError> {
+    let buffer = read_file(path)?;
+
+    T::deserialize_compressed_unchecked(Cursor::new(&buffer)).map_err(|source| {
+        LoadError::Deserialize {
+            path: path.to_string(),
+            source,
+        }
+    })
+}
+
+/// Like [`to_file`], storing only the x-coordinate and a sign bit of each
+/// curve point, which roughly halves MNT-753 keys and proofs. Loading pays
+/// for a square root per point.
+pub fn save_compressed<T: CanonicalSerialize>(path: &str, value: &T) -> Result<(), SaveError> {
+    let mut buffer = Vec::new();
+    value
+        .serialize_compressed(&mut buffer)
+        .map_err(|source| SaveError::Serialize {
+            path: path.to_string(),
+            source,
+        })?;
+
+    write_file(path, &buffer)
+}
+
+/// Writes `(pk, vk)` uncompressed, behind a [`KEYS_MAGIC`] and
+/// [`KEYS_VERSION`] header.
+pub fn save_keys<E: Pairing>(
+    path: &str,
+    pk: &ProvingKey<E>,
+    vk: &VerifyingKey<E>,
+) -> Result<(), SaveError> {
+    write_keys(path, pk, vk, Compress::No)
+}
+
+/// Like [`save_keys`], with compressed points and a
+/// [`KEYS_VERSION_COMPRESSED`] header. [`load_keys`] reads both.
+pub fn save_keys_compressed<E: Pairing>(
+    path: &str,
+    pk: &ProvingKey<E>,
+    vk: &VerifyingKey<E>,
+) -> Result<(), SaveError> {
+    write_keys(path, pk, vk, Compress::Yes)
+}
+
+fn write_keys<E: Pairing>(
+    path: &str,
+    pk: &ProvingKey<E>,
+    vk: &VerifyingKey<E>,
+    compress: Compress,
+) -> Result<(), SaveError> {
+    let serialize_err = |source| SaveError::Serialize {
+        path: path.to_string(),
+        source,
+    };
+
+    let mut buffer = Vec::new();
+    buffer.extend_from_slice(KEYS_MAGIC);
+    buffer.push(match compress {
+        Compress::No => KEYS_VERSION,
+        Compress::Yes => KEYS_VERSION_COMPRESSED,
+    });
+    pk.serialize_with_mode(&mut buffer, compress)
+        .map_err(serialize_err)?;
+    vk.serialize_with_mode(&mut buffer, compress)
+        .map_err(serialize_err)?;
+
+    write_file(path, &buffer)
+}
+
+/// Reads keys written by [`save_keys`], [`save_keys_compressed`] or an
+/// earlier version, and returns them with the version they were stored in.
+///
+/// The header says whether the points are compressed. A file without the
+/// [`KEYS_MAGIC`] header is read as version 1, the layout of
+/// `proof_keys.bin` and of [`save_compressed`], with points compressed as
+/// `headerless` says.
+pub fn load_keys<E: Pairing>(path: &str, headerless: Compress) -> Result<(Keys<E>, u8), LoadError> {
+    let buffer = read_file(path)?;
+    let (version, compress, body) = keys_body(path, &buffer, headerless)?;
+
+    let keys =
+        Keys::<E>::deserialize_with_mode(body, compress, Validate::No).map_err(|source| {
+            LoadError::Deserialize {
+                path: path.to_string(),
+                source,
+            }
+        })?;
+    Ok((keys, version))
+}
+
+/// Splits a keys file into its version, how its points are compressed and
+/// the serialized `(pk, vk)`.
+fn keys_body<'a>(
+    path: &str,
+    buffer: &'a [u8],
+    headerless: Compress,
+) -> Result<(u8, Compress, &'a [u8]), LoadError> {
+    let header_len = KEYS_MAGIC.len() + 1;
+    let (version, body) = if buffer.len() >= header_len && &buffer[..KEYS_MAGIC.len()] == KEYS_MAGIC
+    {
+        (buffer[KEYS_MAGIC.len()], &buffer[header_len..])
+    } else {
+        (1, buffer)
+    };
+    let compress = match version {
+        1 => headerless,
+        KEYS_VERSION => Compress::No,
+        KEYS_VERSION_COMPRESSED => Compress::Yes,
+        version => {
+            return Err(LoadError::UnsupportedVersion {
+                path: path.to_string(),
+                version,
+            })
+        }
+    };
+
+    Ok((version, compress, body))
+}
+
+/// Reads only the verifying key of a file [`load_keys`] reads, and the
+/// version it was stored in.
+///
+/// A Groth16 proving key serializes its verifying key first, so this maps
+/// the file and deserializes from the start of the body: only the pages
+/// holding the header and the vk are read from disk, rather than the whole
+/// MNT-753 proving key.
+#[cfg(feature = "mmap")]
+pub fn load_verifying_key_mmap<E: Pairing>(
+    path: &str,
+    headerless: Compress,
+) -> Result<(VerifyingKey<E>, u8), LoadError> {
+    let io_err = |source| LoadError::Io {
+        path: path.to_string(),
+        source,
+    };
+
+    let file = File::open(path).map_err(io_err)?;
+    if file.metadata().map_err(io_err)?.len() == 0 {
+        return Err(LoadError::Empty {
+            path: path.to_string(),
+        });
+    }
+    // SAFETY: the map is only read while this function runs. Truncating the
+    // file meanwhile is undefined behaviour, as for any mapped file, so keys
+    // must not be rewritten while they are being loaded.
+    let map = unsafe { memmap2::Mmap::map(&file) }.map_err(io_err)?;
+    let (version, compress, body) = keys_body(path, &map, headerless)?;
+
+    let vk = VerifyingKey::<E>::deserialize_with_mode(body, compress, Validate::No).map_err(
+        |source| LoadError::Deserialize {
+            path: path.to_string(),
+            source,
+        },
+    )?;
+    Ok((vk, version))
+}
+
+/// Writes `proof` and the public inputs it was produced for, behind a
+/// [`PROOF_MAGIC`] and [`PROOF_VERSION`] header.
+pub fn save_proof<E: Pairing>(
+    path: &str,
+    proof: &Proof<E>,
+    public_inputs: &[E::ScalarField],
+) -> Result<(), SaveError> {
+    write_proof(path, proof, public_inputs, Compress::No)
+}
+
+/// Like [`save_proof`], with the proof's points compressed and a
+/// [`PROOF_VERSION_COMPRESSED`] header. [`load_proof`] reads both.
+pub fn save_proof_compressed<E: Pairing>(
+    path: &str,
+    proof: &Proof<E>,
+    public_inputs: &[E::ScalarField],
+) -> Result<(), SaveError> {
+    write_proof(path, proof, public_inputs, Compress::Yes)
+}
+
+fn write_proof<E: Pairing>(
+    path: &str,
+    proof: &Proof<E>,
+    public_inputs: &[E::ScalarField],
+    compress: Compress,
+) -> Result<(), SaveError> {
+    let serialize_err = |source| SaveError::Serialize {
+        path: path.to_string(),
+        source,
+    };
+
+    let mut buffer = Vec::new();
+    buffer.extend_from_slice(PROOF_MAGIC);
+    buffer.push(match compress {
+        Compress::No => PROOF_VERSION,
+        Compress::Yes => PROOF_VERSION_COMPRESSED,
+    });
+    proof
+        .serialize_with_mode(&mut buffer, compress)
+        .map_err(serialize_err)?;
+    public_inputs
+        .to_vec()
+        .serialize_with_mode(&mut buffer, compress)
+        .map_err(serialize_err)?;
+
+    write_file(path, &buffer)
+}
+
+pub fn load_proof<E: Pairing>(path: &str) -> Result<(Proof<E>, Vec<E::ScalarField>), LoadError> {
+    let buffer = read_file(path)?;
+
+    let header_len = PROOF_MAGIC.len() + 1;
+    if buffer.len() < header_len || &buffer[..PROOF_MAGIC.len()] != PROOF_MAGIC {
+        return Err(LoadError::BadHeader {
+            path: path.to_string(),
+        });
+    }
+    let compress = match buffer[PROOF_MAGIC.len()] {
+        PROOF_VERSION => Compress::No,
+        PROOF_VERSION_COMPRESSED => Compress::Yes,
+        version => {
+            return Err(LoadError::UnsupportedVersion {
+                path: path.to_string(),
+                version,
+            })
+        }
+    };
+
+    <(Proof<E>, Vec<E::ScalarField>)>::deserialize_with_mode(
+        &buffer[header_len..],
+        compress,
+        Validate::Yes,
+    )
+    .map_err(|source| LoadError::Deserialize {
+        path: path.to_string(),
+        source,
+    })
+}
+
+/// Iterates over the leaves of a file in the layout of `leaves.bin`, reading
+/// one leaf at a time instead of the whole file. Like [`from_file`], the
+/// field elements are not checked, but a leaf count the file is too short to
+/// hold is refused up front.
+pub struct LeafReader {
+    path: String,
+    reader: BufReader<File>,
+    remaining: u64,
+}
+
+pub fn read_leaves_streaming(path: &str) -> Result<LeafReader, LoadError> {
+    let file = File::open(path).map_err(|source| LoadError::Io {
+        path: path.to_string(),
+        source,
+    })?;
+    let len = file
+        .metadata()
+        .map_err(|source| LoadError::Io {
+            path: path.to_string(),
+            source,
+        })?
+        .len();
+    let mut reader = BufReader::new(file);
+    let remaining =
+        u64::deserialize_uncompressed(&mut reader).map_err(|source| LoadError::Deserialize {
+            path: path.to_string(),
+            source,
+        })?;
+
+    // Every leaf takes at least its length prefix
+    let min_leaf_len = Vec::<ConstraintF>::new().uncompressed_size() as u64;
+    let count_len = remaining.uncompressed_size() as u64;
+    if remaining > len.saturating_sub(count_len) / min_leaf_len {
+        return Err(LoadError::LeafCountTooLarge {
+            path: path.to_string(),
+            leaves: remaining,
+            len,
+        });
+    }
+
+    Ok(LeafReader {
+        path: path.to_string(),
+        reader,
+        remaining,
+    })
+}
+
+impl LeafReader {
+    /// Leaves not read yet.
+    pub fn remaining(&self) -> u64 {
+        self.remaining
+    }
+}
+
+impl Iterator for LeafReader {
+    type Item = Result<Vec<ConstraintF>, LoadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+
+        let leaf = Vec::<ConstraintF>::deserialize_uncompressed_unchecked(&mut self.reader)
+            .map_err(|source| LoadError::Deserialize {
+                path: self.path.clone(),
+                source,
+            });
+        if leaf.is_err() {
+            // The rest of the file can't be framed past a bad leaf
+            self.remaining = 0;
+        }
+        Some(leaf)
+    }
+}
+
+/// Builds the tree `MntMerkleTree::new` would from the leaves in `path`,
+/// hashing each leaf as it is read so only the digests are kept in memory.
+#[cfg(feature = "prover")]
+pub fn build_tree_streaming(
+    path: &str,
+    leaf_params: &<LeafH as CRHScheme>::Parameters,
+    two_to_one_params: &<LeafH as CRHScheme>::Parameters,
+) -> Result<MntMerkleTree, LoadError> {
+    let tree_err = |e: ark_crypto_primitives::Error| LoadError::Tree {
+        path: path.to_string(),
+        message: e.to_string(),
+    };
+
+    let leaves = read_leaves_streaming(path)?;
+    // Checked before any leaf is hashed, a count too large for `usize` included
+    let count = leaves.remaining().try_into().unwrap_or(usize::MAX);
+    check_leaf_count(path, count)?;
+    let capacity = 1usize << MAX_TREE_HEIGHT;
+    if count > capacity {
+        return Err(LoadError::Tree {
+            path: path.to_string(),
+            message: TreeError::TooManyLeaves {
+                leaves: count,
+                capacity,
+            }
+            .to_string(),
+        });
+    }
+    let mut digests = Vec::with_capacity(count);
+    for leaf in leaves {
+        digests.push(<LeafH as CRHScheme>::evaluate(leaf_params, leaf?).map_err(tree_err)?);
+    }
+
+    MntMerkleTree::new_with_leaf_digest(leaf_params, two_to_one_params, digests).map_err(tree_err)
+}
+
+/// Reads leaves written by [`leaves_to_json`]: an array of leaves, each an
+/// array of field elements as canonical decimal strings.
+pub fn leaves_from_json(path: &str) -> Result<Vec<Vec<ConstraintF>>, LoadError> {
+    let buffer = read_file(path)?;
+    let leaves: Vec<Vec<String>> =
+        serde_json::from_slice(&buffer).map_err(|source| LoadError::Json {
+            path: path.to_string(),
+            source,
+        })?;
+
+    leaves
+        .iter()
+        .map(|leaf| {
+            leaf.iter()
+                .map(|value| {
+                    ConstraintF::from_str(value)
+                        .ok()
+                        // `from_str` silently reduces, so require the canonical form
+                        .filter(|element| decimal(element) == *value)
+                        .ok_or_else(|| LoadError::InvalidFieldElement {
+                            path: path.to_string(),
+                            value: value.clone(),
+                        })
+                })
+                .collect()
+        })
+        .collect()
+}
+
+pub fn leaves_to_json(path: &str, leaves: &[Vec<ConstraintF>]) -> Result<(), SaveError> {
+    let leaves: Vec<Vec<String>> = leaves
+        .iter()
+        .map(|leaf| leaf.iter().map(decimal).collect())
+        .collect();
+    let buffer = serde_json::to_vec_pretty(&leaves).map_err(|source| SaveError::Json {
+        path: path.to_string(),
+        source,
+    })?;
+
+    write_file(path, &buffer)
+}
+
+/// Writes the leaf digests and the root of `tree`, behind a [`TREE_MAGIC`]
+/// and [`TREE_VERSION`] header.
+///
+/// Only the leaf level is stored. arkworks keeps a tree's nodes private and
+/// builds one only through `new_with_leaf_digest`, which hashes the inner
+/// nodes itself, so stored inner nodes could not be handed back to it and
+/// [`load_tree`] has to fold them up again. The leaf digests are likewise
+/// only readable as the sibling hash of a path, so they are collected in a
+/// single walk over the leaves, one path each: that copies the auth paths but
+/// hashes nothing.
+#[cfg(feature = "prover")]
+pub fn save_tree(path: &str, tree: &MntMerkleTree) -> Result<(), SaveError> {
+    let capacity = 1usize << (tree.height() - 1);
+    let mut digests = vec![ConstraintF::default(); capacity];
+    for index in 0..capacity {
+        // The path of a leaf carries the digest of its neighbour
+        digests[index ^ 1] = tree
+            .generate_proof(index)
+            .expect("index is below the tree's capacity")
+            .leaf_sibling_hash;
+    }
+
+    let mut buffer = Vec::new();
+    buffer.extend_from_slice(TREE_MAGIC);
+    buffer.push(TREE_VERSION);
+    (tree.root(), digests)
+        .serialize_uncompressed(&mut buffer)
+        .map_err(|source| SaveError::Serialize {
+            path: path.to_string(),
+            source,
+        })?;
+
+    write_file(path, &buffer)
+}
+
+/// Restores a tree written by [`save_tree`], checking that it has the stored
+/// root. The inner nodes are folded back up from the leaf digests, one
+/// compression each, but no leaf is rehashed.
+#[cfg(feature = "prover")]
+pub fn load_tree(
+    path: &str,
+    leaf_params: &<LeafH as CRHScheme>::Parameters,
+    two_to_one_params: &<LeafH as CRHScheme>::Parameters,
+) -> Result<MntMerkleTree, LoadError> {
+    let buffer = read_file(path)?;
+
+    let header_len = TREE_MAGIC.len() + 1;
+    if buffer.len() < header_len || &buffer[..TREE_MAGIC.len()] != TREE_MAGIC {
+        return Err(LoadError::BadHeader {
+            path: path.to_string(),
+        });
+    }
+    let version = buffer[TREE_MAGIC.len()];
+    if version != TREE_VERSION {
+        return Err(LoadError::UnsupportedVersion {
+            path: path.to_string(),
+            version,
+        });
+    }
+
+    let (root, digests) =
+        <(ConstraintF, Vec<ConstraintF>)>::deserialize_uncompressed(&buffer[header_len..])
+            .map_err(|source| LoadError::Deserialize {
+                path: path.to_string(),
+                source,
+            })?;
+
+    check_leaf_count(path, digests.len())?;
+    let tree = MntMerkleTree::new_with_leaf_digest(leaf_params, two_to_one_params, digests)
+        .map_err(|e| LoadError::Tree {
+            path: path.to_string(),
+            message: e.to_string(),
+        })?;
+    if tree.root() != root {
+        return Err(LoadError::RootMismatch {
+            path: path.to_string(),
+        });
+    }
+
+    Ok(tree)
+}
+
+/// Refuses a leaf count `new_with_leaf_digest` would panic on: it needs a
+/// power of two, at least two, and a file can hold any count.
+#[cfg(feature = "prover")]
+fn check_leaf_count(path: &str, leaves: usize) -> Result<(), LoadError> {
+    if leaves < 2 || !leaves.is_power_of_two() {
+        return Err(LoadError::Tree {
+            path: path.to_string(),
+            message: TreeError::UnpaddedLeafCount { leaves }.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Writes `root` behind a [`ROOT_MAGIC`] and [`ROOT_VERSION`] header, to
+/// prove membership against as the tree was when it was taken.
+pub fn save_root_snapshot(path: &str, root: &ConstraintF) -> Result<(), SaveError> {
+    let mut buffer = Vec::new();
+    buffer.extend_from_slice(ROOT_MAGIC);
+    buffer.push(ROOT_VERSION);
+    root.serialize_uncompressed(&mut buffer)
+        .map_err(|source| SaveError::Serialize {
+            path: path.to_string(),
+            source,
+        })?;
+
+    write_file(path, &buffer)
+}
+
+pub fn load_root_snapshot(path: &str) -> Result<ConstraintF, LoadError> {
+    let buffer = read_file(path)?;
+
+    let header_len = ROOT_MAGIC.len() + 1;
+    if buffer.len() < header_len || &buffer[..ROOT_MAGIC.len()] != ROOT_MAGIC {
+        return Err(LoadError::BadHeader {
+            path: path.to_string(),
+        });
+    }
+    let version = buffer[ROOT_MAGIC.len()];
+    if version != ROOT_VERSION {
+        return Err(LoadError::UnsupportedVersion {
+            path: path.to_string(),
+            version,
+        });
+    }
+
+    ConstraintF::deserialize_uncompressed(&buffer[header_len..]).map_err(|source| {
+        LoadError::Deserialize {
+            path: path.to_string(),
+            source,
+        }
+    })
+}
+
+/// Writes `matrices` behind a [`R1CS_MAGIC`] and [`R1CS_VERSION`] header.
+///
+/// After the header follow, in the uncompressed arkworks encoding, the
+/// number of instance variables, witness variables and constraints as
+/// little-endian `u64`s, then the A, B and C matrices. Each matrix is a
+/// `u64` row count followed by the rows, and each row a `u64` term count
+/// followed by `(coefficient, variable)` terms: a 95-byte little-endian
+/// field element and a `u64` variable index.
+pub fn save_r1cs(path: &str, matrices: &ConstraintMatrices<ConstraintF>) -> Result<(), SaveError> {
+    let serialize_err = |source| SaveError::Serialize {
+        path: path.to_string(),
+        source,
+    };
+
+    let mut buffer = Vec::new();
+    buffer.extend_from_slice(R1CS_MAGIC);
+    buffer.push(R1CS_VERSION);
+    (
+        matrices.num_instance_variables as u64,
+        matrices.num_witness_variables as u64,
+        matrices.num_constraints as u64,
+    )
+        .serialize_uncompressed(&mut buffer)
+        .map_err(serialize_err)?;
+    for matrix in [&matrices.a, &matrices.b, &matrices.c] {
+        matrix
+            .serialize_uncompressed(&mut buffer)
+            .map_err(serialize_err)?;
+    }
+
+    write_file(path, &buffer)
+}
+
+pub fn load_r1cs(path: &str) -> Result<ConstraintMatrices<ConstraintF>, LoadError> {
+    let buffer = read_file(path)?;
+
+    let header_len = R1CS_MAGIC.len() + 1;
+    if buffer.len() < header_len || &buffer[..R1CS_MAGIC.len()] != R1CS_MAGIC {
+        return Err(LoadError::BadHeader {
+            path: path.to_string(),
+        });
+    }
+    let version = buffer[R1CS_MAGIC.len()];
+    if version != R1CS_VERSION {
+        return Err(LoadError::UnsupportedVersion {
+            path: path.to_string(),
+            version,
+        });
+    }
+
+    let deserialize_err = |source| LoadError::Deserialize {
+        path: path.to_string(),
+        source,
+    };
+    let mut reader = &buffer[header_len..];
+    let (num_instance_variables, num_witness_variables, num_constraints) =
+        <(u64, u64, u64)>::deserialize_uncompressed(&mut reader).map_err(deserialize_err)?;
+    let mut matrix =
+        || Matrix::<ConstraintF>::deserialize_uncompressed(&mut reader).map_err(deserialize_err);
+    let (a, b, c) = (matrix()?, matrix()?, matrix()?);
+
+    let non_zero = |matrix: &Matrix<ConstraintF>| matrix.iter().map(Vec::len).sum();
+    Ok(ConstraintMatrices {
+        num_instance_variables: num_instance_variables as usize,
+        num_witness_variables: num_witness_variables as usize,
+        num_constraints: num_constraints as usize,
+        a_num_non_zero: non_zero(&a),
+        b_num_non_zero: non_zero(&b),
+        c_num_non_zero: non_zero(&c),
+        a,
+        b,
+        c,
+    })
+}
@@ -0,0 +1,106 @@
+use ark_ff::PrimeField;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+
+use crate::{ConstraintF, PublicKeyVar};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LeafField {
+    /// Recomputed from the secret in-circuit, binding the leaf to its owner.
+    PublicKeyX,
+    /// Recomputed like the x-coordinate. With both coordinates in the leaf,
+    /// only one public key opens it, rather than the two points that share
+    /// an x-coordinate.
+    PublicKeyY,
+    Value,
+    Commitment,
+}
+
+/// The order of the field elements in a leaf. The native leaf and the one
+/// opened in-circuit are both built from the same layout, so they can't
+/// drift apart.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LeafLayout {
+    fields: Vec<LeafField>,
+}
+
+/// The values a leaf can be built from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LeafValues<F: PrimeField = ConstraintF> {
+    pub pk_x: F,
+    pub pk_y: F,
+    pub value: F,
+    pub commitment: F,
+}
+
+impl<F: PrimeField> LeafValues<F> {
+    pub fn get(&self, field: LeafField) -> F {
+        match field {
+            LeafField::PublicKeyX => self.pk_x,
+            LeafField::PublicKeyY => self.pk_y,
+            LeafField::Value => self.value,
+            LeafField::Commitment => self.commitment,
+        }
+    }
+}
+
+impl Default for LeafLayout {
+    /// `[pk.x]`, the layout of the puzzle's leaves.
+    fn default() -> Self {
+        Self {
+            fields: vec![LeafField::PublicKeyX],
+        }
+    }
+}
+
+impl LeafLayout {
+    /// Returns `None` unless `fields` holds [`LeafField::PublicKeyX`] and no
+    /// field twice.
+    pub fn new(fields: Vec<LeafField>) -> Option<Self> {
+        let has_key = fields.contains(&LeafField::PublicKeyX);
+        let distinct = fields
+            .iter()
+            .enumerate()
+            .all(|(i, field)| !fields[i + 1..].contains(field));
+
+        (has_key && distinct).then_some(Self { fields })
+    }
+
+    /// `[pk.x, pk.y]`, committing to the whole public key.
+    pub fn public_key() -> Self {
+        Self {
+            fields: vec![LeafField::PublicKeyX, LeafField::PublicKeyY],
+        }
+    }
+
+    pub fn fields(&self) -> &[LeafField] {
+        &self.fields
+    }
+
+    pub fn leaf<F: PrimeField>(&self, values: &LeafValues<F>) -> Vec<F> {
+        self.fields.iter().map(|field| values.get(*field)).collect()
+    }
+
+    /// The in-circuit counterpart of [`Self::leaf`]. Fields other than the
+    /// public key are witnessed from `values` only if the layout uses them.
+    pub fn leaf_var<F: PrimeField>(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        (pk_x, pk_y): &PublicKeyVar<F>,
+        values: &LeafValues<F>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        self.fields
+            .iter()
+            .map(|field| match field {
+                LeafField::PublicKeyX => Ok(pk_x.clone()),
+                LeafField::PublicKeyY => Ok(pk_y.clone()),
+                LeafField::Value | LeafField::Commitment => {
+                    FpVar::new_witness(ark_relations::ns!(cs, "leaf_field"), || {
+                        Ok(values.get(*field))
+                    })
+                }
+            })
+            .collect()
+    }
+}
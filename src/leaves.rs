@@ -0,0 +1,70 @@
+use ark_ff::{PrimeField, UniformRand};
+use ark_mnt6_753::{Fr as MNT6BigFr, G1Affine};
+use ark_std::rand::Rng;
+use blake2::{Blake2s, Digest};
+
+use crate::{public_key, scalar_to_fr, ConstraintF, Mnt753Cycle};
+
+/// Samples a secret that a strict [`crate::SpendCircuit`] accepts: the
+/// canonical lower-half representative of a scalar.
+pub fn sample_secret<R: Rng>(rng: &mut R) -> ConstraintF {
+    canonical_secret(MNT6BigFr::rand(rng))
+}
+
+/// Derives the secret of `seed`, the same way every time. Like
+/// [`sample_secret`], the result is the canonical representative of its key,
+/// so it is accepted by a strict [`crate::SpendCircuit`].
+///
+/// The seed is expanded with Blake2s into 128 bytes, well past the 753 bits
+/// of the scalar field, so reducing them leaves no measurable bias.
+pub fn secret_from_seed(seed: &[u8]) -> ConstraintF {
+    let mut bytes = Vec::with_capacity(128);
+    for counter in 0u8..4 {
+        let mut hasher = Blake2s::new();
+        hasher.update(b"gamma-ray secret");
+        hasher.update([counter]);
+        hasher.update(seed);
+        bytes.extend_from_slice(&hasher.finalize());
+    }
+
+    canonical_secret(MNT6BigFr::from_le_bytes_mod_order(&bytes))
+}
+
+fn canonical_secret(s: MNT6BigFr) -> ConstraintF {
+    crate::canonical_secret::<Mnt753Cycle>(scalar_to_fr(s))
+}
+
+/// Samples `count` secrets and returns the leaves of their public keys along
+/// with the secrets, so `secrets[i]` owns `leaves[i]`.
+pub fn generate_leaves<R: Rng>(
+    count: usize,
+    rng: &mut R,
+) -> (Vec<Vec<ConstraintF>>, Vec<ConstraintF>) {
+    let secrets: Vec<_> = (0..count).map(|_| sample_secret(rng)).collect();
+    let leaves = secrets
+        .iter()
+        .map(|s| public_key_leaf(&public_key(*s)))
+        .collect();
+
+    (leaves, secrets)
+}
+
+/// The leaf of `pk`: its x-coordinate alone, as in the puzzle's leaves.
+pub fn public_key_leaf(pk: &G1Affine) -> Vec<ConstraintF> {
+    vec![pk.x]
+}
+
+/// The leaf of `pk` under [`crate::LeafLayout::public_key`]: both of its
+/// coordinates, so the other point with the same x-coordinate can't open it.
+pub fn full_public_key_leaf(pk: &G1Affine) -> Vec<ConstraintF> {
+    vec![pk.x, pk.y]
+}
+
+/// The position of the first leaf owned by `secret`, if any.
+///
+/// This only derives the public key once and then compares, so a linear scan
+/// is cheap next to anything else done with the leaves.
+pub fn find_leaf_index(leaves: &[Vec<ConstraintF>], secret: ConstraintF) -> Option<usize> {
+    let leaf = public_key_leaf(&public_key(secret));
+    leaves.iter().position(|candidate| *candidate == leaf)
+}
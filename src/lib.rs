@@ -0,0 +1,164 @@
+use ark_ec::pairing::Pairing;
+use ark_mnt4_753::Fr as MNT4BigFr;
+
+use ark_crypto_primitives::snark::SNARK;
+use ark_groth16::{Groth16, Proof, ProvingKey, VerifyingKey};
+use ark_relations::r1cs::SynthesisError;
+use tracing::instrument;
+pub use zeroize::Zeroizing;
+
+#[cfg(feature = "prover")]
+pub mod arity;
+pub mod batch;
+#[cfg(feature = "prover")]
+pub mod constraints;
+pub mod convert;
+#[cfg(feature = "prover")]
+pub mod cycle;
+#[cfg(feature = "prover")]
+pub mod hash_family;
+#[cfg(feature = "prover")]
+pub mod incremental;
+#[cfg(feature = "native-io")]
+pub mod io;
+#[cfg(feature = "prover")]
+pub mod leaf_layout;
+#[cfg(feature = "prover")]
+pub mod leaves;
+#[cfg(feature = "prover")]
+pub mod multi_spend;
+#[cfg(feature = "prover")]
+pub mod note;
+#[cfg(feature = "prover")]
+pub mod nullifier;
+pub mod nullifier_set;
+#[cfg(feature = "prover")]
+pub mod params;
+#[cfg(feature = "prover")]
+pub mod poseidon_parameters;
+#[cfg(feature = "prover")]
+pub mod range;
+#[cfg(feature = "prover")]
+pub mod rescue;
+#[cfg(feature = "prover")]
+pub mod solution;
+#[cfg(feature = "prover")]
+pub mod sparse_tree;
+#[cfg(feature = "prover")]
+pub mod spend;
+#[cfg(feature = "prover")]
+pub mod spend_auth;
+pub mod transaction;
+#[cfg(feature = "prover")]
+pub mod transfer;
+#[cfg(feature = "prover")]
+pub mod tree;
+pub mod verify;
+#[cfg(feature = "prover")]
+pub mod witness;
+
+#[cfg(feature = "prover")]
+pub use arity::{Arity, Binary, Wide, WideMerkleTree, WidePath, WidePathVar};
+pub use batch::batch_verify;
+#[cfg(feature = "parallel")]
+pub use batch::prove_batch;
+#[cfg(feature = "prover")]
+pub use batch::prove_batch_sequential;
+#[cfg(feature = "prover")]
+pub use constraints::{
+    check_satisfied, constraint_matrices, count_constraints, matrices_satisfied,
+    synthesize_and_report, CircuitShape, ConstraintStats,
+};
+pub use convert::{
+    fr_to_scalar, fr_to_scalar_reduced, public_inputs_from_strings, public_inputs_to_strings,
+    scalar_to_fr, InvalidPublicInput, NotRepresentable,
+};
+#[cfg(feature = "prover")]
+pub use cycle::{points_from_x, public_key, CurveCycle, Mnt753Cycle};
+#[cfg(feature = "prover")]
+pub use hash_family::{HashFamily, IdentityConverter, LeafConverter, PoseidonFamily, RescueFamily};
+#[cfg(feature = "prover")]
+pub use incremental::IncrementalTree;
+#[cfg(feature = "mmap")]
+pub use io::load_verifying_key_mmap;
+#[cfg(all(feature = "native-io", feature = "prover"))]
+pub use io::{build_tree_streaming, load_tree, save_tree};
+#[cfg(feature = "native-io")]
+pub use io::{
+    from_file, from_file_checked, leaves_from_json, leaves_to_json, load_compressed, load_keys,
+    load_proof, load_r1cs, load_root_snapshot, read_leaves_streaming, save_compressed, save_keys,
+    save_keys_compressed, save_proof, save_proof_compressed, save_r1cs, save_root_snapshot,
+    to_file, LeafReader, LoadError, SaveError,
+};
+#[cfg(feature = "prover")]
+pub use leaf_layout::{LeafField, LeafLayout, LeafValues};
+#[cfg(feature = "prover")]
+pub use leaves::{
+    find_leaf_index, full_public_key_leaf, generate_leaves, public_key_leaf, sample_secret,
+    secret_from_seed,
+};
+#[cfg(feature = "prover")]
+pub use multi_spend::MultiSpendCircuit;
+#[cfg(feature = "prover")]
+pub use note::{commitment, commitment_var, commitment_var_with, commitment_with, Note};
+#[cfg(feature = "prover")]
+pub use nullifier::{
+    canonical_scalar, canonical_secret, check_nullifier, domain_tag, enforce_all_distinct,
+    expected_nullifiers, named_domain, nullifier, nullifier_var, nullifier_var_with,
+    nullifier_var_with_layout, nullifier_with, nullifier_with_layout, positional_nullifier,
+    positional_nullifier_var_with, positional_nullifier_with, scalar_nullifier,
+    scalar_nullifier_var_with_layout, scalar_nullifier_with_layout, MissingLeafIndex,
+    NullifierInput, LEGACY_DOMAIN,
+};
+pub use nullifier_set::NullifierSet;
+#[cfg(feature = "prover")]
+pub use params::CircuitParams;
+#[cfg(feature = "prover")]
+pub use range::{enforce_canonical, enforce_in_range};
+#[cfg(feature = "prover")]
+pub use solution::solve;
+#[cfg(feature = "prover")]
+pub use sparse_tree::{NonMembershipCircuit, NonMembershipProof, SparseMerkleTree};
+#[cfg(feature = "prover")]
+pub(crate) use spend::SpendGadget;
+#[cfg(feature = "prover")]
+pub use spend::{
+    check_path_height, path_height, prove, prove_from_path, prove_with_seed, public_inputs, setup,
+    verify_update, CompressH, CompressHG, CrhParamsVar, LeafH, LeafHG, LeafVar, MerklePath,
+    MerklePathVar, MerkleTreeParams, MerkleTreeParamsVar, MntMerkleTree, MntMerkleTreeParams,
+    MntMerkleTreeParamsVar, Payout, PublicKeyVar, SpendCircuit, CIRCUIT_VERSION,
+};
+#[cfg(feature = "prover")]
+pub use spend_auth::{sign, verify_signature, Signature, SpendAuthCircuit};
+pub use transaction::SpendTransaction;
+#[cfg(feature = "prover")]
+pub use transfer::{InputNote, TransferCircuit, VALUE_BITS};
+#[cfg(feature = "parallel")]
+pub use tree::build_tree_parallel;
+#[cfg(feature = "prover")]
+pub use tree::{build_tree, tree_depth, tree_from_public_keys, TreeError, MAX_TREE_HEIGHT};
+pub use verify::{
+    compose_proof, decompose_proof, proofs_equal, validate_public_inputs, verify_detailed,
+    verify_proof_bytes, verify_public_inputs, verify_spend, verify_spend_multi_root,
+    vk_fingerprint, VerifyError, VerifyReport, PUZZLE_VK_FINGERPRINT,
+};
+#[cfg(feature = "prover")]
+pub use witness::{leaf_at, validate_witness, WitnessError};
+
+pub type ConstraintF = MNT4BigFr;
+
+/// A proving key and the verifying key from the same setup.
+pub type Keys<E> = (ProvingKey<E>, VerifyingKey<E>);
+
+// Public inputs are allocated as (root, nullifier), in that order. Circuits
+// with a payout are verified with `verify_public_inputs` and
+// `public_inputs`.
+#[instrument(skip_all)]
+pub fn verify<E: Pairing>(
+    vk: &VerifyingKey<E>,
+    root: &E::ScalarField,
+    nullifier: &E::ScalarField,
+    proof: &Proof<E>,
+) -> Result<bool, SynthesisError> {
+    Groth16::<E>::verify(vk, &[*root, *nullifier], proof)
+}
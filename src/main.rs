@@ -1,212 +1,848 @@
-use ark_ec::AffineRepr;
-use ark_ff::PrimeField;
+use ark_ff::{BigInteger, PrimeField};
 use ark_mnt4_753::{Fr as MNT4BigFr, MNT4_753};
-use ark_mnt6_753::G1Affine;
-use ark_mnt6_753::{constraints::G1Var, Fr as MNT6BigFr};
 
-use ark_crypto_primitives::merkle_tree::{Config, MerkleTree, Path};
-use ark_crypto_primitives::{crh::TwoToOneCRHScheme, snark::SNARK};
+use ark_crypto_primitives::snark::SNARK;
 use ark_groth16::Groth16;
-use ark_r1cs_std::fields::fp::FpVar;
-use ark_r1cs_std::prelude::*;
-use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
-use ark_serialize::{CanonicalDeserialize, Read};
+use ark_serialize::Compress;
 
-use prompt::{puzzle, welcome};
+use anyhow::{anyhow, ensure, Context, Result};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 
-use std::fs::File;
-use std::io::Cursor;
+use prompt::{puzzle, welcome};
 
-pub mod poseidon_parameters;
+use tracing::{instrument, Level};
+use tracing_subscriber::fmt::format::FmtSpan;
 
-type ConstraintF = MNT4BigFr;
+use ark_crypto_primitives::merkle_tree::Path as MerklePath;
+use ark_std::rand::SeedableRng;
 
-use ark_crypto_primitives::{
-    crh::{poseidon, *},
-    merkle_tree::constraints::*,
-    merkle_tree::*,
+use std::io::Read;
+use std::path::Path;
+use std::process;
+use std::time::{Duration, Instant};
+
+use puzzle_gamma_ray::{
+    check_satisfied, constraint_matrices, count_constraints, find_leaf_index, from_file,
+    generate_leaves, io, leaf_at, load_proof, load_root_snapshot, nullifier, path_height,
+    poseidon_parameters, prove, prove_with_seed, public_inputs, public_inputs_to_strings,
+    public_key, public_key_leaf, save_keys, save_keys_compressed, save_proof,
+    save_proof_compressed, save_r1cs, save_root_snapshot, setup, solve, to_file, tree_depth,
+    validate_witness, verify_public_inputs, verify_spend, vk_fingerprint, CircuitParams,
+    Mnt753Cycle, MntMerkleTree, MntMerkleTreeParams, NullifierSet, SpendCircuit, Zeroizing,
+    LEGACY_DOMAIN, PUZZLE_VK_FINGERPRINT,
 };
-use ark_std::rand::SeedableRng;
 
-type LeafH = poseidon::CRH<ConstraintF>;
-type LeafHG = poseidon::constraints::CRHGadget<ConstraintF>;
-
-type CompressH = poseidon::TwoToOneCRH<ConstraintF>;
-type CompressHG = poseidon::constraints::TwoToOneCRHGadget<ConstraintF>;
-
-type LeafVar = [FpVar<ConstraintF>];
-struct MntMerkleTreeParamsVar;
-impl ConfigGadget<MntMerkleTreeParams, ConstraintF> for MntMerkleTreeParamsVar {
-    type Leaf = LeafVar;
-    type LeafDigest = <LeafHG as CRHSchemeGadget<LeafH, ConstraintF>>::OutputVar;
-    type LeafInnerConverter = IdentityDigestConverter<FpVar<ConstraintF>>;
-    type InnerDigest = <CompressHG as TwoToOneCRHSchemeGadget<CompressH, ConstraintF>>::OutputVar;
-    type LeafHash = LeafHG;
-    type TwoToOneHash = CompressHG;
-}
-
-type MntMerkleTree = MerkleTree<MntMerkleTreeParams>;
-
-struct MntMerkleTreeParams;
-
-impl Config for MntMerkleTreeParams {
-    type Leaf = [ConstraintF];
-
-    type LeafDigest = <LeafH as CRHScheme>::Output;
-    type LeafInnerDigestConverter = IdentityDigestConverter<ConstraintF>;
-    type InnerDigest = <CompressH as TwoToOneCRHScheme>::Output;
-
-    type LeafHash = LeafH;
-    type TwoToOneHash = CompressH;
-}
-
-#[derive(Clone)]
-struct SpendCircuit {
-    pub leaf_params: <LeafH as CRHScheme>::Parameters,
-    pub two_to_one_params: <LeafH as CRHScheme>::Parameters,
-    pub root: <CompressH as TwoToOneCRHScheme>::Output,
-    pub proof: Path<MntMerkleTreeParams>,
-    pub secret: ConstraintF,
-    pub nullifier: ConstraintF,
-}
-
-impl ConstraintSynthesizer<ConstraintF> for SpendCircuit {
-    fn generate_constraints(
-        self,
-        cs: ConstraintSystemRef<ConstraintF>,
-    ) -> Result<(), SynthesisError> {
-        // Allocate Merkle Tree Root
-        let root = <LeafHG as CRHSchemeGadget<LeafH, _>>::OutputVar::new_input(
-            ark_relations::ns!(cs, "new_digest"),
-            || Ok(self.root),
-        )?;
-
-        // Allocate Parameters for CRH
-        let leaf_crh_params_var =
-            <LeafHG as CRHSchemeGadget<LeafH, _>>::ParametersVar::new_constant(
-                ark_relations::ns!(cs, "leaf_crh_parameter"),
-                &self.leaf_params,
-            )?;
-        let two_to_one_crh_params_var =
-            <CompressHG as TwoToOneCRHSchemeGadget<CompressH, _>>::ParametersVar::new_constant(
-                ark_relations::ns!(cs, "two_to_one_crh_parameter"),
-                &self.two_to_one_params,
-            )?;
-
-        let secret = FpVar::new_witness(ark_relations::ns!(cs, "secret"), || Ok(self.secret))?;
-        let secret_bits = secret.to_bits_le()?;
-        Boolean::enforce_smaller_or_equal_than_le(&secret_bits, MNT6BigFr::MODULUS)?;
-
-        let nullifier = <LeafHG as CRHSchemeGadget<LeafH, _>>::OutputVar::new_input(
-            ark_relations::ns!(cs, "nullifier"),
-            || Ok(self.nullifier),
-        )?;
-
-        let nullifier_in_circuit =
-            <LeafHG as CRHSchemeGadget<LeafH, _>>::evaluate(&leaf_crh_params_var, &[secret])?;
-        nullifier_in_circuit.enforce_equal(&nullifier)?;
-
-        let base = G1Var::new_constant(ark_relations::ns!(cs, "base"), G1Affine::generator())?;
-        let pk = base.scalar_mul_le(secret_bits.iter())?.to_affine()?;
-
-        // Allocate Leaf
-        let leaf_g: Vec<_> = vec![pk.x];
-
-        // Allocate Merkle Tree Path
-        let cw: PathVar<MntMerkleTreeParams, ConstraintF, MntMerkleTreeParamsVar> =
-            PathVar::new_witness(ark_relations::ns!(cs, "new_witness"), || Ok(&self.proof))?;
-
-        cw.verify_membership(
-            &leaf_crh_params_var,
-            &two_to_one_crh_params_var,
-            &root,
-            &leaf_g,
-        )?
-        .enforce_equal(&Boolean::constant(true))?;
+const DEFAULT_LEAVES: &str = "./leaves.bin";
+const DEFAULT_KEYS: &str = "./proof_keys.bin";
+const DEFAULT_SECRET: &str = "./leaked_secret.bin";
+
+/// The shape of the puzzle's fixtures, which `gen-fixtures` reproduces.
+const FIXTURE_LEAVES: usize = 4;
+const FIXTURE_LEAKED_INDEX: usize = 2;
+
+#[derive(Parser)]
+#[command(about = "ZK Hack puzzle: gamma-ray")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[command(flatten)]
+    inspect: Inspect,
+    /// The fixtures of the puzzle run when no subcommand is given
+    #[command(flatten)]
+    fixtures: Fixtures,
+    /// Seed for the proving and leaf generation randomness
+    #[arg(long, global = true, default_value_t = 0)]
+    seed: u64,
+    /// Log spans and their durations to stderr at this level and above
+    #[arg(long, global = true)]
+    log: Option<Level>,
+    /// Print how long building the tree, proving and verifying took
+    #[arg(long, global = true)]
+    timings: bool,
+    /// Have `verify` and `solve` print a JSON object with whether they
+    /// succeeded, the public inputs of each proof and the timings, instead
+    /// of text
+    #[arg(long, global = true)]
+    json: bool,
+}
 
-        Ok(())
+/// What to report about the spend circuit before proving it.
+#[derive(Args)]
+struct Inspect {
+    /// Print the size of the spend circuit before proving
+    #[arg(long, global = true)]
+    stats: bool,
+    /// Write the A, B and C matrices of the spend circuit to this file
+    #[arg(long, global = true, value_name = "PATH")]
+    dump_r1cs: Option<String>,
+    /// Only check that the witnesses satisfy the spend circuit, without
+    /// loading the keys or proving
+    #[arg(long, global = true)]
+    check: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Prove ownership of the leaf at `index` and write the proof to disk
+    Prove {
+        #[command(flatten)]
+        fixtures: Fixtures,
+        #[arg(long, default_value_t = 2)]
+        index: usize,
+        #[arg(long, default_value = "./proof.bin")]
+        proof: String,
+        /// Prove against the root written by `snapshot-root` instead of the
+        /// root of the leaves, opening the leaf with the path written with it
+        /// rather than one from the leaves, which may have changed since
+        #[arg(long, value_name = "PATH", requires = "path")]
+        root_snapshot: Option<String>,
+        /// The path `snapshot-root` wrote with the root, which picks the leaf
+        /// in place of `--index`
+        #[arg(long, value_name = "PATH", requires = "root_snapshot")]
+        path: Option<String>,
+    },
+    /// Verify a proof written by `prove` against the root of the leaves
+    Verify {
+        #[command(flatten)]
+        fixtures: Fixtures,
+        #[arg(long, default_value = "./proof.bin")]
+        proof: String,
+        /// Verify against the root written by `snapshot-root` instead
+        #[arg(long, value_name = "PATH")]
+        root_snapshot: Option<String>,
+        /// Reject proofs whose nullifier is in this set of spent nullifiers,
+        /// and add the nullifier of an accepted one. Created if missing.
+        #[arg(long, value_name = "PATH")]
+        nullifiers: Option<String>,
+    },
+    /// Save the root of the leaves, to prove against after the tree changes
+    SnapshotRoot {
+        #[arg(long, default_value = DEFAULT_LEAVES)]
+        leaves: String,
+        #[arg(long)]
+        output: String,
+        /// Also save the path of the leaf at this index, for `prove
+        /// --root-snapshot`
+        #[arg(long, requires = "path")]
+        index: Option<usize>,
+        /// Where to write the path of the leaf at `--index`
+        #[arg(long, value_name = "PATH", requires = "index")]
+        path: Option<String>,
+    },
+    /// Print the size and public inputs of a proof written by `prove`
+    Inspect {
+        #[arg(long, default_value = "./proof.bin")]
+        proof: String,
+        /// A verifying key written by `write-vk`, to print its fingerprint
+        /// and whether the proof verifies under it
+        #[arg(long)]
+        vk: Option<String>,
+    },
+    /// Run the puzzle: an honest spend followed by the double spend
+    Solve {
+        #[command(flatten)]
+        fixtures: Fixtures,
+    },
+    /// Generate random leaves and the secrets that own them
+    GenLeaves {
+        #[arg(long, default_value_t = 16)]
+        count: usize,
+        /// Where to write the leaves, in the layout of leaves.bin
+        #[arg(long)]
+        leaves: String,
+        /// Where to write the secrets, where entry `i` owns leaf `i`
+        #[arg(long)]
+        secrets: String,
+    },
+    /// Write a fresh leaves.bin, leaked_secret.bin and proof_keys.bin to
+    /// `output_dir`, determined by `--seed`. Use them with `--any-keys`, which
+    /// the run without a subcommand takes too.
+    GenFixtures {
+        #[arg(long)]
+        output_dir: String,
+    },
+    /// Rewrite a keys file with compressed points
+    CompressKeys {
+        #[arg(long, default_value = DEFAULT_KEYS)]
+        keys: String,
+        #[arg(long)]
+        output: String,
+    },
+    /// Write the verifying key of a keys file on its own, as `verifier` and
+    /// `inspect --vk` read it
+    WriteVk {
+        #[arg(long, default_value = DEFAULT_KEYS)]
+        keys: String,
+        #[arg(long)]
+        output: String,
+    },
+}
+
+#[derive(Args)]
+struct Fixtures {
+    #[arg(long, default_value = DEFAULT_LEAVES)]
+    leaves: String,
+    #[arg(long, default_value = DEFAULT_KEYS)]
+    keys: String,
+    /// A file written with `to_file`, a `0x` prefixed big-endian hex
+    /// literal, or `-` to read such a literal from stdin
+    #[arg(long, default_value = DEFAULT_SECRET)]
+    secret: String,
+    /// Read the keys, and write proofs, with compressed points
+    #[arg(long)]
+    compressed: bool,
+    /// Accept keys from any setup of the spend circuit, such as the ones
+    /// `gen-fixtures` writes, rather than only the puzzle's
+    #[arg(long)]
+    any_keys: bool,
+    /// How the points of a keys file without a header are stored, which
+    /// defaults to `--compressed`. Files with a header say so themselves.
+    #[arg(long, value_enum)]
+    key_format: Option<KeyFormat>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum KeyFormat {
+    Compressed,
+    Uncompressed,
+}
+
+impl Fixtures {
+    fn key_compression(&self) -> Compress {
+        match self.key_format {
+            Some(KeyFormat::Compressed) => Compress::Yes,
+            Some(KeyFormat::Uncompressed) => Compress::No,
+            None if self.compressed => Compress::Yes,
+            None => Compress::No,
+        }
+    }
+}
+
+type Keys = puzzle_gamma_ray::Keys<MNT4_753>;
+
+// The crate's boxed errors are not `Send`, so they can't be wrapped directly
+fn crypto_err(context: &str) -> impl FnOnce(ark_crypto_primitives::Error) -> anyhow::Error + '_ {
+    move |e| anyhow!("{}: {}", context, e)
+}
+
+#[instrument(skip_all, fields(leaves = leaves.len()))]
+fn merkle_tree(params: &CircuitParams, leaves: &[Vec<MNT4BigFr>]) -> Result<MntMerkleTree> {
+    params
+        .merkle_tree(leaves.iter().map(|x| x.as_slice()))
+        // `TreeError` holds the crate's boxed error, which is not `Send` either
+        .map_err(|e| anyhow!("could not build the Merkle tree: {}", e))
+}
+
+fn load_keys(fixtures: &Fixtures) -> Result<Keys> {
+    let keys = read_keys(&fixtures.keys, fixtures.key_compression())?;
+    if fixtures.any_keys {
+        return Ok(keys);
+    }
+
+    let fingerprint = vk_fingerprint(&keys.1);
+    ensure!(
+        fingerprint == PUZZLE_VK_FINGERPRINT,
+        "{} was not generated for this circuit: its verifying key fingerprint is {}, expected {}",
+        fixtures.keys,
+        hex(&fingerprint),
+        hex(&PUZZLE_VK_FINGERPRINT)
+    );
+    Ok(keys)
+}
+
+/// Reads keys in any layout `save_keys` has written, warning about ones that
+/// predate the current version. `headerless` is how the points of a file
+/// without a header are stored.
+fn read_keys(path: &str, headerless: Compress) -> Result<Keys> {
+    let (keys, version) = puzzle_gamma_ray::load_keys::<MNT4_753>(path, headerless)?;
+    if version < io::KEYS_VERSION {
+        eprintln!(
+            "warning: {} has version {} keys, rewrite them with `save_keys` to upgrade to version {}",
+            path, version, io::KEYS_VERSION
+        );
+    }
+    Ok(keys)
+}
+
+fn read_secret(source: &str) -> Result<Zeroizing<MNT4BigFr>> {
+    if source == "-" {
+        let mut literal = Zeroizing::new(String::new());
+        std::io::stdin()
+            .read_to_string(&mut literal)
+            .context("could not read the secret from stdin")?;
+        return parse_secret(literal.trim());
+    }
+    if source.starts_with("0x") {
+        return parse_secret(source);
+    }
+    Ok(Zeroizing::new(from_file(source)?))
+}
+
+/// Parses a `0x` prefixed big-endian hex literal, rejecting values at or
+/// above the modulus rather than reducing them. Errors leave the literal
+/// out, as it is secret.
+fn parse_secret(literal: &str) -> Result<Zeroizing<MNT4BigFr>> {
+    let digits = literal
+        .strip_prefix("0x")
+        .context("the secret must be a 0x prefixed hex literal")?;
+    ensure!(
+        !digits.is_empty() && digits.chars().all(|c| c.is_ascii_hexdigit()),
+        "the secret is not a hex literal"
+    );
+
+    let size = (MNT4BigFr::MODULUS_BIT_SIZE as usize).div_ceil(8);
+    let digits = digits.trim_start_matches('0');
+    ensure!(
+        digits.len() <= 2 * size,
+        "the secret does not fit in a field element"
+    );
+    let padded = Zeroizing::new(format!("{:0>width$}", digits, width = 2 * size));
+    let bytes: Zeroizing<Vec<u8>> = Zeroizing::new(
+        (0..padded.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&padded[i..i + 2], 16).unwrap())
+            .collect(),
+    );
+
+    let secret = Zeroizing::new(MNT4BigFr::from_be_bytes_mod_order(&bytes));
+    let canonical = Zeroizing::new(secret.into_bigint().to_bytes_be());
+    ensure!(
+        canonical[canonical.len() - size..] == bytes[..],
+        "the secret is not canonical: it is at least the modulus"
+    );
+    Ok(secret)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn inspect(options: &Inspect, circuit: &SpendCircuit) -> Result<()> {
+    if options.stats {
+        let stats = count_constraints(circuit).context("could not synthesize the circuit")?;
+        println!("{}", stats);
+    }
+    if let Some(path) = &options.dump_r1cs {
+        let matrices = constraint_matrices(circuit).context("could not synthesize the circuit")?;
+        save_r1cs(path, &matrices)?;
     }
+    Ok(())
 }
 
-fn from_file<T: CanonicalDeserialize>(path: &str) -> T {
-    let mut file = File::open(path).unwrap();
-    let mut buffer = Vec::new();
-    file.read_to_end(&mut buffer).unwrap();
-    T::deserialize_uncompressed_unchecked(Cursor::new(&buffer)).unwrap()
+fn print_check(name: &str, circuit: &SpendCircuit) -> Result<()> {
+    let satisfied = check_satisfied(circuit).context("could not synthesize the circuit")?;
+    println!(
+        "{} {}",
+        name,
+        if satisfied {
+            "satisfies the circuit"
+        } else {
+            "does not satisfy the circuit"
+        }
+    );
+    Ok(())
 }
 
+/// Wall-clock time spent in each phase, summed over repeats so the double
+/// spend's proof lands on the same row as the honest one's.
+#[derive(Default)]
+struct Timings {
+    phases: Vec<(&'static str, Duration)>,
+}
+
+impl Timings {
+    fn time<T>(&mut self, phase: &'static str, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        let elapsed = start.elapsed();
+
+        match self.phases.iter_mut().find(|(name, _)| *name == phase) {
+            Some((_, total)) => *total += elapsed,
+            None => self.phases.push((phase, elapsed)),
+        }
+        result
+    }
+
+    fn print(&self) {
+        for (phase, elapsed) in &self.phases {
+            println!("{:<12} {:>8} ms", phase, elapsed.as_millis());
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        self.phases
+            .iter()
+            .map(|(phase, elapsed)| {
+                let millis = serde_json::Value::from(elapsed.as_millis() as u64);
+                (phase.to_string(), millis)
+            })
+            .collect::<serde_json::Map<_, _>>()
+            .into()
+    }
+}
+
+/// The result of `verify` or `solve` for `--json`, with the public inputs of
+/// each proof they checked as decimal strings.
+fn print_json(result: &Result<Vec<Vec<MNT4BigFr>>>, timings: &Timings) {
+    let public_inputs: Vec<Vec<String>> = result
+        .as_deref()
+        .unwrap_or_default()
+        .iter()
+        .map(|inputs| public_inputs_to_strings(inputs))
+        .collect();
+    let mut report = serde_json::json!({
+        "success": result.is_ok(),
+        "public_inputs": public_inputs,
+        "timings_ms": timings.to_json(),
+    });
+    if let Err(e) = result {
+        report["error"] = format!("{:#}", e).into();
+    }
+    println!("{}", report);
+}
+
+const TREE: &str = "tree";
+const SETUP: &str = "setup";
+const PROVING: &str = "proving";
+const VERIFYING: &str = "verifying";
+
 fn main() {
-    welcome();
-    puzzle(PUZZLE_DESCRIPTION);
-
-    let rng = &mut ark_std::rand::rngs::StdRng::seed_from_u64(0u64);
-
-    let leaves: Vec<Vec<MNT4BigFr>> = from_file("./leaves.bin");
-    let leaked_secret: MNT4BigFr = from_file("./leaked_secret.bin");
-    let (pk, vk): (
-        <Groth16<MNT4_753> as SNARK<MNT4BigFr>>::ProvingKey,
-        <Groth16<MNT4_753> as SNARK<MNT4BigFr>>::VerifyingKey,
-    ) = from_file("./proof_keys.bin");
-
-    let leaf_crh_params = poseidon_parameters::poseidon_parameters();
-    let i = 2;
-    let two_to_one_crh_params = leaf_crh_params.clone();
-
-    let nullifier = <LeafH as CRHScheme>::evaluate(&leaf_crh_params, vec![leaked_secret]).unwrap();
-
-    let tree = MntMerkleTree::new(
-        &leaf_crh_params,
-        &two_to_one_crh_params,
-        leaves.iter().map(|x| x.as_slice()),
-    )
-    .unwrap();
-    let root = tree.root();
-    let leaf = &leaves[i];
-
-    let tree_proof = tree.generate_proof(i).unwrap();
-    assert!(tree_proof
-        .verify(
-            &leaf_crh_params,
-            &two_to_one_crh_params,
-            &root,
-            leaf.as_slice()
+    if let Err(e) = run(Cli::parse()) {
+        eprintln!("error: {:#}", e);
+        process::exit(1);
+    }
+}
+
+fn run(cli: Cli) -> Result<()> {
+    // Without a subscriber the spans are disabled and cost next to nothing
+    if let Some(level) = cli.log {
+        tracing_subscriber::fmt()
+            .with_max_level(level)
+            .with_span_events(FmtSpan::CLOSE)
+            .with_writer(std::io::stderr)
+            .init();
+    }
+
+    debug_assert_eq!(
+        poseidon_parameters::validate_parameters(&poseidon_parameters::poseidon_parameters()),
+        Ok(())
+    );
+
+    let mut timings = Timings::default();
+    let result = match cli.command {
+        Some(Command::Prove {
+            fixtures,
+            index,
+            proof,
+            root_snapshot,
+            path,
+        }) => prove_cmd(
+            &fixtures,
+            index,
+            &proof,
+            root_snapshot.as_deref().zip(path.as_deref()),
+            cli.seed,
+            &cli.inspect,
+            &mut timings,
         )
-        .unwrap());
-
-    let c = SpendCircuit {
-        leaf_params: leaf_crh_params.clone(),
-        two_to_one_params: two_to_one_crh_params.clone(),
-        root: root.clone(),
-        proof: tree_proof.clone(),
-        nullifier: nullifier.clone(),
-        secret: leaked_secret.clone(),
+        .map(|()| Vec::new()),
+        Some(Command::Verify {
+            fixtures,
+            proof,
+            root_snapshot,
+            nullifiers,
+        }) => verify_cmd(
+            &fixtures,
+            &proof,
+            root_snapshot.as_deref(),
+            nullifiers.as_deref(),
+            cli.json,
+            &mut timings,
+        ),
+        Some(Command::SnapshotRoot {
+            leaves,
+            output,
+            index,
+            path,
+        }) => snapshot_root_cmd(&leaves, &output, index.zip(path.as_deref()), &mut timings)
+            .map(|()| Vec::new()),
+        Some(Command::Inspect { proof, vk }) => {
+            inspect_cmd(&proof, vk.as_deref()).map(|()| Vec::new())
+        }
+        Some(Command::Solve { fixtures }) => {
+            solve_cmd(&fixtures, cli.seed, &cli.inspect, cli.json, &mut timings)
+        }
+        Some(Command::GenLeaves {
+            count,
+            leaves,
+            secrets,
+        }) => gen_leaves_cmd(count, cli.seed, &leaves, &secrets).map(|()| Vec::new()),
+        Some(Command::GenFixtures { output_dir }) => {
+            gen_fixtures_cmd(cli.seed, &output_dir, &mut timings).map(|()| Vec::new())
+        }
+        Some(Command::CompressKeys { keys, output }) => {
+            compress_keys_cmd(&keys, &output).map(|()| Vec::new())
+        }
+        Some(Command::WriteVk { keys, output }) => {
+            write_vk_cmd(&keys, &output).map(|()| Vec::new())
+        }
+        None => solve_cmd(
+            &cli.fixtures,
+            cli.seed,
+            &cli.inspect,
+            cli.json,
+            &mut timings,
+        ),
     };
 
-    let proof = Groth16::<MNT4_753>::prove(&pk, c.clone(), rng).unwrap();
+    if cli.json {
+        print_json(&result, &timings);
+        return result.map(|_| ());
+    }
+    result?;
+
+    if cli.timings {
+        timings.print();
+    }
+    Ok(())
+}
+
+/// Proves the spend of the leaf at `i`, or with `root_snapshot`, a root
+/// snapshot and the path saved with it, of the leaf that path opens.
+fn prove_cmd(
+    fixtures: &Fixtures,
+    i: usize,
+    proof_path: &str,
+    root_snapshot: Option<(&str, &str)>,
+    seed: u64,
+    options: &Inspect,
+    timings: &mut Timings,
+) -> Result<()> {
+    let secret: Zeroizing<MNT4BigFr> = read_secret(&fixtures.secret)?;
+    let params = CircuitParams::poseidon();
+
+    let nullifier = nullifier(&params.leaf, *secret, LEGACY_DOMAIN)
+        .map_err(crypto_err("could not compute the nullifier"))?;
+
+    let (root, tree_proof, height) = match root_snapshot {
+        Some((snapshot_path, path_path)) => {
+            let root = load_root_snapshot(snapshot_path)?;
+            let tree_proof: MerklePath<MntMerkleTreeParams> = from_file(path_path)?;
+            let leads_to_root = tree_proof
+                .verify(
+                    &params.leaf,
+                    &params.two_to_one,
+                    &root,
+                    public_key_leaf(&public_key(*secret)),
+                )
+                .map_err(crypto_err("could not check the path"))?;
+            ensure!(
+                leads_to_root,
+                "the path in {} does not lead from the secret's leaf to the root in {}",
+                path_path,
+                snapshot_path
+            );
+            let height = path_height(&tree_proof);
+            (root, tree_proof, height)
+        }
+        None => {
+            let leaves: Vec<Vec<MNT4BigFr>> = from_file(&fixtures.leaves)?;
+            // Fail before building the tree, which takes a while
+            leaf_at(&leaves, i)?;
+
+            let tree = timings.time(TREE, || merkle_tree(&params, &leaves))?;
+            let tree_proof =
+                validate_witness(&params.leaf, &params.two_to_one, &tree, &leaves, i, *secret)?;
+            (tree.root(), tree_proof, tree_depth(leaves.len()))
+        }
+    };
 
-    assert!(Groth16::<MNT4_753>::verify(&vk, &vec![root, nullifier], &proof).unwrap());
+    let c = params
+        .spend_circuit::<Mnt753Cycle>(root, tree_proof, secret, nullifier)
+        .with_strict_nullifier(false)
+        .with_height(height);
 
-    /* Enter your solution here */
+    inspect(options, &c)?;
+    if options.check {
+        return print_check("the spend", &c);
+    }
 
-    let nullifier_hack = MNT4BigFr::from(0);
-    let secret_hack = MNT4BigFr::from(0);
+    let public_inputs = public_inputs(&c);
+    let (pk, _) = load_keys(fixtures)?;
+    let proof = timings
+        .time(PROVING, || prove_with_seed(&pk, c, seed))
+        .context("proving failed")?;
 
-    /* End of solution */
+    if fixtures.compressed {
+        save_proof_compressed(proof_path, &proof, &public_inputs)?;
+    } else {
+        save_proof(proof_path, &proof, &public_inputs)?;
+    }
+    Ok(())
+}
+
+fn verify_cmd(
+    fixtures: &Fixtures,
+    proof_path: &str,
+    root_snapshot: Option<&str>,
+    nullifiers_path: Option<&str>,
+    json: bool,
+    timings: &mut Timings,
+) -> Result<Vec<Vec<MNT4BigFr>>> {
+    let (_, vk) = load_keys(fixtures)?;
+    let (proof, public_inputs) = load_proof::<MNT4_753>(proof_path)?;
+
+    let root = match root_snapshot {
+        Some(snapshot_path) => load_root_snapshot(snapshot_path)?,
+        None => {
+            let leaves: Vec<Vec<MNT4BigFr>> = from_file(&fixtures.leaves)?;
+            let params = CircuitParams::poseidon();
+            timings.time(TREE, || merkle_tree(&params, &leaves))?.root()
+        }
+    };
+
+    ensure!(
+        public_inputs.first() == Some(&root),
+        "the proof is for a different root"
+    );
+    timings.time(VERIFYING, || {
+        verify_public_inputs(&vk, &public_inputs, &proof)
+    })?;
+
+    if let Some(nullifiers_path) = nullifiers_path {
+        let mut spent = if Path::new(nullifiers_path).exists() {
+            NullifierSet::load(nullifiers_path)?
+        } else {
+            NullifierSet::new()
+        };
+        let nullifier = public_inputs
+            .get(1)
+            .context("the proof has no nullifier input")?;
+        ensure!(
+            spent.insert(nullifier),
+            "the proof is valid, but its nullifier was already spent"
+        );
+        spent.save(nullifiers_path)?;
+    }
+    if !json {
+        println!("proof accepted");
+    }
+    Ok(vec![public_inputs])
+}
+
+fn inspect_cmd(proof_path: &str, vk_path: Option<&str>) -> Result<()> {
+    let (proof, public_inputs) = load_proof::<MNT4_753>(proof_path)?;
+    let size = std::fs::metadata(proof_path)
+        .with_context(|| format!("could not read {}", proof_path))?
+        .len();
+
+    println!("{}: {} bytes", proof_path, size);
+    println!("{} public inputs", public_inputs.len());
+    for (i, input) in public_inputs_to_strings(&public_inputs).iter().enumerate() {
+        println!("  [{}] {}", i, input);
+    }
+
+    if let Some(vk_path) = vk_path {
+        let vk: <Groth16<MNT4_753> as SNARK<MNT4BigFr>>::VerifyingKey = from_file(vk_path)?;
+        let fingerprint = vk_fingerprint(&vk);
+        println!(
+            "verifying key {}{}",
+            hex(&fingerprint),
+            if fingerprint == PUZZLE_VK_FINGERPRINT {
+                " (the puzzle's)"
+            } else {
+                ""
+            }
+        );
+        match verify_public_inputs(&vk, &public_inputs, &proof) {
+            Ok(()) => println!("the proof verifies under it"),
+            Err(e) => println!("the proof does not verify under it: {}", e),
+        }
+    }
+    Ok(())
+}
 
-    assert_ne!(nullifier, nullifier_hack);
+fn gen_leaves_cmd(count: usize, seed: u64, leaves_path: &str, secrets_path: &str) -> Result<()> {
+    let rng = &mut ark_std::rand::rngs::StdRng::seed_from_u64(seed);
+    let (leaves, secrets) = generate_leaves(count, rng);
 
-    let c2 = SpendCircuit {
-        leaf_params: leaf_crh_params.clone(),
-        two_to_one_params: two_to_one_crh_params.clone(),
-        root: root.clone(),
-        proof: tree_proof.clone(),
-        nullifier: nullifier_hack.clone(),
-        secret: secret_hack.clone(),
+    to_file(leaves_path, &leaves)?;
+    to_file(secrets_path, &secrets)?;
+    Ok(())
+}
+
+/// Writes fixtures of the puzzle's shape: the leaked secret owns the leaf at
+/// the index `prove` defaults to, and the keys are for the legacy circuit
+/// `solve` proves.
+fn gen_fixtures_cmd(seed: u64, output_dir: &str, timings: &mut Timings) -> Result<()> {
+    let rng = &mut ark_std::rand::rngs::StdRng::seed_from_u64(seed);
+    let (leaves, secrets) = generate_leaves(FIXTURE_LEAVES, rng);
+    let leaked_secret = Zeroizing::new(secrets[FIXTURE_LEAKED_INDEX]);
+
+    let params = CircuitParams::poseidon();
+    let tree = timings.time(TREE, || merkle_tree(&params, &leaves))?;
+    let tree_proof = validate_witness(
+        &params.leaf,
+        &params.two_to_one,
+        &tree,
+        &leaves,
+        FIXTURE_LEAKED_INDEX,
+        *leaked_secret,
+    )?;
+    let nullifier = nullifier(&params.leaf, *leaked_secret, LEGACY_DOMAIN)
+        .map_err(crypto_err("could not compute the nullifier"))?;
+
+    let c = params
+        .spend_circuit::<Mnt753Cycle>(tree.root(), tree_proof, leaked_secret.clone(), nullifier)
+        .with_strict_nullifier(false)
+        .with_height(tree_depth(leaves.len()));
+    let (pk, vk) = timings
+        .time(SETUP, || setup(c, rng))
+        .context("setup failed")?;
+
+    let path = |name: &str| {
+        Path::new(output_dir)
+            .join(name)
+            .to_string_lossy()
+            .into_owned()
     };
+    to_file(&path("leaves.bin"), &leaves)?;
+    to_file(&path("leaked_secret.bin"), &*leaked_secret)?;
+    save_keys(&path("proof_keys.bin"), &pk, &vk)?;
+    Ok(())
+}
+
+/// Saves the root of the leaves and, with `path`, the path of the leaf at an
+/// index, which `prove` needs once the leaves have moved on.
+fn snapshot_root_cmd(
+    leaves_path: &str,
+    output_path: &str,
+    path: Option<(usize, &str)>,
+    timings: &mut Timings,
+) -> Result<()> {
+    let leaves: Vec<Vec<MNT4BigFr>> = from_file(leaves_path)?;
+    let params = CircuitParams::poseidon();
+    let tree = timings.time(TREE, || merkle_tree(&params, &leaves))?;
+
+    if let Some((index, path_path)) = path {
+        leaf_at(&leaves, index)?;
+        let tree_proof = tree
+            .generate_proof(index)
+            .map_err(crypto_err("could not open the leaf"))?;
+        to_file(path_path, &tree_proof)?;
+    }
+    save_root_snapshot(output_path, &tree.root())?;
+    Ok(())
+}
+
+fn compress_keys_cmd(keys_path: &str, output_path: &str) -> Result<()> {
+    let (pk, vk) = read_keys(keys_path, Compress::No)?;
+    save_keys_compressed(output_path, &pk, &vk)?;
+    Ok(())
+}
+
+fn write_vk_cmd(keys_path: &str, output_path: &str) -> Result<()> {
+    let (_, vk) = read_keys(keys_path, Compress::No)?;
+    to_file(output_path, &vk)?;
+    Ok(())
+}
 
-    let proof = Groth16::<MNT4_753>::prove(&pk, c2.clone(), rng).unwrap();
+fn solve_cmd(
+    fixtures: &Fixtures,
+    seed: u64,
+    options: &Inspect,
+    json: bool,
+    timings: &mut Timings,
+) -> Result<Vec<Vec<MNT4BigFr>>> {
+    if !json {
+        welcome();
+        puzzle(PUZZLE_DESCRIPTION);
+    }
+
+    let rng = &mut ark_std::rand::rngs::StdRng::seed_from_u64(seed);
+
+    let leaves: Vec<Vec<MNT4BigFr>> = from_file(&fixtures.leaves)?;
+    let leaked_secret: Zeroizing<MNT4BigFr> = read_secret(&fixtures.secret)?;
+    let keys = if options.check {
+        None
+    } else {
+        Some(load_keys(fixtures)?)
+    };
 
-    assert!(Groth16::<MNT4_753>::verify(&vk, &vec![root, nullifier_hack], &proof).unwrap());
+    let params = CircuitParams::poseidon();
+    let i = find_leaf_index(&leaves, *leaked_secret).context("the leaked secret owns no leaf")?;
+
+    let nullifier = nullifier(&params.leaf, *leaked_secret, LEGACY_DOMAIN)
+        .map_err(crypto_err("could not compute the nullifier"))?;
+
+    let tree = timings.time(TREE, || merkle_tree(&params, &leaves))?;
+    let root = tree.root();
+
+    let tree_proof = validate_witness(
+        &params.leaf,
+        &params.two_to_one,
+        &tree,
+        &leaves,
+        i,
+        *leaked_secret,
+    )?;
+
+    // proof_keys.bin was generated for the original, non-strict circuit
+    let c = params
+        .spend_circuit::<Mnt753Cycle>(root, tree_proof.clone(), leaked_secret.clone(), nullifier)
+        .with_strict_nullifier(false)
+        .with_height(tree_depth(leaves.len()));
+
+    inspect(options, &c)?;
+
+    match &keys {
+        Some((pk, vk)) => {
+            let proof = timings
+                .time(PROVING, || prove(pk, c.clone(), rng))
+                .context("proving the honest spend failed")?;
+            timings
+                .time(VERIFYING, || verify_spend(vk, &root, &nullifier, &proof))
+                .context("the honest spend was not accepted")?;
+        }
+        None => print_check("the honest spend", &c)?,
+    }
+
+    /* Enter your solution here */
+
+    let (secret_hack, nullifier_hack) = solve(&leaves, *leaked_secret)
+        .context("the leaked secret owns no leaf or has no alternate representative")?;
+
+    /* End of solution */
+
+    ensure!(
+        nullifier != nullifier_hack,
+        "the double spend reuses the nullifier"
+    );
+
+    let c2 = params
+        .spend_circuit::<Mnt753Cycle>(
+            root,
+            tree_proof.clone(),
+            Zeroizing::new(secret_hack),
+            nullifier_hack,
+        )
+        .with_strict_nullifier(false)
+        .with_height(tree_depth(leaves.len()));
+
+    match &keys {
+        Some((pk, vk)) => {
+            let proof = timings
+                .time(PROVING, || prove(pk, c2.clone(), rng))
+                .context("proving the double spend failed")?;
+            timings
+                .time(VERIFYING, || {
+                    verify_spend(vk, &root, &nullifier_hack, &proof)
+                })
+                .context("the double spend was not accepted")?;
+        }
+        None => print_check("the double spend", &c2)?,
+    }
+    Ok(vec![public_inputs(&c), public_inputs(&c2)])
 }
 
 const PUZZLE_DESCRIPTION: &str = r"
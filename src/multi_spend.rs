@@ -0,0 +1,96 @@
+use ark_crypto_primitives::merkle_tree::Path;
+use ark_crypto_primitives::sponge::poseidon::PoseidonConfig;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+
+use crate::{
+    enforce_all_distinct, CurveCycle, MerkleTreeParams, Mnt753Cycle, SpendGadget, LEGACY_DOMAIN,
+};
+
+pub type SpendInput<F> = (F, Path<MerkleTreeParams<F>>, F);
+
+/// Spends several notes against a shared root in one proof.
+///
+/// Each input is a `(secret, path, nullifier)` tuple. The public inputs are
+/// the root followed by the nullifiers, in input order.
+#[derive(Clone)]
+pub struct MultiSpendCircuit<C: CurveCycle = Mnt753Cycle> {
+    pub leaf_params: PoseidonConfig<C::ConstraintF>,
+    pub two_to_one_params: PoseidonConfig<C::ConstraintF>,
+    pub root: C::ConstraintF,
+    pub inputs: Vec<SpendInput<C::ConstraintF>>,
+    pub strict_nullifier: bool,
+    pub nullifier_domain: u64,
+}
+
+impl<C: CurveCycle> MultiSpendCircuit<C> {
+    pub fn new(
+        leaf_params: PoseidonConfig<C::ConstraintF>,
+        two_to_one_params: PoseidonConfig<C::ConstraintF>,
+        root: C::ConstraintF,
+        inputs: Vec<SpendInput<C::ConstraintF>>,
+    ) -> Self {
+        Self {
+            leaf_params,
+            two_to_one_params,
+            root,
+            inputs,
+            strict_nullifier: true,
+            nullifier_domain: LEGACY_DOMAIN,
+        }
+    }
+
+    pub fn with_strict_nullifier(mut self, strict_nullifier: bool) -> Self {
+        self.strict_nullifier = strict_nullifier;
+        self
+    }
+
+    pub fn with_nullifier_domain(mut self, nullifier_domain: u64) -> Self {
+        self.nullifier_domain = nullifier_domain;
+        self
+    }
+
+    pub fn public_inputs(&self) -> Vec<C::ConstraintF> {
+        let mut public_inputs = vec![self.root];
+        public_inputs.extend(self.inputs.iter().map(|(_, _, nullifier)| *nullifier));
+        public_inputs
+    }
+}
+
+impl<C: CurveCycle> ConstraintSynthesizer<C::ConstraintF> for MultiSpendCircuit<C> {
+    fn generate_constraints(
+        self,
+        cs: ConstraintSystemRef<C::ConstraintF>,
+    ) -> Result<(), SynthesisError> {
+        let root = FpVar::new_input(ark_relations::ns!(cs, "merkle_root"), || Ok(self.root))?;
+
+        let (leaf_crh_params_var, two_to_one_crh_params_var) = SpendGadget::<C>::allocate_params(
+            cs.clone(),
+            &self.leaf_params,
+            &self.two_to_one_params,
+        )?;
+
+        let gadget = SpendGadget::<C> {
+            cs,
+            leaf_crh_params_var: &leaf_crh_params_var,
+            two_to_one_crh_params_var: &two_to_one_crh_params_var,
+            root: &root,
+            strict_nullifier: self.strict_nullifier,
+            nullifier_domain: self.nullifier_domain,
+            positional_nullifier: false,
+            scalar_nullifier: false,
+        };
+
+        let nullifiers = self
+            .inputs
+            .iter()
+            .map(|(secret, proof, nullifier)| gadget.enforce(proof, *secret, *nullifier))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // The same note must not be spent twice within one proof
+        enforce_all_distinct(&nullifiers)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+use ark_crypto_primitives::crh::{CRHScheme, CRHSchemeGadget};
+use ark_crypto_primitives::sponge::{poseidon::PoseidonConfig, Absorb};
+use ark_ff::PrimeField;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_relations::r1cs::SynthesisError;
+use ark_std::rand::Rng;
+
+use crate::{ConstraintF, CrhParamsVar, HashFamily, PoseidonFamily};
+
+/// A note owned by the key with x-coordinate `pk_x`. The trapdoor `rho` makes
+/// two notes of the same value for the same key commit to different leaves.
+///
+/// A note is stored in the tree as the single-element leaf [`Note::leaf`],
+/// `[commitment]`, never as its fields. Only the [`commitment`] is public, so
+/// that is all a coordinator can insert, and every circuit that spends a note
+/// (`SpendCircuit::with_note`, `TransferCircuit`) recomputes the commitment
+/// from the witnessed `value` and `rho` and opens that leaf.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Note<F: PrimeField = ConstraintF> {
+    pub pk_x: F,
+    pub value: u64,
+    pub rho: F,
+}
+
+impl<F: PrimeField> Note<F> {
+    pub fn new(pk_x: F, value: u64, rho: F) -> Self {
+        Self { pk_x, value, rho }
+    }
+
+    /// A note with a freshly sampled trapdoor.
+    pub fn random<R: Rng>(pk_x: F, value: u64, rng: &mut R) -> Self {
+        Self::new(pk_x, value, F::rand(rng))
+    }
+}
+
+impl<F: PrimeField + Absorb> Note<F> {
+    /// The leaf the note is stored as, `[commitment(params, self)]`.
+    pub fn leaf(&self, params: &PoseidonConfig<F>) -> Result<Vec<F>, ark_crypto_primitives::Error> {
+        Ok(vec![commitment(params, self)?])
+    }
+}
+
+/// `H([pk_x, value, rho])`, the only element of the leaf `note` is stored as.
+pub fn commitment<F: PrimeField + Absorb>(
+    params: &PoseidonConfig<F>,
+    note: &Note<F>,
+) -> Result<F, ark_crypto_primitives::Error> {
+    commitment_with::<F, PoseidonFamily>(params, note)
+}
+
+pub fn commitment_var<F: PrimeField + Absorb>(
+    params: &CrhParamsVar<F>,
+    pk_x: FpVar<F>,
+    value: FpVar<F>,
+    rho: FpVar<F>,
+) -> Result<FpVar<F>, SynthesisError> {
+    commitment_var_with::<F, PoseidonFamily>(params, pk_x, value, rho)
+}
+
+/// [`commitment`] under the leaf hash of `H`.
+pub fn commitment_with<F: PrimeField, H: HashFamily<F>>(
+    params: &H::Parameters,
+    note: &Note<F>,
+) -> Result<F, ark_crypto_primitives::Error> {
+    H::Leaf::evaluate(params, vec![note.pk_x, F::from(note.value), note.rho])
+}
+
+/// [`commitment_var`] under the leaf hash of `H`.
+pub fn commitment_var_with<F: PrimeField, H: HashFamily<F>>(
+    params: &H::ParametersVar,
+    pk_x: FpVar<F>,
+    value: FpVar<F>,
+    rho: FpVar<F>,
+) -> Result<FpVar<F>, SynthesisError> {
+    H::LeafGadget::evaluate(params, &[pk_x, value, rho])
+}
@@ -0,0 +1,360 @@
+use ark_crypto_primitives::crh::{CRHScheme, CRHSchemeGadget};
+use ark_crypto_primitives::sponge::{poseidon::PoseidonConfig, Absorb};
+use ark_ff::{BigInteger, PrimeField, ToConstraintField};
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_r1cs_std::ToConstraintFieldGadget;
+use ark_relations::r1cs::SynthesisError;
+use blake2::{Blake2s, Digest};
+
+use std::fmt;
+
+use crate::{ConstraintF, CrhParamsVar, CurveCycle, HashFamily, Mnt753Cycle, PoseidonFamily};
+
+/// Domain 0 hashes the bare secret, which is how the puzzle fixtures were
+/// produced. Any other domain is absorbed before the secret.
+pub const LEGACY_DOMAIN: u64 = 0;
+
+/// Hashes `label` into a field element with Blake2s, so tags of different
+/// labels only collide with negligible probability.
+pub fn domain_tag(label: &str) -> ConstraintF {
+    ConstraintF::from_le_bytes_mod_order(&label_digest(label))
+}
+
+/// A nullifier domain named by `label`, for
+/// [`crate::SpendCircuit::with_nullifier_domain`]. Domains are absorbed as
+/// 64-bit integers, so this is the low 64 bits of the label's digest rather
+/// than the whole [`domain_tag`]. It is never [`LEGACY_DOMAIN`].
+pub fn named_domain(label: &str) -> u64 {
+    let digest = label_digest(label);
+    let mut low = [0u8; 8];
+    low.copy_from_slice(&digest[..8]);
+
+    u64::from_le_bytes(low).max(LEGACY_DOMAIN + 1)
+}
+
+/// The order in which a nullifier absorbs its inputs. Every layout puts the
+/// domain tag first, then the secret, then the leaf index, and both the
+/// native and in-circuit nullifiers are assembled by [`Self::assemble`], so
+/// the two can't disagree on the order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NullifierInput {
+    /// `[secret]`, the puzzle's nullifier under [`LEGACY_DOMAIN`].
+    Secret,
+    /// `[domain, secret]`.
+    Tagged { domain: u64 },
+    /// `[secret, index]`, see [`positional_nullifier`].
+    Positional,
+    /// `[domain, secret, index]`.
+    TaggedPositional { domain: u64 },
+}
+
+impl NullifierInput {
+    /// The layout of a nullifier under `domain`, positional or not. Only
+    /// domains other than [`LEGACY_DOMAIN`] are absorbed.
+    pub fn new(domain: u64, positional: bool) -> Self {
+        match (domain == LEGACY_DOMAIN, positional) {
+            (true, false) => NullifierInput::Secret,
+            (false, false) => NullifierInput::Tagged { domain },
+            (true, true) => NullifierInput::Positional,
+            (false, true) => NullifierInput::TaggedPositional { domain },
+        }
+    }
+
+    pub fn is_positional(&self) -> bool {
+        matches!(
+            self,
+            NullifierInput::Positional | NullifierInput::TaggedPositional { .. }
+        )
+    }
+
+    /// Lays out the input from the values of its fields, native field
+    /// elements or variables alike. `domain` turns the tag into a value, and
+    /// `index` is only read by positional layouts, which fail without it.
+    pub fn assemble<T>(
+        &self,
+        domain: impl FnOnce(u64) -> T,
+        secret: T,
+        index: Option<T>,
+    ) -> Result<Vec<T>, MissingLeafIndex> {
+        let mut input = Vec::with_capacity(3);
+        if let NullifierInput::Tagged { domain: tag }
+        | NullifierInput::TaggedPositional { domain: tag } = self
+        {
+            input.push(domain(*tag));
+        }
+        input.push(secret);
+        if self.is_positional() {
+            input.push(index.ok_or(MissingLeafIndex)?);
+        }
+        Ok(input)
+    }
+}
+
+/// A positional [`NullifierInput`] was assembled without the leaf index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MissingLeafIndex;
+
+impl fmt::Display for MissingLeafIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "positional nullifiers absorb the leaf index")
+    }
+}
+
+impl std::error::Error for MissingLeafIndex {}
+
+fn label_digest(label: &str) -> Vec<u8> {
+    let mut hasher = Blake2s::new();
+    hasher.update(b"gamma-ray domain");
+    hasher.update(label.as_bytes());
+    hasher.finalize().to_vec()
+}
+
+pub fn nullifier<F: PrimeField + Absorb>(
+    params: &PoseidonConfig<F>,
+    secret: F,
+    domain: u64,
+) -> Result<F, ark_crypto_primitives::Error> {
+    nullifier_with::<F, PoseidonFamily>(params, secret, domain)
+}
+
+/// [`nullifier`] under the leaf hash of `H`.
+pub fn nullifier_with<F: PrimeField, H: HashFamily<F>>(
+    params: &H::Parameters,
+    secret: F,
+    domain: u64,
+) -> Result<F, ark_crypto_primitives::Error> {
+    nullifier_with_layout::<F, H>(params, NullifierInput::new(domain, false), secret, None)
+}
+
+/// The nullifier of `secret` absorbed in `layout`, with `index` the position
+/// of the spent leaf for positional layouts.
+pub fn nullifier_with_layout<F: PrimeField, H: HashFamily<F>>(
+    params: &H::Parameters,
+    layout: NullifierInput,
+    secret: F,
+    index: Option<usize>,
+) -> Result<F, ark_crypto_primitives::Error> {
+    let index = index.map(|index| F::from(index as u64));
+    H::Leaf::evaluate(params, layout.assemble(F::from, secret, index)?)
+}
+
+/// Like [`nullifier`], also absorbing the index of the spent leaf after the
+/// secret, so notes of the same key at different positions get distinct
+/// nullifiers.
+pub fn positional_nullifier<F: PrimeField + Absorb>(
+    params: &PoseidonConfig<F>,
+    secret: F,
+    index: usize,
+    domain: u64,
+) -> Result<F, ark_crypto_primitives::Error> {
+    positional_nullifier_with::<F, PoseidonFamily>(params, secret, index, domain)
+}
+
+/// [`positional_nullifier`] under the leaf hash of `H`.
+pub fn positional_nullifier_with<F: PrimeField, H: HashFamily<F>>(
+    params: &H::Parameters,
+    secret: F,
+    index: usize,
+    domain: u64,
+) -> Result<F, ark_crypto_primitives::Error> {
+    nullifier_with_layout::<F, H>(
+        params,
+        NullifierInput::new(domain, true),
+        secret,
+        Some(index),
+    )
+}
+
+pub fn nullifier_var<F: PrimeField + Absorb>(
+    params: &CrhParamsVar<F>,
+    secret: FpVar<F>,
+    domain: u64,
+) -> Result<FpVar<F>, SynthesisError> {
+    nullifier_var_with::<F, PoseidonFamily>(params, secret, domain)
+}
+
+/// [`nullifier_var`] under the leaf hash of `H`.
+pub fn nullifier_var_with<F: PrimeField, H: HashFamily<F>>(
+    params: &H::ParametersVar,
+    secret: FpVar<F>,
+    domain: u64,
+) -> Result<FpVar<F>, SynthesisError> {
+    nullifier_var_with_layout::<F, H>(params, NullifierInput::new(domain, false), secret, None)
+}
+
+/// [`nullifier_with_layout`] in-circuit. A positional layout without an
+/// `index` is [`SynthesisError::AssignmentMissing`].
+pub fn nullifier_var_with_layout<F: PrimeField, H: HashFamily<F>>(
+    params: &H::ParametersVar,
+    layout: NullifierInput,
+    secret: FpVar<F>,
+    index: Option<FpVar<F>>,
+) -> Result<FpVar<F>, SynthesisError> {
+    let domain = |domain| FpVar::constant(F::from(domain));
+    let input = layout
+        .assemble(domain, secret, index)
+        .map_err(|_| SynthesisError::AssignmentMissing)?;
+    H::LeafGadget::evaluate(params, &input)
+}
+
+/// [`positional_nullifier`] in-circuit, for the leaf at position `index`.
+pub fn positional_nullifier_var_with<F: PrimeField, H: HashFamily<F>>(
+    params: &H::ParametersVar,
+    secret: FpVar<F>,
+    index: FpVar<F>,
+    domain: u64,
+) -> Result<FpVar<F>, SynthesisError> {
+    nullifier_var_with_layout::<F, H>(
+        params,
+        NullifierInput::new(domain, true),
+        secret,
+        Some(index),
+    )
+}
+
+/// The scalar a spend of `secret` multiplies the generator by, reduced into
+/// `C::ScalarField` and negated if it is above `(r - 1) / 2`.
+///
+/// `s`, `s + r` and `r - s` all own the leaf `[pk.x]`, and all have the same
+/// canonical scalar, so a nullifier derived from it can't be changed by
+/// spending an alias of the secret.
+pub fn canonical_scalar<C: CurveCycle>(secret: C::ConstraintF) -> C::ScalarField {
+    let scalar = C::ScalarField::from_le_bytes_mod_order(&secret.into_bigint().to_bytes_le());
+    if scalar.into_bigint() <= C::ScalarField::MODULUS_MINUS_ONE_DIV_TWO {
+        scalar
+    } else {
+        -scalar
+    }
+}
+
+/// The secret a strict [`crate::SpendCircuit`] spends the leaf of `secret`
+/// with: [`canonical_scalar`] as a constraint field element. It owns the
+/// same leaf, and is `secret` itself unless that is above `(r - 1) / 2`.
+pub fn canonical_secret<C: CurveCycle>(secret: C::ConstraintF) -> C::ConstraintF {
+    C::ConstraintF::from_le_bytes_mod_order(
+        &canonical_scalar::<C>(secret).into_bigint().to_bytes_le(),
+    )
+}
+
+/// Like [`nullifier`], hashing the canonical encoding of
+/// [`canonical_scalar`] instead of the secret as a field element, for a
+/// [`crate::SpendCircuit::with_scalar_nullifier`].
+pub fn scalar_nullifier(
+    params: &PoseidonConfig<ConstraintF>,
+    secret: ConstraintF,
+    domain: u64,
+) -> Result<ConstraintF, ark_crypto_primitives::Error> {
+    scalar_nullifier_with_layout::<Mnt753Cycle, PoseidonFamily>(
+        params,
+        NullifierInput::new(domain, false),
+        secret,
+        None,
+    )
+}
+
+/// [`nullifier_with_layout`] over the little-endian bytes of
+/// [`canonical_scalar`], as arkworks serializes it, packed into field
+/// elements by `ToConstraintField` in place of the secret.
+pub fn scalar_nullifier_with_layout<C: CurveCycle, H: HashFamily<C::ConstraintF>>(
+    params: &H::Parameters,
+    layout: NullifierInput,
+    secret: C::ConstraintF,
+    index: Option<usize>,
+) -> Result<C::ConstraintF, ark_crypto_primitives::Error> {
+    let bytes = canonical_scalar::<C>(secret).into_bigint().to_bytes_le();
+    let packed = bytes
+        .to_field_elements()
+        .expect("bytes always pack into field elements");
+
+    let index = index.map(|index| vec![C::ConstraintF::from(index as u64)]);
+    let domain = |domain| vec![C::ConstraintF::from(domain)];
+    H::Leaf::evaluate(params, layout.assemble(domain, packed, index)?.concat())
+}
+
+/// [`scalar_nullifier_with_layout`] in-circuit. The canonical scalar is
+/// witnessed and constrained to be `secret` or `r - secret`, which takes
+/// `secret <= r` as every spend enforces.
+pub fn scalar_nullifier_var_with_layout<C: CurveCycle, H: HashFamily<C::ConstraintF>>(
+    params: &H::ParametersVar,
+    layout: NullifierInput,
+    secret: &FpVar<C::ConstraintF>,
+    index: Option<FpVar<C::ConstraintF>>,
+) -> Result<FpVar<C::ConstraintF>, SynthesisError> {
+    let cs = secret.cs();
+    let to_constraint_field = |scalar: C::ScalarField| {
+        C::ConstraintF::from_le_bytes_mod_order(&scalar.into_bigint().to_bytes_le())
+    };
+
+    let scalar = FpVar::new_witness(ark_relations::ns!(cs, "canonical_scalar"), || {
+        Ok(to_constraint_field(canonical_scalar::<C>(secret.value()?)))
+    })?;
+    let scalar_bits = scalar.to_bits_le()?;
+    Boolean::enforce_smaller_or_equal_than_le(
+        &scalar_bits,
+        C::ScalarField::MODULUS_MINUS_ONE_DIV_TWO,
+    )?;
+
+    // Both roots are below the constraint field's modulus, so the product
+    // only vanishes for `scalar == secret` or `scalar + secret == r`, and at
+    // most one of those is in the lower half
+    let modulus = FpVar::constant(C::ConstraintF::from_le_bytes_mod_order(
+        &C::ScalarField::MODULUS.to_bytes_le(),
+    ));
+    (&scalar - secret).mul_equals(&(&scalar + secret - modulus), &FpVar::zero())?;
+
+    // The bits above the scalar modulus are zero, so the encoding can be cut
+    // or padded to its length
+    let byte_len = C::ScalarField::MODULUS.to_bytes_le().len();
+    let bits: Vec<_> = scalar_bits
+        .into_iter()
+        .chain(std::iter::repeat(Boolean::constant(false)))
+        .take(byte_len * 8)
+        .collect();
+    let bytes: Vec<_> = bits.chunks(8).map(UInt8::from_bits_le).collect();
+    let packed = bytes.as_slice().to_constraint_field()?;
+
+    let index = index.map(|index| vec![index]);
+    let domain = |domain| vec![FpVar::constant(C::ConstraintF::from(domain))];
+    let input = layout
+        .assemble(domain, packed, index)
+        .map_err(|_| SynthesisError::AssignmentMissing)?;
+    H::LeafGadget::evaluate(params, &input.concat())
+}
+
+/// Checks `claimed_nullifier` against the secret under [`LEGACY_DOMAIN`], as
+/// a [`crate::SpendCircuit`] with the default domain would.
+pub fn check_nullifier<F: PrimeField + Absorb>(
+    params: &PoseidonConfig<F>,
+    secret: F,
+    claimed_nullifier: F,
+) -> bool {
+    nullifier(params, secret, LEGACY_DOMAIN)
+        .map(|nullifier| nullifier == claimed_nullifier)
+        .unwrap_or(false)
+}
+
+/// The nullifier an honest spend of each of `secrets` reveals under
+/// [`LEGACY_DOMAIN`], in the same order: the ground truth to compare the
+/// nullifiers of proofs against.
+pub fn expected_nullifiers(
+    params: &PoseidonConfig<ConstraintF>,
+    secrets: &[ConstraintF],
+) -> Result<Vec<ConstraintF>, ark_crypto_primitives::Error> {
+    secrets
+        .iter()
+        .map(|secret| nullifier(params, *secret, LEGACY_DOMAIN))
+        .collect()
+}
+
+/// Enforces that no two of `values` are equal, so one proof can't count the
+/// same nullifier twice. Every pair is compared, which costs `O(n^2)`
+/// constraints: 4 per pair, 24 for four values.
+pub fn enforce_all_distinct<F: PrimeField>(values: &[FpVar<F>]) -> Result<(), SynthesisError> {
+    for (i, a) in values.iter().enumerate() {
+        for b in &values[i + 1..] {
+            a.is_eq(b)?.enforce_equal(&Boolean::constant(false))?;
+        }
+    }
+    Ok(())
+}
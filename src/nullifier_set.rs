@@ -0,0 +1,67 @@
+use ark_serialize::CanonicalSerialize;
+
+use std::collections::HashSet;
+
+use crate::ConstraintF;
+#[cfg(feature = "native-io")]
+use crate::{from_file, to_file, LoadError, SaveError};
+
+/// The nullifiers already spent. A valid proof whose nullifier is in the set
+/// is a replay: the circuit can only tie a nullifier to its secret, so it's
+/// up to the verifier to remember which ones it has seen.
+///
+/// Nullifiers are kept in their compressed encoding.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NullifierSet {
+    seen: HashSet<Vec<u8>>,
+}
+
+fn encode(nullifier: &ConstraintF) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    nullifier
+        .serialize_compressed(&mut buffer)
+        .expect("a field element serializes into a vector");
+    buffer
+}
+
+impl NullifierSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records `nullifier` as spent, returning `false` if it already was.
+    pub fn insert(&mut self, nullifier: &ConstraintF) -> bool {
+        self.seen.insert(encode(nullifier))
+    }
+
+    pub fn contains(&self, nullifier: &ConstraintF) -> bool {
+        self.seen.contains(&encode(nullifier))
+    }
+
+    pub fn len(&self) -> usize {
+        self.seen.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.seen.is_empty()
+    }
+}
+
+#[cfg(feature = "native-io")]
+impl NullifierSet {
+    /// Reads a set written by [`Self::save`].
+    pub fn load(path: &str) -> Result<Self, LoadError> {
+        let seen: Vec<Vec<u8>> = from_file(path)?;
+        Ok(Self {
+            seen: seen.into_iter().collect(),
+        })
+    }
+
+    /// Writes the encodings in sorted order, so the same set always gives
+    /// the same file.
+    pub fn save(&self, path: &str) -> Result<(), SaveError> {
+        let mut seen: Vec<Vec<u8>> = self.seen.iter().cloned().collect();
+        seen.sort();
+        to_file(path, &seen)
+    }
+}
@@ -0,0 +1,101 @@
+use ark_crypto_primitives::merkle_tree::{MerkleTree, Path};
+use ark_ff::PrimeField;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use ark_std::borrow::Borrow;
+
+use crate::{
+    poseidon_parameters, ConstraintF, CurveCycle, HashFamily, MerkleTreeParams, PoseidonFamily,
+    SpendCircuit, TreeError, Zeroizing,
+};
+
+/// The hash parameters of a tree and of the circuits that open it. Building
+/// the tree and allocating the circuit constants from the same value is what
+/// keeps the native and in-circuit hashes from drifting apart.
+#[derive(Clone)]
+pub struct CircuitParams<F: PrimeField = ConstraintF, H: HashFamily<F> = PoseidonFamily> {
+    pub leaf: H::Parameters,
+    pub two_to_one: H::Parameters,
+}
+
+impl CircuitParams {
+    /// The puzzle's parameters: [`poseidon_parameters`] for both hashes.
+    pub fn poseidon() -> Self {
+        let leaf = poseidon_parameters::poseidon_parameters();
+        Self {
+            two_to_one: leaf.clone(),
+            leaf,
+        }
+    }
+}
+
+impl<F: PrimeField, H: HashFamily<F>> CircuitParams<F, H> {
+    pub fn new(leaf: H::Parameters, two_to_one: H::Parameters) -> Self {
+        Self { leaf, two_to_one }
+    }
+
+    /// Builds the tree of `leaves`, which must already be padded to a power
+    /// of two of at least two, as the arkworks tree panics otherwise. See
+    /// [`crate::build_tree`] to pad them.
+    pub fn merkle_tree<L: Borrow<[F]>>(
+        &self,
+        leaves: impl IntoIterator<Item = L>,
+    ) -> Result<MerkleTree<MerkleTreeParams<F, H>>, TreeError> {
+        let leaves: Vec<L> = leaves.into_iter().collect();
+        if leaves.is_empty() {
+            return Err(TreeError::NoLeaves);
+        }
+        if leaves.len() < 2 || !leaves.len().is_power_of_two() {
+            return Err(TreeError::UnpaddedLeafCount {
+                leaves: leaves.len(),
+            });
+        }
+        MerkleTree::new(&self.leaf, &self.two_to_one, leaves).map_err(TreeError::Crypto)
+    }
+
+    /// Allocates both parameters as constants, in the order every spend
+    /// circuit allocates them.
+    pub fn allocate(
+        &self,
+        cs: ConstraintSystemRef<F>,
+    ) -> Result<(H::ParametersVar, H::ParametersVar), SynthesisError> {
+        allocate_params::<F, H>(cs, &self.leaf, &self.two_to_one)
+    }
+
+    /// A [`SpendCircuit`] with these parameters; see [`SpendCircuit::new`].
+    pub fn spend_circuit<C: CurveCycle<ConstraintF = F>>(
+        &self,
+        root: F,
+        proof: Path<MerkleTreeParams<F, H>>,
+        secret: Zeroizing<F>,
+        nullifier: F,
+    ) -> SpendCircuit<C, H> {
+        SpendCircuit::new(
+            self.leaf.clone(),
+            self.two_to_one.clone(),
+            root,
+            proof,
+            secret,
+            nullifier,
+        )
+    }
+}
+
+/// Allocates both hashes' parameters as constants. Constants take no
+/// variables or constraints, so the puzzle's leaf and two-to-one parameters,
+/// which are equal, cost nothing extra for being allocated twice.
+pub(crate) fn allocate_params<F: PrimeField, H: HashFamily<F>>(
+    cs: ConstraintSystemRef<F>,
+    leaf_params: &H::Parameters,
+    two_to_one_params: &H::Parameters,
+) -> Result<(H::ParametersVar, H::ParametersVar), SynthesisError> {
+    // Allocate Parameters for CRH
+    let leaf_crh_params_var =
+        H::ParametersVar::new_constant(ark_relations::ns!(cs, "leaf_crh_parameter"), leaf_params)?;
+    let two_to_one_crh_params_var = H::ParametersVar::new_constant(
+        ark_relations::ns!(cs, "two_to_one_crh_parameter"),
+        two_to_one_params,
+    )?;
+
+    Ok((leaf_crh_params_var, two_to_one_crh_params_var))
+}
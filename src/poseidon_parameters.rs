@@ -1,10 +1,22 @@
-use ark_crypto_primitives::sponge::poseidon::PoseidonConfig;
+use ark_crypto_primitives::crh::{poseidon::CRH, CRHScheme};
+use ark_crypto_primitives::sponge::poseidon::{find_poseidon_ark_and_mds, PoseidonConfig};
+use ark_ff::PrimeField;
 use ark_std::str::FromStr;
 use ark_std::{One, Zero};
 
+use std::fmt;
+
 use ark_mnt4_753::Fr as MNT4BigFr;
 type F = MNT4BigFr;
 
+/// Field elements absorbed per permutation by [`poseidon_parameters`].
+pub const RATE: usize = 2;
+/// Field elements of the state that are never absorbed into or squeezed.
+pub const CAPACITY: usize = 1;
+/// The state size, and so the size of the MDS matrix and each round's
+/// constants.
+pub const WIDTH: usize = RATE + CAPACITY;
+
 pub fn poseidon_parameters() -> PoseidonConfig<F> {
     let full_rounds = 8;
     let partial_rounds = 29;
@@ -652,5 +664,126 @@ pub fn poseidon_parameters() -> PoseidonConfig<F> {
         vec![F::zero(), F::one(), F::one()],
     ];
 
-    PoseidonConfig::<F>::new(full_rounds, partial_rounds, alpha, mds, ark, 2, 1)
+    PoseidonConfig::<F>::new(full_rounds, partial_rounds, alpha, mds, ark, RATE, CAPACITY)
+}
+
+/// [`poseidon_parameters`] if an input of `arity` elements is absorbed in a
+/// single permutation, or [`PoseidonParamsError::ArityExceedsRate`].
+///
+/// Longer inputs still hash correctly: the sponge absorbs them `RATE`
+/// elements at a time and permutes in between, as arkworks always does.
+/// This is for callers that size circuits assuming one permutation per hash.
+pub fn parameters_for_arity(arity: usize) -> Result<PoseidonConfig<F>, PoseidonParamsError> {
+    if arity > RATE {
+        return Err(PoseidonParamsError::ArityExceedsRate { arity, rate: RATE });
+    }
+    Ok(poseidon_parameters())
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PoseidonParamsError {
+    ZeroRate,
+    ArkShape { expected: (usize, usize) },
+    MdsShape { expected: usize },
+    ReferenceMismatch,
+    ArityExceedsRate { arity: usize, rate: usize },
+}
+
+impl fmt::Display for PoseidonParamsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PoseidonParamsError::ZeroRate => write!(f, "poseidon rate must be at least 1"),
+            PoseidonParamsError::ArkShape {
+                expected: (rows, cols),
+            } => write!(f, "round constants must be a {}x{} matrix", rows, cols),
+            PoseidonParamsError::MdsShape { expected } => {
+                write!(f, "mds must be a {}x{} matrix", expected, expected)
+            }
+            PoseidonParamsError::ReferenceMismatch => {
+                write!(
+                    f,
+                    "poseidon parameters do not reproduce the reference digest"
+                )
+            }
+            PoseidonParamsError::ArityExceedsRate { arity, rate } => write!(
+                f,
+                "{} inputs do not fit in one permutation of a rate {} sponge",
+                arity, rate
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PoseidonParamsError {}
+
+/// Derives round constants and an MDS matrix for a sponge of width `rate + 1`
+/// with the Grain LFSR from the Poseidon paper, for callers that want another
+/// rate or number of rounds than the puzzle's.
+///
+/// The output never equals [`poseidon_parameters`], even for its rate and
+/// rounds: the LFSR samples constants of the field's full 753-bit size, while
+/// the puzzle's table holds 254-bit constants and a hand-picked near-MDS
+/// matrix, so that table stays static and is pinned by
+/// [`validate_parameters`] instead.
+pub fn generate_poseidon_parameters(
+    rate: usize,
+    full_rounds: usize,
+    partial_rounds: usize,
+    alpha: u64,
+) -> Result<PoseidonConfig<F>, PoseidonParamsError> {
+    if rate == 0 {
+        return Err(PoseidonParamsError::ZeroRate);
+    }
+
+    let (ark, mds) = find_poseidon_ark_and_mds::<F>(
+        F::MODULUS_BIT_SIZE as u64,
+        rate,
+        full_rounds as u64,
+        partial_rounds as u64,
+        0,
+    );
+    let params = PoseidonConfig::<F>::new(full_rounds, partial_rounds, alpha, mds, ark, rate, 1);
+    validate_shape(&params)?;
+
+    Ok(params)
+}
+
+/// `H([1, 2])` under [`poseidon_parameters`].
+const REFERENCE_DIGEST: &str = "35796444448934800399722927598670123036726383729167388435637432127639127460170044039116191526820867467406550569540258941885668286483588148485237477555282589160670597442978293551164831302697783653003604757841076461609699256973235";
+
+/// Checks that `params` has the shape of the puzzle's sponge and hashes the
+/// reference input to the committed digest, catching a table that was
+/// edited or truncated by accident.
+pub fn validate_parameters(params: &PoseidonConfig<F>) -> Result<(), PoseidonParamsError> {
+    if params.rate == 0 {
+        return Err(PoseidonParamsError::ZeroRate);
+    }
+    validate_shape(params)?;
+
+    let digest = CRH::<F>::evaluate(params, vec![F::from(1u64), F::from(2u64)])
+        .map_err(|_| PoseidonParamsError::ReferenceMismatch)?;
+    if digest != F::from_str(REFERENCE_DIGEST).map_err(|_| ()).unwrap() {
+        return Err(PoseidonParamsError::ReferenceMismatch);
+    }
+
+    Ok(())
+}
+
+/// Checks that `params` has one row of round constants per round and an MDS
+/// matrix, each as wide as the sponge state.
+fn validate_shape(params: &PoseidonConfig<F>) -> Result<(), PoseidonParamsError> {
+    let (ark, mds) = (&params.ark, &params.mds);
+    let rounds = params.full_rounds + params.partial_rounds;
+    let width = params.rate + params.capacity;
+
+    if ark.len() != rounds || ark.iter().any(|row| row.len() != width) {
+        return Err(PoseidonParamsError::ArkShape {
+            expected: (rounds, width),
+        });
+    }
+    if mds.len() != width || mds.iter().any(|row| row.len() != width) {
+        return Err(PoseidonParamsError::MdsShape { expected: width });
+    }
+
+    Ok(())
 }
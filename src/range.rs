@@ -0,0 +1,33 @@
+use ark_ff::PrimeField;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::SynthesisError;
+
+/// Enforces `value < 2^bits` by decomposing it into its canonical bits and
+/// requiring every bit from `bits` up to be zero.
+///
+/// `bits` must be below the field's bit size, past which the check would
+/// accept everything; it returns [`SynthesisError::Unsatisfiable`] otherwise.
+pub fn enforce_in_range<F: PrimeField>(
+    value: &FpVar<F>,
+    bits: usize,
+) -> Result<(), SynthesisError> {
+    if bits >= F::MODULUS_BIT_SIZE as usize {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+
+    for bit in &value.to_bits_le()?[bits..] {
+        bit.enforce_equal(&Boolean::constant(false))?;
+    }
+    Ok(())
+}
+
+/// Enforces that the little-endian `value_bits` encode a value below
+/// `F::MODULUS`, the unique canonical representative of a field element.
+/// Bits from gadgets like `to_non_unique_bits_le` may also encode
+/// `value + MODULUS`, which this rejects.
+pub fn enforce_canonical<F: PrimeField>(value_bits: &[Boolean<F>]) -> Result<(), SynthesisError> {
+    // `-1` is the largest canonical value, `MODULUS - 1`
+    Boolean::enforce_smaller_or_equal_than_le(value_bits, (-F::one()).into_bigint())?;
+    Ok(())
+}
@@ -0,0 +1,334 @@
+//! A Rescue-Prime sponge, to compare circuit costs against Poseidon.
+//!
+//! Each round raises every state element to `alpha`, mixes with the MDS
+//! matrix and adds constants, then does the same with the inverse power
+//! `1/alpha`. In-circuit the inverse power is a witness checked with one
+//! forward power, so both halves of a round cost the same.
+//!
+//! The round constants are expanded from a label with Blake2s rather than
+//! the SHAKE-256 stream of the Rescue-Prime specification, so digests don't
+//! match other implementations. This is for measuring constraints, not a
+//! vetted instantiation.
+
+use ark_crypto_primitives::crh::{
+    CRHScheme, CRHSchemeGadget, TwoToOneCRHScheme, TwoToOneCRHSchemeGadget,
+};
+use ark_ff::PrimeField;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{Namespace, SynthesisError};
+use ark_std::borrow::Borrow;
+use ark_std::marker::PhantomData;
+use ark_std::rand::Rng;
+use blake2::{Blake2s, Digest};
+use num_bigint::BigUint;
+
+/// The exponents tried for the S-box, smallest first. `x^alpha` is only a
+/// permutation when `alpha` is coprime to `p - 1`.
+const ALPHA_CANDIDATES: [u64; 8] = [3, 5, 7, 11, 13, 17, 19, 23];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RescueConfig<F: PrimeField> {
+    pub rate: usize,
+    pub capacity: usize,
+    pub rounds: usize,
+    pub alpha: u64,
+    /// `alpha^-1 mod p - 1`, as little-endian limbs.
+    pub alpha_inv: Vec<u64>,
+    pub mds: Vec<Vec<F>>,
+    /// Two rows per round, one after each half.
+    pub ark: Vec<Vec<F>>,
+}
+
+impl<F: PrimeField> RescueConfig<F> {
+    /// A sponge absorbing `rate` elements per permutation with a capacity of
+    /// one. Panics if no exponent in `ALPHA_CANDIDATES` is coprime to
+    /// `p - 1`, which no field this crate uses hits.
+    pub fn new(rate: usize, rounds: usize) -> Self {
+        let capacity = 1;
+        let width = rate + capacity;
+
+        let modulus: BigUint = F::MODULUS.into();
+        let order = modulus - 1u64;
+        let alpha = ALPHA_CANDIDATES
+            .into_iter()
+            .find(|alpha| &order % *alpha != BigUint::from(0u64))
+            .expect("no small S-box exponent is coprime to p - 1");
+        // `alpha * alpha_inv = k (p - 1) + 1` for exactly one `k < alpha`
+        let alpha_inv = (1..alpha)
+            .map(|k| &order * k + 1u64)
+            .find(|multiple| multiple % alpha == BigUint::from(0u64))
+            .map(|multiple| multiple / alpha)
+            .expect("alpha is coprime to p - 1")
+            .to_u64_digits();
+
+        // A Cauchy matrix `1 / (x_i + y_j)` with distinct `x_i` and `y_j` is MDS
+        let mds = (0..width)
+            .map(|i| {
+                (0..width)
+                    .map(|j| {
+                        F::from((i + width + j) as u64)
+                            .inverse()
+                            .expect("entries are between width and 3 width")
+                    })
+                    .collect()
+            })
+            .collect();
+        let ark = (0..2 * rounds)
+            .map(|row| (0..width).map(|i| round_constant(row, i)).collect())
+            .collect();
+
+        RescueConfig {
+            rate,
+            capacity,
+            rounds,
+            alpha,
+            alpha_inv,
+            mds,
+            ark,
+        }
+    }
+
+    /// The width-3 sponge that stands in for the Poseidon table.
+    pub fn default_for_tree() -> Self {
+        Self::new(2, 8)
+    }
+
+    fn width(&self) -> usize {
+        self.rate + self.capacity
+    }
+}
+
+fn round_constant<F: PrimeField>(row: usize, i: usize) -> F {
+    // Expanded well past the modulus, like `secret_from_seed`
+    let bytes_needed = (F::MODULUS_BIT_SIZE as usize + 128) / 8;
+    let mut bytes = Vec::with_capacity(bytes_needed + 32);
+    let mut counter = 0u32;
+    while bytes.len() < bytes_needed {
+        let mut hasher = Blake2s::new();
+        hasher.update(b"gamma-ray rescue");
+        hasher.update((row as u64).to_le_bytes());
+        hasher.update((i as u64).to_le_bytes());
+        hasher.update(counter.to_le_bytes());
+        bytes.extend_from_slice(&hasher.finalize());
+        counter += 1;
+    }
+
+    F::from_le_bytes_mod_order(&bytes)
+}
+
+fn mix<F: PrimeField>(params: &RescueConfig<F>, state: &[F], constants: &[F]) -> Vec<F> {
+    params
+        .mds
+        .iter()
+        .zip(constants)
+        .map(|(row, c)| row.iter().zip(state).map(|(m, s)| *m * s).sum::<F>() + c)
+        .collect()
+}
+
+fn permute<F: PrimeField>(params: &RescueConfig<F>, state: &mut Vec<F>) {
+    for round in params.ark.chunks(2) {
+        for x in state.iter_mut() {
+            *x = x.pow([params.alpha]);
+        }
+        *state = mix(params, state, &round[0]);
+        for x in state.iter_mut() {
+            *x = x.pow(&params.alpha_inv);
+        }
+        *state = mix(params, state, &round[1]);
+    }
+}
+
+fn sponge<F: PrimeField>(params: &RescueConfig<F>, input: &[F]) -> F {
+    let mut state = vec![F::zero(); params.width()];
+    // An empty input still goes through one permutation
+    for block in input
+        .chunks(params.rate)
+        .chain(input.is_empty().then_some(&[][..]))
+    {
+        for (x, y) in state[params.capacity..].iter_mut().zip(block) {
+            *x += y;
+        }
+        permute(params, &mut state);
+    }
+
+    state[params.capacity]
+}
+
+/// The parameters as circuit constants, the way arkworks' Poseidon gadget
+/// holds its own.
+#[derive(Clone, Debug)]
+pub struct RescueConfigVar<F: PrimeField> {
+    pub parameters: RescueConfig<F>,
+}
+
+impl<F: PrimeField> AllocVar<RescueConfig<F>, F> for RescueConfigVar<F> {
+    fn new_variable<T: Borrow<RescueConfig<F>>>(
+        _cs: impl Into<Namespace<F>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        _mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let parameters = f()?.borrow().clone();
+        Ok(RescueConfigVar { parameters })
+    }
+}
+
+fn mix_var<F: PrimeField>(
+    params: &RescueConfig<F>,
+    state: &[FpVar<F>],
+    constants: &[F],
+) -> Vec<FpVar<F>> {
+    params
+        .mds
+        .iter()
+        .zip(constants)
+        .map(|(row, c)| {
+            row.iter()
+                .zip(state)
+                .fold(FpVar::Constant(*c), |acc, (m, s)| acc + s * *m)
+        })
+        .collect()
+}
+
+/// `x^(1/alpha)` as a witness `y`, constrained by `y^alpha = x`.
+fn inverse_sbox<F: PrimeField>(
+    params: &RescueConfig<F>,
+    x: &FpVar<F>,
+) -> Result<FpVar<F>, SynthesisError> {
+    if let FpVar::Constant(c) = x {
+        return Ok(FpVar::Constant(c.pow(&params.alpha_inv)));
+    }
+
+    let y = FpVar::new_witness(x.cs(), || Ok(x.value()?.pow(&params.alpha_inv)))?;
+    y.pow_by_constant([params.alpha])?.enforce_equal(x)?;
+    Ok(y)
+}
+
+fn permute_var<F: PrimeField>(
+    params: &RescueConfig<F>,
+    state: &mut Vec<FpVar<F>>,
+) -> Result<(), SynthesisError> {
+    for round in params.ark.chunks(2) {
+        let powered = state
+            .iter()
+            .map(|x| x.pow_by_constant([params.alpha]))
+            .collect::<Result<Vec<_>, _>>()?;
+        *state = mix_var(params, &powered, &round[0]);
+        let rooted = state
+            .iter()
+            .map(|x| inverse_sbox(params, x))
+            .collect::<Result<Vec<_>, _>>()?;
+        *state = mix_var(params, &rooted, &round[1]);
+    }
+
+    Ok(())
+}
+
+fn sponge_var<F: PrimeField>(
+    params: &RescueConfig<F>,
+    input: &[FpVar<F>],
+) -> Result<FpVar<F>, SynthesisError> {
+    let mut state = vec![FpVar::zero(); params.width()];
+    for block in input
+        .chunks(params.rate)
+        .chain(input.is_empty().then_some(&[][..]))
+    {
+        for (x, y) in state[params.capacity..].iter_mut().zip(block) {
+            *x += y;
+        }
+        permute_var(params, &mut state)?;
+    }
+
+    Ok(state[params.capacity].clone())
+}
+
+pub struct RescueCRH<F>(PhantomData<F>);
+pub struct RescueTwoToOneCRH<F>(PhantomData<F>);
+pub struct RescueCRHGadget<F>(PhantomData<F>);
+pub struct RescueTwoToOneCRHGadget<F>(PhantomData<F>);
+
+impl<F: PrimeField> CRHScheme for RescueCRH<F> {
+    type Input = [F];
+    type Output = F;
+    type Parameters = RescueConfig<F>;
+
+    fn setup<R: Rng>(_rng: &mut R) -> Result<Self::Parameters, ark_crypto_primitives::Error> {
+        Ok(RescueConfig::default_for_tree())
+    }
+
+    fn evaluate<T: Borrow<Self::Input>>(
+        parameters: &Self::Parameters,
+        input: T,
+    ) -> Result<Self::Output, ark_crypto_primitives::Error> {
+        Ok(sponge(parameters, input.borrow()))
+    }
+}
+
+impl<F: PrimeField> TwoToOneCRHScheme for RescueTwoToOneCRH<F> {
+    type Input = F;
+    type Output = F;
+    type Parameters = RescueConfig<F>;
+
+    fn setup<R: Rng>(_rng: &mut R) -> Result<Self::Parameters, ark_crypto_primitives::Error> {
+        Ok(RescueConfig::default_for_tree())
+    }
+
+    fn evaluate<T: Borrow<Self::Input>>(
+        parameters: &Self::Parameters,
+        left_input: T,
+        right_input: T,
+    ) -> Result<Self::Output, ark_crypto_primitives::Error> {
+        Ok(sponge(
+            parameters,
+            &[*left_input.borrow(), *right_input.borrow()],
+        ))
+    }
+
+    fn compress<T: Borrow<Self::Output>>(
+        parameters: &Self::Parameters,
+        left_input: T,
+        right_input: T,
+    ) -> Result<Self::Output, ark_crypto_primitives::Error> {
+        Self::evaluate(parameters, left_input, right_input)
+    }
+}
+
+impl<F: PrimeField> CRHSchemeGadget<RescueCRH<F>, F> for RescueCRHGadget<F> {
+    type InputVar = [FpVar<F>];
+    type OutputVar = FpVar<F>;
+    type ParametersVar = RescueConfigVar<F>;
+
+    fn evaluate(
+        parameters: &Self::ParametersVar,
+        input: &Self::InputVar,
+    ) -> Result<Self::OutputVar, SynthesisError> {
+        sponge_var(&parameters.parameters, input)
+    }
+}
+
+impl<F: PrimeField> TwoToOneCRHSchemeGadget<RescueTwoToOneCRH<F>, F>
+    for RescueTwoToOneCRHGadget<F>
+{
+    type InputVar = FpVar<F>;
+    type OutputVar = FpVar<F>;
+    type ParametersVar = RescueConfigVar<F>;
+
+    fn evaluate(
+        parameters: &Self::ParametersVar,
+        left_input: &Self::InputVar,
+        right_input: &Self::InputVar,
+    ) -> Result<Self::OutputVar, SynthesisError> {
+        sponge_var(
+            &parameters.parameters,
+            &[left_input.clone(), right_input.clone()],
+        )
+    }
+
+    fn compress(
+        parameters: &Self::ParametersVar,
+        left_input: &Self::OutputVar,
+        right_input: &Self::OutputVar,
+    ) -> Result<Self::OutputVar, SynthesisError> {
+        Self::evaluate(parameters, left_input, right_input)
+    }
+}
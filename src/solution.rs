@@ -0,0 +1,34 @@
+use ark_mnt4_753::Fr as MNT4BigFr;
+
+use crate::poseidon_parameters::poseidon_parameters;
+use crate::{fr_to_scalar, nullifier, public_key, scalar_to_fr, LEGACY_DOMAIN};
+
+/// Double spends the leaf owned by `leaked_secret` against the non-strict
+/// spend circuit, returning `(secret_hack, nullifier_hack)`.
+///
+/// The leaf only holds the x-coordinate of `secret * G`, and `-(secret * G)`
+/// shares it. `r - secret`, for `r` the MNT6-753 scalar modulus, is that
+/// negated scalar, and since `r` is below the MNT4-753 modulus it is also a
+/// valid, different witness: the circuit accepts it, but it hashes to a new
+/// nullifier.
+///
+/// Returns `None` if `leaked_secret` owns none of `leaves` or has no distinct
+/// alternate representative.
+pub fn solve(
+    leaves: &[Vec<MNT4BigFr>],
+    leaked_secret: MNT4BigFr,
+) -> Option<(MNT4BigFr, MNT4BigFr)> {
+    let pk_x = public_key(leaked_secret).x;
+    if !leaves.iter().any(|leaf| *leaf == [pk_x]) {
+        return None;
+    }
+
+    let scalar = fr_to_scalar(leaked_secret).ok()?;
+    let secret_hack = scalar_to_fr(-scalar);
+    if secret_hack == leaked_secret {
+        return None;
+    }
+
+    let nullifier_hack = nullifier(&poseidon_parameters(), secret_hack, LEGACY_DOMAIN).ok()?;
+    Some((secret_hack, nullifier_hack))
+}
@@ -0,0 +1,204 @@
+use ark_crypto_primitives::crh::CRHScheme;
+use ark_crypto_primitives::merkle_tree::constraints::PathVar;
+use ark_crypto_primitives::merkle_tree::Path;
+use ark_crypto_primitives::sponge::{poseidon::PoseidonConfig, Absorb};
+use ark_ff::{One, PrimeField, Zero};
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+
+use crate::tree::{build_tree, TreeError};
+use crate::{
+    ConstraintF, CrhParamsVar, LeafH, MerkleTreeParams, MerkleTreeParamsVar, MntMerkleTree,
+    MntMerkleTreeParams,
+};
+
+/// A tree over a sorted set of keys, such as spent nullifiers, where each leaf
+/// links a key to its successor as `[key, next_key]`. A key is absent exactly
+/// when some leaf has `key < absent < next_key`.
+///
+/// `0` and `-1` are sentinels bounding the first and last gap, so they can't
+/// be stored. Padded positions hash like `[0, 0]`, an empty gap.
+pub struct SparseMerkleTree {
+    keys: Vec<ConstraintF>,
+    tree: MntMerkleTree,
+}
+
+/// The leaf whose gap contains an absent key, and its path.
+#[derive(Clone)]
+pub struct NonMembershipProof<F: PrimeField + Absorb = ConstraintF> {
+    pub low: F,
+    pub next: F,
+    pub path: Path<MerkleTreeParams<F>>,
+}
+
+impl SparseMerkleTree {
+    /// Builds a tree of height `height` over `keys`, which may be unsorted and
+    /// contain duplicates. The sentinels take one leaf, so at most
+    /// `2^height - 1` distinct keys fit.
+    pub fn new(
+        leaf_params: &<LeafH as CRHScheme>::Parameters,
+        two_to_one_params: &<LeafH as CRHScheme>::Parameters,
+        keys: &[ConstraintF],
+        height: usize,
+    ) -> Result<Self, TreeError> {
+        let max = -ConstraintF::one();
+        if keys.iter().any(|k| k.is_zero() || *k == max) {
+            return Err(TreeError::ReservedKey);
+        }
+
+        let mut sorted = Vec::with_capacity(keys.len() + 2);
+        sorted.push(ConstraintF::zero());
+        sorted.extend_from_slice(keys);
+        sorted.push(max);
+        sorted.sort();
+        sorted.dedup();
+
+        let leaves: Vec<_> = sorted.windows(2).map(|w| vec![w[0], w[1]]).collect();
+        let (tree, _) = build_tree(leaf_params, two_to_one_params, &leaves, height)?;
+
+        // `keys` keeps every linked key, including the sentinels
+        Ok(Self { keys: sorted, tree })
+    }
+
+    pub fn root(&self) -> ConstraintF {
+        self.tree.root()
+    }
+
+    pub fn contains(&self, key: &ConstraintF) -> bool {
+        self.keys[1..self.keys.len() - 1].binary_search(key).is_ok()
+    }
+
+    /// The path to the leaf storing `key`, or `None` if it's absent.
+    pub fn prove_membership(
+        &self,
+        key: &ConstraintF,
+    ) -> Result<Option<Path<MntMerkleTreeParams>>, TreeError> {
+        if !self.contains(key) {
+            return Ok(None);
+        }
+        let index = self.keys.binary_search(key).expect("key is present");
+        self.tree
+            .generate_proof(index)
+            .map(Some)
+            .map_err(TreeError::Crypto)
+    }
+
+    /// The neighbouring keys around `key` and the path to the leaf linking
+    /// them, or `None` if `key` is stored or is a sentinel.
+    pub fn prove_non_membership(
+        &self,
+        key: &ConstraintF,
+    ) -> Result<Option<NonMembershipProof>, TreeError> {
+        let index = match self.keys.binary_search(key) {
+            Ok(_) => return Ok(None),
+            // Sentinels sit on both ends, so an absent key always has a
+            // predecessor and a successor
+            Err(successor) => successor - 1,
+        };
+        let path = self.tree.generate_proof(index).map_err(TreeError::Crypto)?;
+
+        Ok(Some(NonMembershipProof {
+            low: self.keys[index],
+            next: self.keys[index + 1],
+            path,
+        }))
+    }
+}
+
+impl<F: PrimeField + Absorb> NonMembershipProof<F> {
+    /// Native counterpart of [`NonMembershipCircuit`].
+    pub fn verify(
+        &self,
+        leaf_params: &PoseidonConfig<F>,
+        two_to_one_params: &PoseidonConfig<F>,
+        root: &F,
+        key: &F,
+    ) -> Result<bool, ark_crypto_primitives::Error> {
+        if !(self.low < *key && *key < self.next) {
+            return Ok(false);
+        }
+        self.path
+            .verify(leaf_params, two_to_one_params, root, [self.low, self.next])
+    }
+}
+
+/// Proves that `key` is not stored in the [`SparseMerkleTree`] with root
+/// `root`. Public inputs are allocated as (root, key), in that order.
+#[derive(Clone)]
+pub struct NonMembershipCircuit<F: PrimeField + Absorb = ConstraintF> {
+    pub leaf_params: PoseidonConfig<F>,
+    pub two_to_one_params: PoseidonConfig<F>,
+    pub root: F,
+    pub key: F,
+    pub proof: NonMembershipProof<F>,
+}
+
+impl<F: PrimeField + Absorb> NonMembershipCircuit<F> {
+    pub fn new(
+        leaf_params: PoseidonConfig<F>,
+        two_to_one_params: PoseidonConfig<F>,
+        root: F,
+        key: F,
+        proof: NonMembershipProof<F>,
+    ) -> Self {
+        Self {
+            leaf_params,
+            two_to_one_params,
+            root,
+            key,
+            proof,
+        }
+    }
+}
+
+// `FpVar`'s comparisons only cover the lower half of the field, while keys are
+// arbitrary hash outputs, so this compares the canonical bits from the top.
+fn enforce_less_than<F: PrimeField>(a: &FpVar<F>, b: &FpVar<F>) -> Result<(), SynthesisError> {
+    let a_bits = a.to_bits_le()?;
+    let b_bits = b.to_bits_le()?;
+
+    let mut less = Boolean::constant(false);
+    let mut equal = Boolean::constant(true);
+    for (a_i, b_i) in a_bits.iter().zip(&b_bits).rev() {
+        less = less.or(&equal.and(&a_i.not().and(b_i)?)?)?;
+        equal = equal.and(&a_i.is_eq(b_i)?)?;
+    }
+
+    less.enforce_equal(&Boolean::constant(true))
+}
+
+impl<F: PrimeField + Absorb> ConstraintSynthesizer<F> for NonMembershipCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let root = FpVar::new_input(ark_relations::ns!(cs, "root"), || Ok(self.root))?;
+        let key = FpVar::new_input(ark_relations::ns!(cs, "key"), || Ok(self.key))?;
+
+        let leaf_crh_params_var = <CrhParamsVar<F>>::new_constant(
+            ark_relations::ns!(cs, "leaf_crh_parameter"),
+            &self.leaf_params,
+        )?;
+        let two_to_one_crh_params_var = <CrhParamsVar<F>>::new_constant(
+            ark_relations::ns!(cs, "two_to_one_crh_parameter"),
+            &self.two_to_one_params,
+        )?;
+
+        let low = FpVar::new_witness(ark_relations::ns!(cs, "low"), || Ok(self.proof.low))?;
+        let next = FpVar::new_witness(ark_relations::ns!(cs, "next"), || Ok(self.proof.next))?;
+
+        enforce_less_than(&low, &key)?;
+        enforce_less_than(&key, &next)?;
+
+        let cw: PathVar<MerkleTreeParams<F>, F, MerkleTreeParamsVar<F>> =
+            PathVar::new_witness(ark_relations::ns!(cs, "path"), || Ok(&self.proof.path))?;
+
+        cw.verify_membership(
+            &leaf_crh_params_var,
+            &two_to_one_crh_params_var,
+            &root,
+            &[low, next],
+        )?
+        .enforce_equal(&Boolean::constant(true))?;
+
+        Ok(())
+    }
+}
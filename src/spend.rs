@@ -0,0 +1,699 @@
+use ark_ff::PrimeField;
+
+use ark_crypto_primitives::merkle_tree::{Config, MerkleTree, Path};
+use ark_crypto_primitives::snark::SNARK;
+use ark_crypto_primitives::{
+    crh::{poseidon, *},
+    merkle_tree::constraints::*,
+};
+use ark_groth16::{Groth16, Proof, ProvingKey};
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_std::marker::PhantomData;
+use ark_std::rand::{rngs::StdRng, CryptoRng, RngCore, SeedableRng};
+use tracing::instrument;
+
+use crate::{
+    canonical_secret, commitment_var_with, nullifier_var_with_layout, nullifier_with, params,
+    scalar_nullifier_var_with_layout, Arity, Binary, CircuitParams, ConstraintF, CurveCycle,
+    HashFamily, IdentityConverter, Keys, LeafConverter, LeafLayout, LeafValues, Mnt753Cycle, Note,
+    NullifierInput, PoseidonFamily, Zeroizing, LEGACY_DOMAIN,
+};
+
+pub type LeafH = poseidon::CRH<ConstraintF>;
+pub type LeafHG = poseidon::constraints::CRHGadget<ConstraintF>;
+
+pub type CompressH = poseidon::TwoToOneCRH<ConstraintF>;
+pub type CompressHG = poseidon::constraints::TwoToOneCRHGadget<ConstraintF>;
+
+pub type LeafVar<F> = [FpVar<F>];
+pub struct MerkleTreeParamsVar<F, H = PoseidonFamily, L = IdentityConverter>(
+    PhantomData<(F, H, L)>,
+);
+impl<F: PrimeField, H: HashFamily<F>, L: LeafConverter<F>>
+    ConfigGadget<MerkleTreeParams<F, H, L>, F> for MerkleTreeParamsVar<F, H, L>
+{
+    type Leaf = LeafVar<F>;
+    type LeafDigest = FpVar<F>;
+    type LeafInnerConverter = L::Gadget;
+    type InnerDigest = FpVar<F>;
+    type LeafHash = H::LeafGadget;
+    type TwoToOneHash = H::CompressGadget;
+}
+
+/// Merkle tree hashed with the leaf and compression hashes of `H`, with leaf
+/// digests fed to the compression hash through `L`.
+pub struct MerkleTreeParams<F, H = PoseidonFamily, L = IdentityConverter>(PhantomData<(F, H, L)>);
+
+impl<F: PrimeField, H: HashFamily<F>, L: LeafConverter<F>> Config for MerkleTreeParams<F, H, L> {
+    type Leaf = [F];
+
+    type LeafDigest = F;
+    type LeafInnerDigestConverter = L::Native;
+    type InnerDigest = F;
+
+    type LeafHash = H::Leaf;
+    type TwoToOneHash = H::Compress;
+}
+
+/// An opening of a leaf of a tree hashed with `H`, allocated in-circuit.
+pub type MerklePathVar<F, H = PoseidonFamily> =
+    PathVar<MerkleTreeParams<F, H>, F, MerkleTreeParamsVar<F, H>>;
+
+/// An opening of a leaf of a tree hashed with `H`, with `A` children per
+/// node.
+pub type MerklePath<F, H = PoseidonFamily, A = Binary> = <A as Arity<F, H>>::Path;
+
+pub type MntMerkleTreeParams = MerkleTreeParams<ConstraintF>;
+pub type MntMerkleTreeParamsVar = MerkleTreeParamsVar<ConstraintF>;
+pub type MntMerkleTree = MerkleTree<MntMerkleTreeParams>;
+
+/// Spends a leaf of a tree hashed with `H`, with `A` children per node. The
+/// default [`PoseidonFamily`] and [`Binary`] synthesize the constraints
+/// `proof_keys.bin` was generated for.
+///
+/// Nothing has to come from the fixture files:
+///
+/// ```
+/// use ark_std::rand::{rngs::StdRng, SeedableRng};
+/// use puzzle_gamma_ray::{
+///     check_satisfied, generate_leaves, nullifier, CircuitParams, Mnt753Cycle, Zeroizing,
+///     LEGACY_DOMAIN,
+/// };
+///
+/// let params = CircuitParams::poseidon();
+/// let (leaves, secrets) = generate_leaves(4, &mut StdRng::seed_from_u64(0));
+/// let tree = params.merkle_tree(leaves.iter().map(Vec::as_slice)).unwrap();
+///
+/// let circuit = params.spend_circuit::<Mnt753Cycle>(
+///     tree.root(),
+///     tree.generate_proof(3).unwrap(),
+///     Zeroizing::new(secrets[3]),
+///     nullifier(&params.leaf, secrets[3], LEGACY_DOMAIN).unwrap(),
+/// );
+/// assert!(check_satisfied(&circuit).unwrap());
+/// ```
+#[derive(Clone)]
+pub struct SpendCircuit<
+    C: CurveCycle = Mnt753Cycle,
+    H: HashFamily<C::ConstraintF> = PoseidonFamily,
+    A: Arity<C::ConstraintF, H> = Binary,
+> {
+    pub leaf_params: H::Parameters,
+    pub two_to_one_params: H::Parameters,
+    pub root: C::ConstraintF,
+    pub proof: MerklePath<C::ConstraintF, H, A>,
+    /// Cleared when the circuit is dropped. Synthesis still copies it into
+    /// the constraint system's witness assignment.
+    pub secret: Zeroizing<C::ConstraintF>,
+    pub nullifier: C::ConstraintF,
+    /// Only accept the unique canonical representative of the secret. The
+    /// circuit generated with this disabled is the one the puzzle attacks.
+    ///
+    /// Secrets above `(r - 1) / 2`, for `r` the scalar modulus, are rejected:
+    /// the owner of such a key spends it as `r - s`, which owns the same leaf,
+    /// and derives the nullifier from that. See [`canonical_secret`].
+    pub strict_nullifier: bool,
+    /// Domain tag the nullifier is derived under, see [`crate::nullifier`].
+    pub nullifier_domain: u64,
+    /// Derive the nullifier with [`crate::positional_nullifier`] from the
+    /// index of the spent leaf, which the circuit reads off the Merkle path.
+    /// Off by default, as the puzzle's nullifiers only hash the secret.
+    pub positional_nullifier: bool,
+    /// Derive the nullifier with [`crate::scalar_nullifier`] from the canonical
+    /// scalar of the secret rather than the secret itself. Every alias of the
+    /// secret that owns the leaf then reveals the same nullifier, without
+    /// relying on `strict_nullifier` to rule the aliases out.
+    pub scalar_nullifier: bool,
+    /// Opens the leaf this note is stored as, see [`Note`], instead of the
+    /// bare public key. `note.pk_x` is not witnessed: the circuit recomputes
+    /// it from the secret.
+    pub note: Option<Note<C::ConstraintF>>,
+    /// The leaf's fields when no `note` is opened, witnessed from
+    /// `leaf_values`. As with notes, `leaf_values.pk_x` and `pk_y` are
+    /// recomputed.
+    pub leaf_layout: LeafLayout,
+    pub leaf_values: LeafValues<C::ConstraintF>,
+    /// Extra public inputs after the nullifier, see [`Payout`].
+    pub payout: Option<Payout<C::ConstraintF>>,
+    /// Allocated as the last public input and constrained to equal this
+    /// value, so keys generated for one version reject proofs of another.
+    pub version: Option<u32>,
+    /// The height `proof` must have, see [`check_path_height`]. Without it a
+    /// path of any length is synthesized, into a circuit of another shape.
+    pub height: Option<usize>,
+}
+
+/// The version of the spend circuit, bumped whenever its constraints change.
+/// See CHANGELOG.md for what each version changed.
+///
+/// Circuits only carry it when built [`SpendCircuit::with_version`]; the
+/// puzzle's `proof_keys.bin` predates it and is version 1.
+pub const CIRCUIT_VERSION: u32 = 2;
+
+/// Public inputs a spend is bound to without the circuit constraining them,
+/// so a proof made for one payout can't be redirected to another. The
+/// arkworks QAP reduction gives every public input its own row, so even an
+/// input no constraint uses can't be changed without invalidating the proof.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Payout<F> {
+    pub fee: F,
+    pub recipient_hash: F,
+}
+
+impl<C: CurveCycle, H: HashFamily<C::ConstraintF>, A: Arity<C::ConstraintF, H>>
+    SpendCircuit<C, H, A>
+{
+    pub fn new(
+        leaf_params: H::Parameters,
+        two_to_one_params: H::Parameters,
+        root: C::ConstraintF,
+        proof: MerklePath<C::ConstraintF, H, A>,
+        secret: Zeroizing<C::ConstraintF>,
+        nullifier: C::ConstraintF,
+    ) -> Self {
+        Self {
+            leaf_params,
+            two_to_one_params,
+            root,
+            proof,
+            secret,
+            nullifier,
+            strict_nullifier: true,
+            nullifier_domain: LEGACY_DOMAIN,
+            positional_nullifier: false,
+            scalar_nullifier: false,
+            note: None,
+            leaf_layout: LeafLayout::default(),
+            leaf_values: LeafValues::default(),
+            payout: None,
+            version: None,
+            height: None,
+        }
+    }
+
+    pub fn with_strict_nullifier(mut self, strict_nullifier: bool) -> Self {
+        self.strict_nullifier = strict_nullifier;
+        self
+    }
+
+    pub fn with_nullifier_domain(mut self, nullifier_domain: u64) -> Self {
+        self.nullifier_domain = nullifier_domain;
+        self
+    }
+
+    pub fn with_positional_nullifier(mut self, positional_nullifier: bool) -> Self {
+        self.positional_nullifier = positional_nullifier;
+        self
+    }
+
+    pub fn with_scalar_nullifier(mut self, scalar_nullifier: bool) -> Self {
+        self.scalar_nullifier = scalar_nullifier;
+        self
+    }
+
+    pub fn with_note(mut self, note: Note<C::ConstraintF>) -> Self {
+        self.note = Some(note);
+        self
+    }
+
+    pub fn with_leaf_layout(
+        mut self,
+        leaf_layout: LeafLayout,
+        leaf_values: LeafValues<C::ConstraintF>,
+    ) -> Self {
+        self.leaf_layout = leaf_layout;
+        self.leaf_values = leaf_values;
+        self
+    }
+
+    pub fn with_payout(mut self, payout: Payout<C::ConstraintF>) -> Self {
+        self.payout = Some(payout);
+        self
+    }
+
+    pub fn with_height(mut self, height: usize) -> Self {
+        self.height = Some(height);
+        self
+    }
+
+    pub fn with_version(mut self, version: u32) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    /// The public inputs in allocation order: the root, the nullifier, with a
+    /// payout the fee and the recipient hash, and with a version the version.
+    pub fn public_inputs(&self) -> Vec<C::ConstraintF> {
+        let mut public_inputs = vec![self.root, self.nullifier];
+        if let Some(payout) = &self.payout {
+            public_inputs.extend([payout.fee, payout.recipient_hash]);
+        }
+        if let Some(version) = self.version {
+            public_inputs.push(C::ConstraintF::from(version));
+        }
+        public_inputs
+    }
+
+    pub(crate) fn leaf_opening(&self) -> LeafOpening<'_, C::ConstraintF> {
+        match &self.note {
+            Some(note) => LeafOpening::Note(note),
+            None => LeafOpening::Layout(&self.leaf_layout, &self.leaf_values),
+        }
+    }
+}
+
+pub type CrhParamsVar<F> =
+    <poseidon::constraints::CRHGadget<F> as CRHSchemeGadget<poseidon::CRH<F>, F>>::ParametersVar;
+
+/// Affine `(x, y)` coordinates of a public key, see
+/// [`CurveCycle::public_key_var`].
+pub type PublicKeyVar<F> = (FpVar<F>, FpVar<F>);
+
+/// The public nullifier a spend allocates and the public key it opened.
+pub(crate) type SpendVars<F> = (FpVar<F>, PublicKeyVar<F>);
+
+/// How [`SpendGadget::enforce_with_key`] builds the leaf from the public key.
+pub(crate) enum LeafOpening<'a, F: PrimeField> {
+    Layout(&'a LeafLayout, &'a LeafValues<F>),
+    Note(&'a Note<F>),
+}
+
+/// Constraints shared by every note spent against the same root.
+pub(crate) struct SpendGadget<'a, C: CurveCycle, H: HashFamily<C::ConstraintF> = PoseidonFamily> {
+    pub cs: ConstraintSystemRef<C::ConstraintF>,
+    pub leaf_crh_params_var: &'a H::ParametersVar,
+    pub two_to_one_crh_params_var: &'a H::ParametersVar,
+    pub root: &'a FpVar<C::ConstraintF>,
+    pub strict_nullifier: bool,
+    pub nullifier_domain: u64,
+    pub positional_nullifier: bool,
+    pub scalar_nullifier: bool,
+}
+
+impl<'a, C: CurveCycle, H: HashFamily<C::ConstraintF>> SpendGadget<'a, C, H> {
+    pub fn allocate_params(
+        cs: ConstraintSystemRef<C::ConstraintF>,
+        leaf_params: &H::Parameters,
+        two_to_one_params: &H::Parameters,
+    ) -> Result<(H::ParametersVar, H::ParametersVar), SynthesisError> {
+        params::allocate_params::<C::ConstraintF, H>(cs, leaf_params, two_to_one_params)
+    }
+
+    /// Allocates the note's witnesses and its public nullifier, and enforces
+    /// that the secret owns a leaf under `root` and derives the nullifier.
+    pub fn enforce(
+        &self,
+        proof: &Path<MerkleTreeParams<C::ConstraintF, H>>,
+        secret: C::ConstraintF,
+        nullifier: C::ConstraintF,
+    ) -> Result<FpVar<C::ConstraintF>, SynthesisError> {
+        self.enforce_with_leaf(proof, secret, nullifier, &[])
+    }
+
+    /// Like [`Self::enforce`], for leaves that carry `leaf_suffix` after the
+    /// public key x-coordinate.
+    pub fn enforce_with_leaf(
+        &self,
+        proof: &Path<MerkleTreeParams<C::ConstraintF, H>>,
+        secret: C::ConstraintF,
+        nullifier: C::ConstraintF,
+        leaf_suffix: &[FpVar<C::ConstraintF>],
+    ) -> Result<FpVar<C::ConstraintF>, SynthesisError> {
+        self.enforce_with::<Binary, _>(proof, secret, nullifier, |(pk_x, _)| {
+            let mut leaf = vec![pk_x.clone()];
+            leaf.extend_from_slice(leaf_suffix);
+            Ok(leaf)
+        })
+        .map(|(nullifier, _)| nullifier)
+    }
+
+    /// Like [`Self::enforce`], for the leaf of a [`Note`] of the secret's
+    /// public key with the already allocated `value` and `rho`.
+    pub fn enforce_note(
+        &self,
+        proof: &Path<MerkleTreeParams<C::ConstraintF, H>>,
+        secret: C::ConstraintF,
+        nullifier: C::ConstraintF,
+        value: FpVar<C::ConstraintF>,
+        rho: FpVar<C::ConstraintF>,
+    ) -> Result<FpVar<C::ConstraintF>, SynthesisError> {
+        self.enforce_with::<Binary, _>(proof, secret, nullifier, |(pk_x, _)| {
+            self.note_leaf(pk_x, value, rho)
+        })
+        .map(|(nullifier, _)| nullifier)
+    }
+
+    /// Enforces a spend of a leaf opened as `leaf` in a tree of arity `A`,
+    /// also returning the affine `(x, y)` coordinates of the secret's public
+    /// key.
+    pub fn enforce_with_key<A: Arity<C::ConstraintF, H>>(
+        &self,
+        proof: &MerklePath<C::ConstraintF, H, A>,
+        secret: C::ConstraintF,
+        nullifier: C::ConstraintF,
+        leaf: LeafOpening<'_, C::ConstraintF>,
+    ) -> Result<SpendVars<C::ConstraintF>, SynthesisError> {
+        let cs = self.cs.clone();
+        self.enforce_with::<A, _>(proof, secret, nullifier, |pk| {
+            let note = match leaf {
+                LeafOpening::Note(note) => note,
+                LeafOpening::Layout(layout, values) => return layout.leaf_var(cs, pk, values),
+            };
+            let value = FpVar::new_witness(ark_relations::ns!(cs, "note_value"), || {
+                Ok(C::ConstraintF::from(note.value))
+            })?;
+            let rho = FpVar::new_witness(ark_relations::ns!(cs, "note_rho"), || Ok(note.rho))?;
+
+            self.note_leaf(&pk.0, value, rho)
+        })
+    }
+
+    /// The in-circuit [`Note::leaf`], `[commitment]`.
+    fn note_leaf(
+        &self,
+        pk_x: &FpVar<C::ConstraintF>,
+        value: FpVar<C::ConstraintF>,
+        rho: FpVar<C::ConstraintF>,
+    ) -> Result<Vec<FpVar<C::ConstraintF>>, SynthesisError> {
+        Ok(vec![commitment_var_with::<_, H>(
+            self.leaf_crh_params_var,
+            pk_x.clone(),
+            value,
+            rho,
+        )?])
+    }
+
+    /// The nullifier of `secret` in `layout`, from the secret itself or from
+    /// its canonical scalar.
+    fn nullifier_var(
+        &self,
+        layout: NullifierInput,
+        secret: &FpVar<C::ConstraintF>,
+        index: Option<FpVar<C::ConstraintF>>,
+    ) -> Result<FpVar<C::ConstraintF>, SynthesisError> {
+        if self.scalar_nullifier {
+            scalar_nullifier_var_with_layout::<C, H>(
+                self.leaf_crh_params_var,
+                layout,
+                secret,
+                index,
+            )
+        } else {
+            nullifier_var_with_layout::<_, H>(
+                self.leaf_crh_params_var,
+                layout,
+                secret.clone(),
+                index,
+            )
+        }
+    }
+
+    /// Builds the leaf from the public key coordinates with `leaf`.
+    fn enforce_with<A: Arity<C::ConstraintF, H>, L>(
+        &self,
+        proof: &MerklePath<C::ConstraintF, H, A>,
+        secret: C::ConstraintF,
+        nullifier: C::ConstraintF,
+        leaf: L,
+    ) -> Result<SpendVars<C::ConstraintF>, SynthesisError>
+    where
+        L: FnOnce(
+            &PublicKeyVar<C::ConstraintF>,
+        ) -> Result<Vec<FpVar<C::ConstraintF>>, SynthesisError>,
+    {
+        let cs = self.cs.clone();
+
+        let secret = FpVar::new_witness(ark_relations::ns!(cs, "spend_secret"), || Ok(secret))?;
+        let secret_bits = secret.to_bits_le()?;
+        Boolean::enforce_smaller_or_equal_than_le(&secret_bits, C::ScalarField::MODULUS)?;
+
+        if self.strict_nullifier {
+            // `to_bits_le` is already the canonical encoding of the secret.
+            // `s` and `r - s` yield public keys with the same x-coordinate,
+            // so only the lower half of the scalar field is accepted
+            Boolean::enforce_smaller_or_equal_than_le(
+                &secret_bits,
+                C::ScalarField::MODULUS_MINUS_ONE_DIV_TWO,
+            )?;
+        }
+
+        let nullifier =
+            FpVar::new_input(ark_relations::ns!(cs, "spend_nullifier"), || Ok(nullifier))?;
+
+        // The positional nullifier is only enforced once the path is
+        // allocated, which leaves the constraints of the legacy one in place
+        let layout = NullifierInput::new(self.nullifier_domain, self.positional_nullifier);
+        if !layout.is_positional() {
+            let _ns = ark_relations::ns!(cs, "nullifier_check");
+            self.nullifier_var(layout, &secret, None)?
+                .enforce_equal(&nullifier)?;
+        }
+
+        let pk = C::public_key_var(cs.clone(), &secret_bits)?;
+
+        // Allocate Leaf
+        let leaf_g = leaf(&pk)?;
+
+        // Allocate Merkle Tree Path
+        let cw = A::PathVar::new_witness(ark_relations::ns!(cs, "merkle_path"), || Ok(proof))?;
+
+        if layout.is_positional() {
+            // The position bits are the path's own, so the index can't be
+            // claimed independently of the leaf being opened
+            let _ns = ark_relations::ns!(cs, "nullifier_check");
+            let index = Boolean::le_bits_to_fp_var(&A::leaf_position(&cw)?)?;
+            self.nullifier_var(layout, &secret, Some(index))?
+                .enforce_equal(&nullifier)?;
+        }
+
+        // Namespaces only label constraints for `synthesize_and_report`
+        let _ns = ark_relations::ns!(cs, "membership");
+        A::verify_membership(
+            &cw,
+            self.leaf_crh_params_var,
+            self.two_to_one_crh_params_var,
+            self.root,
+            &leaf_g,
+        )?
+        .enforce_equal(&Boolean::constant(true))?;
+
+        Ok((nullifier, pk))
+    }
+}
+
+impl<C: CurveCycle, H: HashFamily<C::ConstraintF>, A: Arity<C::ConstraintF, H>>
+    SpendCircuit<C, H, A>
+{
+    /// Synthesizes everything but the version: the root, the parameters, the
+    /// spend and the payout. Returns the spent public key and the leaf hash
+    /// parameters, for circuits that extend the spend such as
+    /// [`crate::SpendAuthCircuit`].
+    pub(crate) fn enforce_spend(
+        &self,
+        cs: ConstraintSystemRef<C::ConstraintF>,
+    ) -> Result<(PublicKeyVar<C::ConstraintF>, H::ParametersVar), SynthesisError> {
+        if let Some(height) = self.height {
+            // As `check_path_height`, for any arity
+            if A::path_height(&self.proof) != height {
+                return Err(SynthesisError::Unsatisfiable);
+            }
+        }
+
+        // Allocate Merkle Tree Root
+        let root = FpVar::new_input(ark_relations::ns!(cs, "merkle_root"), || Ok(self.root))?;
+
+        let (leaf_crh_params_var, two_to_one_crh_params_var) =
+            SpendGadget::<C, H>::allocate_params(
+                cs.clone(),
+                &self.leaf_params,
+                &self.two_to_one_params,
+            )?;
+
+        let gadget = SpendGadget::<C, H> {
+            cs,
+            leaf_crh_params_var: &leaf_crh_params_var,
+            two_to_one_crh_params_var: &two_to_one_crh_params_var,
+            root: &root,
+            strict_nullifier: self.strict_nullifier,
+            nullifier_domain: self.nullifier_domain,
+            positional_nullifier: self.positional_nullifier,
+            scalar_nullifier: self.scalar_nullifier,
+        };
+        // The default layout opens `[pk.x]` exactly as `SpendGadget::enforce`
+        let (_, pk) = gadget.enforce_with_key::<A>(
+            &self.proof,
+            *self.secret,
+            self.nullifier,
+            self.leaf_opening(),
+        )?;
+
+        if let Some(payout) = self.payout {
+            // Allocating the inputs is what binds them, see `Payout`
+            let _ = FpVar::new_input(ark_relations::ns!(gadget.cs, "fee"), || Ok(payout.fee))?;
+            let _ = FpVar::new_input(ark_relations::ns!(gadget.cs, "recipient_hash"), || {
+                Ok(payout.recipient_hash)
+            })?;
+        }
+
+        Ok((pk, leaf_crh_params_var))
+    }
+
+    /// Allocates the version, if any, as the last public input.
+    pub(crate) fn enforce_version(
+        &self,
+        cs: ConstraintSystemRef<C::ConstraintF>,
+    ) -> Result<(), SynthesisError> {
+        if let Some(version) = self.version {
+            let version = C::ConstraintF::from(version);
+            FpVar::new_input(ark_relations::ns!(cs, "version"), || Ok(version))?
+                .enforce_equal(&FpVar::constant(version))?;
+        }
+        Ok(())
+    }
+}
+
+impl<C: CurveCycle, H: HashFamily<C::ConstraintF>, A: Arity<C::ConstraintF, H>>
+    ConstraintSynthesizer<C::ConstraintF> for SpendCircuit<C, H, A>
+{
+    fn generate_constraints(
+        self,
+        cs: ConstraintSystemRef<C::ConstraintF>,
+    ) -> Result<(), SynthesisError> {
+        let _ = self.enforce_spend(cs.clone())?;
+        self.enforce_version(cs)
+    }
+}
+
+/// The height of the tree `path` opens a leaf of, counted as in
+/// [`crate::build_tree`]: a tree of height `h` has `2^h` leaves.
+pub fn path_height<P: Config>(path: &Path<P>) -> usize {
+    path.auth_path.len() + 1
+}
+
+/// Checks that `path` has the number of nodes a tree of `height` needs,
+/// returning [`SynthesisError::Unsatisfiable`] if not.
+///
+/// The nodes are allocated one witness each, so the length fixes the shape of
+/// the circuit rather than being something a constraint could check. Keys
+/// from a setup with the right height reject a proof synthesized from a
+/// truncated path anyway; this catches it before proving.
+pub fn check_path_height<P: Config>(path: &Path<P>, height: usize) -> Result<(), SynthesisError> {
+    if path_height(path) != height {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+    Ok(())
+}
+
+/// Enforces that `old_leaf` is in the tree with root `old_root` at the
+/// position `old_path` opens, and that replacing it with `new_leaf` gives a
+/// tree with root `new_root`.
+///
+/// The siblings are unchanged by the update, so the new root is recomputed
+/// along `old_path` from the new leaf, as `PathVar::update_leaf` does.
+pub fn verify_update<F: PrimeField, H: HashFamily<F>>(
+    leaf_params: &H::ParametersVar,
+    two_to_one_params: &H::ParametersVar,
+    old_path: &PathVar<MerkleTreeParams<F, H>, F, MerkleTreeParamsVar<F, H>>,
+    old_root: &FpVar<F>,
+    old_leaf: &LeafVar<F>,
+    new_leaf: &LeafVar<F>,
+    new_root: &FpVar<F>,
+) -> Result<(), SynthesisError> {
+    old_path
+        .update_leaf(leaf_params, two_to_one_params, old_root, old_leaf, new_leaf)?
+        .enforce_equal(new_root)
+}
+
+/// Runs the circuit-specific Groth16 setup for the shape of `circuit`.
+///
+/// Keys are only valid for circuits with the same shape: any change to the
+/// constraints, the tree height of `circuit.proof`, `strict_nullifier`,
+/// `nullifier_domain`, `positional_nullifier`, `scalar_nullifier`, whether a
+/// `note` is opened, the `leaf_layout`, whether a `payout` is bound or the
+/// `version` requires rerunning setup and replacing `proof_keys.bin`.
+#[instrument(skip_all, fields(height = A::path_height(&circuit.proof)))]
+pub fn setup<
+    C: CurveCycle,
+    H: HashFamily<C::ConstraintF>,
+    A: Arity<C::ConstraintF, H>,
+    R: RngCore + CryptoRng,
+>(
+    circuit: SpendCircuit<C, H, A>,
+    rng: &mut R,
+) -> Result<Keys<C::Pairing>, SynthesisError> {
+    Groth16::<C::Pairing>::circuit_specific_setup(circuit, rng)
+}
+
+#[instrument(skip_all, fields(public_inputs = pk.vk.gamma_abc_g1.len() - 1))]
+pub fn prove<
+    C: CurveCycle,
+    H: HashFamily<C::ConstraintF>,
+    A: Arity<C::ConstraintF, H>,
+    R: RngCore + CryptoRng,
+>(
+    pk: &ProvingKey<C::Pairing>,
+    circuit: SpendCircuit<C, H, A>,
+    rng: &mut R,
+) -> Result<Proof<C::Pairing>, SynthesisError> {
+    Groth16::<C::Pairing>::prove(pk, circuit, rng)
+}
+
+/// Proves with a `StdRng` seeded from `seed`. Groth16 proofs are randomized,
+/// so this is what makes proofs reproducible: the same key, circuit and seed
+/// always yield the same proof, while different seeds yield different ones.
+pub fn prove_with_seed<
+    C: CurveCycle,
+    H: HashFamily<C::ConstraintF>,
+    A: Arity<C::ConstraintF, H>,
+>(
+    pk: &ProvingKey<C::Pairing>,
+    circuit: SpendCircuit<C, H, A>,
+    seed: u64,
+) -> Result<Proof<C::Pairing>, SynthesisError> {
+    prove(pk, circuit, &mut StdRng::seed_from_u64(seed))
+}
+
+/// Proves the spend of the leaf `path` opens under `root`, for provers that
+/// keep their own path rather than the whole tree. The circuit is the one
+/// [`CircuitParams::spend_circuit`] builds, with `strict_nullifier` and
+/// `height` set as given and the nullifier derived under [`LEGACY_DOMAIN`],
+/// which is returned with the proof.
+///
+/// A strict spend proves with the [`canonical_secret`] of `secret`, so the
+/// nullifier is the one of that secret. `proof_keys.bin` is for the
+/// non-strict circuit with the fixture tree's height.
+#[allow(clippy::too_many_arguments)]
+pub fn prove_from_path<C: CurveCycle, H: HashFamily<C::ConstraintF>, R: RngCore + CryptoRng>(
+    pk: &ProvingKey<C::Pairing>,
+    root: C::ConstraintF,
+    path: Path<MerkleTreeParams<C::ConstraintF, H>>,
+    secret: Zeroizing<C::ConstraintF>,
+    params: &CircuitParams<C::ConstraintF, H>,
+    strict_nullifier: bool,
+    height: Option<usize>,
+    rng: &mut R,
+) -> Result<(Proof<C::Pairing>, C::ConstraintF), ark_crypto_primitives::Error> {
+    let secret = if strict_nullifier {
+        Zeroizing::new(canonical_secret::<C>(*secret))
+    } else {
+        secret
+    };
+    let nullifier = nullifier_with::<_, H>(&params.leaf, *secret, LEGACY_DOMAIN)?;
+    let mut circuit = params
+        .spend_circuit::<C>(root, path, secret, nullifier)
+        .with_strict_nullifier(strict_nullifier);
+    circuit.height = height;
+
+    Ok((prove(pk, circuit, rng)?, nullifier))
+}
+
+/// Exactly the inputs `circuit` allocates with `new_input`, in allocation
+/// order, so a verifier built from the same circuit can't get them out of
+/// order. Its length is the number of public inputs the keys expect.
+pub fn public_inputs<C: CurveCycle, H: HashFamily<C::ConstraintF>, A: Arity<C::ConstraintF, H>>(
+    circuit: &SpendCircuit<C, H, A>,
+) -> Vec<C::ConstraintF> {
+    circuit.public_inputs()
+}
@@ -0,0 +1,129 @@
+use ark_crypto_primitives::crh::{CRHScheme, CRHSchemeGadget};
+use ark_crypto_primitives::sponge::poseidon::PoseidonConfig;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::UniformRand;
+use ark_mnt4_753::Fr as MNT4BigFr;
+use ark_mnt6_753::{constraints::G1Var, Fr as MNT6BigFr, G1Affine, G1Projective};
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_std::rand::Rng;
+
+use crate::{
+    fr_to_scalar_reduced, public_key, scalar_to_fr, LeafH, LeafHG, Mnt753Cycle, SpendCircuit,
+};
+
+/// A Schnorr signature over MNT6-753 G1: `s * G == r + e * pk` for the
+/// challenge `e` of [`challenge`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Signature {
+    pub r: G1Affine,
+    pub s: MNT6BigFr,
+}
+
+/// `H([r.x, r.y, pk.x, pk.y, message])`, hashed in the base field of G1 so
+/// the circuit computes it natively.
+pub fn challenge(
+    params: &PoseidonConfig<MNT4BigFr>,
+    r: &G1Affine,
+    pk: &G1Affine,
+    message: MNT4BigFr,
+) -> Result<MNT4BigFr, ark_crypto_primitives::Error> {
+    <LeafH as CRHScheme>::evaluate(params, vec![r.x, r.y, pk.x, pk.y, message])
+}
+
+/// Signs `message` with the key owned by `secret`.
+pub fn sign<R: Rng>(
+    params: &PoseidonConfig<MNT4BigFr>,
+    secret: MNT4BigFr,
+    message: MNT4BigFr,
+    rng: &mut R,
+) -> Result<Signature, ark_crypto_primitives::Error> {
+    let k = MNT6BigFr::rand(rng);
+    let r = (G1Affine::generator() * k).into_affine();
+    let e = challenge(params, &r, &public_key(secret), message)?;
+
+    Ok(Signature {
+        r,
+        // The circuit multiplies by the bits of `e` and the secret, which
+        // reduces them into the scalar field
+        s: k + fr_to_scalar_reduced(e) * fr_to_scalar_reduced(secret),
+    })
+}
+
+pub fn verify_signature(
+    params: &PoseidonConfig<MNT4BigFr>,
+    pk: &G1Affine,
+    message: MNT4BigFr,
+    signature: &Signature,
+) -> Result<bool, ark_crypto_primitives::Error> {
+    let e = challenge(params, &signature.r, pk, message)?;
+    Ok(G1Affine::generator() * signature.s
+        == signature.r.into_group() + *pk * fr_to_scalar_reduced(e))
+}
+
+/// A [`SpendCircuit`] that also checks a [`Signature`] by the spent key over
+/// `message`, binding the proof to one transaction so it can't be replayed
+/// for another.
+///
+/// The options of `spend` carry over. Public inputs are allocated as those
+/// of the spend, with the message after the payout and before the version.
+#[derive(Clone)]
+pub struct SpendAuthCircuit {
+    pub spend: SpendCircuit<Mnt753Cycle>,
+    pub message: MNT4BigFr,
+    pub signature: Signature,
+}
+
+impl SpendAuthCircuit {
+    pub fn new(spend: SpendCircuit<Mnt753Cycle>, message: MNT4BigFr, signature: Signature) -> Self {
+        Self {
+            spend,
+            message,
+            signature,
+        }
+    }
+
+    /// The public inputs in allocation order, see [`SpendCircuit::public_inputs`].
+    pub fn public_inputs(&self) -> Vec<MNT4BigFr> {
+        let mut public_inputs = self.spend.public_inputs();
+        // The version stays the last input
+        let message_index = public_inputs.len() - usize::from(self.spend.version.is_some());
+        public_inputs.insert(message_index, self.message);
+        public_inputs
+    }
+}
+
+impl ConstraintSynthesizer<MNT4BigFr> for SpendAuthCircuit {
+    fn generate_constraints(
+        self,
+        cs: ConstraintSystemRef<MNT4BigFr>,
+    ) -> Result<(), SynthesisError> {
+        let ((pk_x, pk_y), leaf_crh_params_var) = self.spend.enforce_spend(cs.clone())?;
+
+        let message = FpVar::new_input(ark_relations::ns!(cs, "message"), || Ok(self.message))?;
+
+        let r = G1Var::new_witness(ark_relations::ns!(cs, "signature_r"), || {
+            Ok(G1Projective::from(self.signature.r))
+        })?;
+        // `s` is below the MNT6-753 modulus, so it fits in the base field
+        let s = FpVar::new_witness(ark_relations::ns!(cs, "signature_s"), || {
+            Ok(scalar_to_fr(self.signature.s))
+        })?;
+
+        let r_affine = r.to_affine()?;
+        let e = <LeafHG as CRHSchemeGadget<LeafH, MNT4BigFr>>::evaluate(
+            &leaf_crh_params_var,
+            &[r_affine.x, r_affine.y, pk_x.clone(), pk_y.clone(), message],
+        )?;
+
+        let pk = G1Var::new(pk_x, pk_y, FpVar::one());
+        let generator =
+            G1Var::new_constant(ark_relations::ns!(cs, "generator"), G1Affine::generator())?;
+        let lhs = generator.scalar_mul_le(s.to_bits_le()?.iter())?;
+        let rhs = r + pk.scalar_mul_le(e.to_bits_le()?.iter())?;
+        lhs.enforce_equal(&rhs)?;
+
+        self.spend.enforce_version(cs)
+    }
+}
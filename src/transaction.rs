@@ -0,0 +1,49 @@
+use ark_ec::pairing::Pairing;
+use ark_groth16::{Proof, VerifyingKey};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+
+use crate::{verify_spend, VerifyError};
+
+/// A spend as it is submitted: the proof together with the public inputs it
+/// was made for.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpendTransaction<E: Pairing> {
+    pub root: E::ScalarField,
+    pub nullifier: E::ScalarField,
+    pub proof: Proof<E>,
+}
+
+impl<E: Pairing> SpendTransaction<E> {
+    pub fn new(root: E::ScalarField, nullifier: E::ScalarField, proof: Proof<E>) -> Self {
+        Self {
+            root,
+            nullifier,
+            proof,
+        }
+    }
+
+    /// The public inputs in the order the spend circuit allocates them.
+    pub fn public_inputs(&self) -> [E::ScalarField; 2] {
+        [self.root, self.nullifier]
+    }
+
+    pub fn verify(&self, vk: &VerifyingKey<E>) -> Result<(), VerifyError> {
+        verify_spend(vk, &self.root, &self.nullifier, &self.proof)
+    }
+
+    /// The uncompressed encoding of `(root, nullifier, proof)`.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SerializationError> {
+        let mut buffer = Vec::new();
+        self.root.serialize_uncompressed(&mut buffer)?;
+        self.nullifier.serialize_uncompressed(&mut buffer)?;
+        self.proof.serialize_uncompressed(&mut buffer)?;
+        Ok(buffer)
+    }
+
+    /// Decodes [`Self::to_bytes`], checking that the proof's points are valid.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SerializationError> {
+        let (root, nullifier, proof) =
+            <(E::ScalarField, E::ScalarField, Proof<E>)>::deserialize_uncompressed(bytes)?;
+        Ok(Self::new(root, nullifier, proof))
+    }
+}
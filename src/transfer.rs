@@ -0,0 +1,148 @@
+use ark_crypto_primitives::merkle_tree::Path;
+use ark_crypto_primitives::sponge::{poseidon::PoseidonConfig, Absorb};
+use ark_ff::PrimeField;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+
+use crate::{
+    commitment, commitment_var, enforce_all_distinct, enforce_in_range, CurveCycle,
+    MerkleTreeParams, Mnt753Cycle, Note, SpendGadget, LEGACY_DOMAIN,
+};
+
+/// Bit length every note value and the fee are range checked to.
+pub const VALUE_BITS: usize = 64;
+
+/// A note being spent, opened as the leaf its [`Note`] is stored as.
+#[derive(Clone)]
+pub struct InputNote<F: PrimeField + Absorb> {
+    pub secret: F,
+    pub value: u64,
+    pub rho: F,
+    pub proof: Path<MerkleTreeParams<F>>,
+    pub nullifier: F,
+}
+
+/// Spends `inputs` and splits their value into the `outputs`, enforcing
+/// `sum(inputs) == sum(outputs) + fee`.
+///
+/// Public inputs are the root, the fee, the input nullifiers and the output
+/// [`commitment`]s, in that order, so a coordinator can insert each output
+/// into the tree as its [`Note::leaf`], which a later transfer spends. Every value is range checked to [`VALUE_BITS`], so a sum
+/// of values can never wrap around the field.
+#[derive(Clone)]
+pub struct TransferCircuit<C: CurveCycle = Mnt753Cycle> {
+    pub leaf_params: PoseidonConfig<C::ConstraintF>,
+    pub two_to_one_params: PoseidonConfig<C::ConstraintF>,
+    pub root: C::ConstraintF,
+    pub fee: u64,
+    pub inputs: Vec<InputNote<C::ConstraintF>>,
+    pub outputs: Vec<Note<C::ConstraintF>>,
+    pub strict_nullifier: bool,
+    pub nullifier_domain: u64,
+}
+
+impl<C: CurveCycle> TransferCircuit<C> {
+    pub fn new(
+        leaf_params: PoseidonConfig<C::ConstraintF>,
+        two_to_one_params: PoseidonConfig<C::ConstraintF>,
+        root: C::ConstraintF,
+        fee: u64,
+        inputs: Vec<InputNote<C::ConstraintF>>,
+        outputs: Vec<Note<C::ConstraintF>>,
+    ) -> Self {
+        Self {
+            leaf_params,
+            two_to_one_params,
+            root,
+            fee,
+            inputs,
+            outputs,
+            strict_nullifier: true,
+            nullifier_domain: LEGACY_DOMAIN,
+        }
+    }
+
+    pub fn public_inputs(&self) -> Result<Vec<C::ConstraintF>, ark_crypto_primitives::Error> {
+        let mut public_inputs = vec![self.root, C::ConstraintF::from(self.fee)];
+        public_inputs.extend(self.inputs.iter().map(|note| note.nullifier));
+        for note in &self.outputs {
+            public_inputs.push(commitment(&self.leaf_params, note)?);
+        }
+        Ok(public_inputs)
+    }
+}
+
+impl<C: CurveCycle> ConstraintSynthesizer<C::ConstraintF> for TransferCircuit<C> {
+    fn generate_constraints(
+        self,
+        cs: ConstraintSystemRef<C::ConstraintF>,
+    ) -> Result<(), SynthesisError> {
+        let root = FpVar::new_input(ark_relations::ns!(cs, "merkle_root"), || Ok(self.root))?;
+        let fee = FpVar::new_input(ark_relations::ns!(cs, "fee"), || {
+            Ok(C::ConstraintF::from(self.fee))
+        })?;
+        enforce_in_range(&fee, VALUE_BITS)?;
+
+        let (leaf_crh_params_var, two_to_one_crh_params_var) = SpendGadget::<C>::allocate_params(
+            cs.clone(),
+            &self.leaf_params,
+            &self.two_to_one_params,
+        )?;
+
+        let gadget = SpendGadget::<C> {
+            cs: cs.clone(),
+            leaf_crh_params_var: &leaf_crh_params_var,
+            two_to_one_crh_params_var: &two_to_one_crh_params_var,
+            root: &root,
+            strict_nullifier: self.strict_nullifier,
+            nullifier_domain: self.nullifier_domain,
+            positional_nullifier: false,
+            scalar_nullifier: false,
+        };
+
+        let mut balance = FpVar::zero();
+        let mut nullifiers = Vec::with_capacity(self.inputs.len());
+        for note in &self.inputs {
+            let value = FpVar::new_witness(ark_relations::ns!(cs, "input_value"), || {
+                Ok(C::ConstraintF::from(note.value))
+            })?;
+            enforce_in_range(&value, VALUE_BITS)?;
+            let rho = FpVar::new_witness(ark_relations::ns!(cs, "input_rho"), || Ok(note.rho))?;
+
+            nullifiers.push(gadget.enforce_note(
+                &note.proof,
+                note.secret,
+                note.nullifier,
+                value.clone(),
+                rho,
+            )?);
+            balance += value;
+        }
+
+        // The same note must not be spent twice within one transfer
+        enforce_all_distinct(&nullifiers)?;
+
+        for note in &self.outputs {
+            let pk_x = FpVar::new_witness(ark_relations::ns!(cs, "output_pk_x"), || Ok(note.pk_x))?;
+            let value = FpVar::new_witness(ark_relations::ns!(cs, "output_value"), || {
+                Ok(C::ConstraintF::from(note.value))
+            })?;
+            enforce_in_range(&value, VALUE_BITS)?;
+            let rho = FpVar::new_witness(ark_relations::ns!(cs, "output_rho"), || Ok(note.rho))?;
+
+            let output_commitment =
+                FpVar::new_input(ark_relations::ns!(cs, "output_commitment"), || {
+                    commitment(&self.leaf_params, note).map_err(|_| SynthesisError::Unsatisfiable)
+                })?;
+            commitment_var(&leaf_crh_params_var, pk_x, value.clone(), rho)?
+                .enforce_equal(&output_commitment)?;
+
+            balance -= value;
+        }
+
+        (balance - fee).enforce_equal(&FpVar::zero())?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,196 @@
+use ark_crypto_primitives::crh::CRHScheme;
+use ark_ff::Zero;
+use ark_mnt6_753::G1Affine;
+
+use tracing::instrument;
+
+use std::fmt;
+
+use crate::{public_key_leaf, CircuitParams, ConstraintF, LeafH, MntMerkleTree};
+
+/// The leaf used to fill unoccupied positions of a fixed-height tree.
+pub fn empty_leaf() -> Vec<ConstraintF> {
+    vec![ConstraintF::zero()]
+}
+
+/// The tallest tree [`build_tree`] builds. The arkworks tree is dense, so
+/// every position is hashed and held in memory: about 300 MB at this height,
+/// and twice that per level above it. [`crate::IncrementalTree`] only stores
+/// the positions in use, for taller trees.
+pub const MAX_TREE_HEIGHT: usize = 20;
+
+#[derive(Debug)]
+pub enum TreeError {
+    ZeroHeight,
+    HeightTooLarge {
+        height: usize,
+        max: usize,
+    },
+    NoLeaves,
+    /// The arkworks tree needs a power of two leaves, at least two, and
+    /// panics otherwise.
+    UnpaddedLeafCount {
+        leaves: usize,
+    },
+    /// A [`crate::WideMerkleTree`] needs a power of its arity leaves.
+    UnpaddedWideLeafCount {
+        leaves: usize,
+        arity: usize,
+    },
+    UnsupportedArity {
+        arity: usize,
+    },
+    TooManyLeaves {
+        leaves: usize,
+        capacity: usize,
+    },
+    ReservedKey,
+    IndexOutOfRange {
+        index: usize,
+        capacity: usize,
+    },
+    Crypto(ark_crypto_primitives::Error),
+}
+
+impl fmt::Display for TreeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TreeError::ZeroHeight => write!(f, "tree height must be at least 1"),
+            TreeError::HeightTooLarge { height, max } => write!(
+                f,
+                "a dense tree of height {} is too large to build, the limit is {}",
+                height, max
+            ),
+            TreeError::NoLeaves => write!(f, "a tree needs at least one leaf"),
+            TreeError::UnpaddedLeafCount { leaves } => write!(
+                f,
+                "the arkworks tree needs a power of two leaves, at least two, not {}",
+                leaves
+            ),
+            TreeError::UnpaddedWideLeafCount { leaves, arity } => write!(
+                f,
+                "a tree of arity {} needs a power of {} leaves, at least {}, not {}",
+                arity, arity, arity, leaves
+            ),
+            TreeError::UnsupportedArity { arity } => write!(
+                f,
+                "a tree's arity must be a power of two of at least 2, not {}",
+                arity
+            ),
+            TreeError::TooManyLeaves { leaves, capacity } => write!(
+                f,
+                "{} leaves do not fit in a tree with {} positions",
+                leaves, capacity
+            ),
+            TreeError::ReservedKey => write!(f, "keys 0 and -1 are reserved as sentinels"),
+            TreeError::IndexOutOfRange { index, capacity } => write!(
+                f,
+                "index {} is out of range for a tree with {} positions",
+                index, capacity
+            ),
+            TreeError::Crypto(e) => write!(f, "failed to hash tree: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for TreeError {}
+
+/// The smallest `height` [`build_tree`] accepts for `leaf_count` leaves:
+/// `ceil(log2(leaf_count))`, and at least 1 since arkworks trees need two
+/// leaves. Paths of such a tree have `height - 1` inner siblings, see
+/// [`crate::path_height`].
+pub fn tree_depth(leaf_count: usize) -> usize {
+    (leaf_count.next_power_of_two().trailing_zeros() as usize).max(1)
+}
+
+/// Builds a tree with `2^height` positions, filling the ones past
+/// `leaves.len()` with [`empty_leaf`], and returns it with the padded leaves.
+///
+/// The arkworks tree is dense, so every padded position is materialized, and
+/// heights above [`MAX_TREE_HEIGHT`] are refused rather than run out of
+/// memory. A single leaf is padded like any other count, as `height` is at
+/// least 1.
+#[instrument(skip_all, fields(leaves = leaves.len(), height = height))]
+pub fn build_tree(
+    leaf_params: &<LeafH as CRHScheme>::Parameters,
+    two_to_one_params: &<LeafH as CRHScheme>::Parameters,
+    leaves: &[Vec<ConstraintF>],
+    height: usize,
+) -> Result<(MntMerkleTree, Vec<Vec<ConstraintF>>), TreeError> {
+    if height == 0 {
+        return Err(TreeError::ZeroHeight);
+    }
+    if height > MAX_TREE_HEIGHT {
+        return Err(TreeError::HeightTooLarge {
+            height,
+            max: MAX_TREE_HEIGHT,
+        });
+    }
+    if leaves.is_empty() {
+        return Err(TreeError::NoLeaves);
+    }
+    let capacity = 1usize << height;
+    if leaves.len() > capacity {
+        return Err(TreeError::TooManyLeaves {
+            leaves: leaves.len(),
+            capacity,
+        });
+    }
+
+    let mut padded = leaves.to_vec();
+    padded.resize(capacity, empty_leaf());
+
+    let tree = MntMerkleTree::new(
+        leaf_params,
+        two_to_one_params,
+        padded.iter().map(|x| x.as_slice()),
+    )
+    .map_err(TreeError::Crypto)?;
+
+    Ok((tree, padded))
+}
+
+/// Builds the smallest tree holding the [`public_key_leaf`]s of `pks`, in
+/// order, and returns it with its leaves, padded as [`build_tree`] does.
+pub fn tree_from_public_keys(
+    pks: &[G1Affine],
+    params: &CircuitParams,
+) -> Result<(MntMerkleTree, Vec<Vec<ConstraintF>>), TreeError> {
+    let leaves: Vec<_> = pks.iter().map(public_key_leaf).collect();
+    build_tree(
+        &params.leaf,
+        &params.two_to_one,
+        &leaves,
+        tree_depth(leaves.len()),
+    )
+}
+
+/// Builds the same tree as `MntMerkleTree::new`, hashing the leaves on the
+/// rayon thread pool before the inner nodes are folded.
+#[cfg(feature = "parallel")]
+#[instrument(skip_all, fields(leaves = leaves.len()))]
+pub fn build_tree_parallel(
+    leaf_params: &<LeafH as CRHScheme>::Parameters,
+    two_to_one_params: &<LeafH as CRHScheme>::Parameters,
+    leaves: &[Vec<ConstraintF>],
+) -> Result<MntMerkleTree, TreeError> {
+    use rayon::prelude::*;
+
+    if leaves.len() < 2 || !leaves.len().is_power_of_two() {
+        return Err(TreeError::UnpaddedLeafCount {
+            leaves: leaves.len(),
+        });
+    }
+
+    let leaf_digests = leaves
+        .par_iter()
+        // The crate's boxed error is not `Send`, so it can't cross threads
+        .map(|leaf| {
+            <LeafH as CRHScheme>::evaluate(leaf_params, leaf.as_slice()).map_err(|e| e.to_string())
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| TreeError::Crypto(e.into()))?;
+
+    MntMerkleTree::new_with_leaf_digest(leaf_params, two_to_one_params, leaf_digests)
+        .map_err(TreeError::Crypto)
+}
@@ -0,0 +1,234 @@
+use ark_ec::pairing::Pairing;
+use ark_ff::PrimeField;
+use ark_groth16::{prepare_verifying_key, Groth16, Proof, VerifyingKey};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError, Valid};
+use blake2::{Blake2s, Digest};
+
+use ark_crypto_primitives::snark::SNARK;
+
+use std::fmt;
+
+/// [`vk_fingerprint`] of the verifying key in `proof_keys.bin`.
+pub const PUZZLE_VK_FINGERPRINT: [u8; 32] = [
+    0xef, 0x81, 0x11, 0x10, 0xc2, 0xa9, 0x65, 0x86, 0x51, 0xe4, 0xfc, 0x92, 0xaf, 0xae, 0x90, 0xbe,
+    0x89, 0x33, 0x9e, 0xbd, 0xec, 0x0f, 0x17, 0x26, 0xb5, 0x11, 0x09, 0x64, 0x29, 0xb4, 0x15, 0xba,
+];
+
+/// BLAKE2s of the uncompressed encoding of `vk`. Any change to the circuit's
+/// shape changes the key, so this tells keys from different setups apart
+/// whichever encoding they were stored with.
+pub fn vk_fingerprint<E: Pairing>(vk: &VerifyingKey<E>) -> [u8; 32] {
+    let mut buffer = Vec::new();
+    vk.serialize_uncompressed(&mut buffer)
+        .expect("writing to a vector can't fail");
+
+    let mut fingerprint = [0u8; 32];
+    fingerprint.copy_from_slice(&Blake2s::digest(&buffer));
+    fingerprint
+}
+
+#[derive(Debug)]
+pub enum VerifyError {
+    /// The verifying key expects a different number of public inputs.
+    PublicInputLength {
+        expected: usize,
+        actual: usize,
+    },
+    Deserialize(SerializationError),
+    /// The public input at `index` is not reduced below the field modulus.
+    NonCanonicalInput {
+        index: usize,
+    },
+    /// The proof does not verify for these public inputs.
+    Rejected,
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::PublicInputLength { expected, actual } => {
+                write!(f, "expected {} public inputs, got {}", expected, actual)
+            }
+            VerifyError::Deserialize(e) => write!(f, "could not deserialize the proof: {}", e),
+            VerifyError::NonCanonicalInput { index } => {
+                write!(f, "public input {} is not a canonical field element", index)
+            }
+            VerifyError::Rejected => write!(f, "proof rejected"),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            VerifyError::Deserialize(e) => Some(e),
+            VerifyError::PublicInputLength { .. }
+            | VerifyError::NonCanonicalInput { .. }
+            | VerifyError::Rejected => None,
+        }
+    }
+}
+
+impl From<SerializationError> for VerifyError {
+    fn from(e: SerializationError) -> Self {
+        VerifyError::Deserialize(e)
+    }
+}
+
+/// Like [`crate::verify`], telling a malformed input apart from a rejection.
+pub fn verify_spend<E: Pairing>(
+    vk: &VerifyingKey<E>,
+    root: &E::ScalarField,
+    nullifier: &E::ScalarField,
+    proof: &Proof<E>,
+) -> Result<(), VerifyError> {
+    verify_public_inputs(vk, &[*root, *nullifier], proof)
+}
+
+/// Accepts the proof if it verifies against any of `allowed_roots`, such as
+/// the roots kept by an [`crate::IncrementalTree`], returning the index of
+/// the first root it verifies against. Costs one verification per root
+/// tried.
+pub fn verify_spend_multi_root<E: Pairing>(
+    vk: &VerifyingKey<E>,
+    allowed_roots: &[E::ScalarField],
+    nullifier: &E::ScalarField,
+    proof: &Proof<E>,
+) -> Result<usize, VerifyError> {
+    for (i, root) in allowed_roots.iter().enumerate() {
+        match verify_spend(vk, root, nullifier, proof) {
+            Ok(()) => return Ok(i),
+            Err(VerifyError::Rejected) => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Err(VerifyError::Rejected)
+}
+
+/// Checks that every input is stored reduced below the field modulus, as
+/// [`verify_public_inputs`] does before verifying.
+///
+/// Checked deserialization already rejects encodings at or above the
+/// modulus, so this guards elements built from raw limbs with
+/// `Fp::new_unchecked`, on which arithmetic gives results that differ from
+/// those on the element they stand for. Such an element is the only one a
+/// round trip through its integer changes.
+pub fn validate_public_inputs<F: PrimeField>(public_inputs: &[F]) -> Result<(), VerifyError> {
+    match public_inputs
+        .iter()
+        .position(|input| F::from_bigint(input.into_bigint()) != Some(*input))
+    {
+        Some(index) => Err(VerifyError::NonCanonicalInput { index }),
+        None => Ok(()),
+    }
+}
+
+pub fn verify_public_inputs<E: Pairing>(
+    vk: &VerifyingKey<E>,
+    public_inputs: &[E::ScalarField],
+    proof: &Proof<E>,
+) -> Result<(), VerifyError> {
+    let expected = vk.gamma_abc_g1.len() - 1;
+    if public_inputs.len() != expected {
+        return Err(VerifyError::PublicInputLength {
+            expected,
+            actual: public_inputs.len(),
+        });
+    }
+    validate_public_inputs(public_inputs)?;
+
+    // Groth16 only errors on an input count mismatch, which is checked above
+    match Groth16::<E>::verify(vk, public_inputs, proof) {
+        Ok(true) => Ok(()),
+        _ => Err(VerifyError::Rejected),
+    }
+}
+
+/// Which step of verification a proof got through, see [`verify_detailed`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerifyReport {
+    Accepted,
+    /// The inputs could not be prepared: the verifying key expects a
+    /// different number of them.
+    InputCount {
+        expected: usize,
+        actual: usize,
+    },
+    /// A point of the proof is off the curve or outside its prime-order
+    /// subgroup, so the proof was corrupted rather than made for other
+    /// inputs.
+    InvalidProof,
+    /// The proof is well formed, but the pairing check fails for these
+    /// inputs, as it does for a proof of another root or nullifier.
+    PairingFailed,
+}
+
+/// Like [`verify_public_inputs`], reporting which check failed instead of
+/// only whether the proof was rejected. The steps are those of arkworks'
+/// verifier with a `PreparedVerifyingKey`, with the proof's points checked
+/// before the pairing.
+pub fn verify_detailed<E: Pairing>(
+    vk: &VerifyingKey<E>,
+    public_inputs: &[E::ScalarField],
+    proof: &Proof<E>,
+) -> VerifyReport {
+    let pvk = prepare_verifying_key(vk);
+    let prepared_inputs = match Groth16::<E>::prepare_inputs(&pvk, public_inputs) {
+        Ok(prepared_inputs) => prepared_inputs,
+        Err(_) => {
+            return VerifyReport::InputCount {
+                expected: vk.gamma_abc_g1.len() - 1,
+                actual: public_inputs.len(),
+            }
+        }
+    };
+    if proof.check().is_err() {
+        return VerifyReport::InvalidProof;
+    }
+
+    match Groth16::<E>::verify_proof_with_prepared_inputs(&pvk, proof, &prepared_inputs) {
+        Ok(true) => VerifyReport::Accepted,
+        _ => VerifyReport::PairingFailed,
+    }
+}
+
+/// Verifies a proof for `public_inputs` given the uncompressed encodings of
+/// the proof and verifying key, for callers without a filesystem such as a
+/// browser. The encodings are checked, as they may come from anywhere.
+pub fn verify_proof_bytes<E: Pairing>(
+    proof_bytes: &[u8],
+    vk_bytes: &[u8],
+    public_inputs: &[E::ScalarField],
+) -> Result<(), VerifyError> {
+    let proof = Proof::<E>::deserialize_uncompressed(proof_bytes)?;
+    let vk = VerifyingKey::<E>::deserialize_uncompressed(vk_bytes)?;
+
+    verify_public_inputs(&vk, public_inputs, &proof)
+}
+
+/// The points `(A, B, C)` of `proof`, for verifiers that take them apart
+/// such as a recursive or aggregating one.
+pub fn decompose_proof<E: Pairing>(proof: &Proof<E>) -> (E::G1Affine, E::G2Affine, E::G1Affine) {
+    (proof.a, proof.b, proof.c)
+}
+
+/// The inverse of [`decompose_proof`]. The points are taken as they are, so
+/// points from an untrusted source should be checked first.
+pub fn compose_proof<E: Pairing>(a: E::G1Affine, b: E::G2Affine, c: E::G1Affine) -> Proof<E> {
+    Proof { a, b, c }
+}
+
+/// Whether `a` and `b` have the same compressed encoding. Groth16 proofs are
+/// randomized, so two proofs of the same statement only compare equal when
+/// both came from [`crate::prove_with_seed`] with the same key, circuit and
+/// seed; otherwise this tells duplicates of one proof apart from others.
+pub fn proofs_equal<E: Pairing>(a: &Proof<E>, b: &Proof<E>) -> bool {
+    let encode = |proof: &Proof<E>| {
+        let mut buffer = Vec::new();
+        proof
+            .serialize_compressed(&mut buffer)
+            .expect("a proof serializes into a vector");
+        buffer
+    };
+    encode(a) == encode(b)
+}
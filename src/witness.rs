@@ -0,0 +1,134 @@
+use ark_crypto_primitives::crh::CRHScheme;
+use ark_crypto_primitives::merkle_tree::Path;
+
+use std::fmt;
+
+use crate::{
+    build_tree, canonical_secret, find_leaf_index, nullifier, public_key, tree_depth,
+    CircuitParams, ConstraintF, LeafH, Mnt753Cycle, MntMerkleTree, MntMerkleTreeParams,
+    SpendCircuit, Zeroizing, LEGACY_DOMAIN,
+};
+
+#[derive(Debug)]
+pub enum WitnessError {
+    /// No leaf is the public key of the secret.
+    UnownedSecret,
+    IndexOutOfRange {
+        index: usize,
+        leaves: usize,
+    },
+    /// The leaf at `index` is not the public key x-coordinate of the secret.
+    LeafMismatch {
+        index: usize,
+    },
+    /// The regenerated path does not lead from the leaf to the tree's root.
+    PathMismatch {
+        index: usize,
+    },
+    // The crate's boxed errors are not `Send`, so only the message is kept
+    Crypto(String),
+}
+
+impl fmt::Display for WitnessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WitnessError::UnownedSecret => write!(f, "the secret owns no leaf"),
+            WitnessError::IndexOutOfRange { index, leaves } => {
+                write!(
+                    f,
+                    "leaf index {} is out of range for {} leaves",
+                    index, leaves
+                )
+            }
+            WitnessError::LeafMismatch { index } => {
+                write!(f, "leaf {} is not owned by the secret", index)
+            }
+            WitnessError::PathMismatch { index } => write!(
+                f,
+                "the path for leaf {} does not verify against the root",
+                index
+            ),
+            WitnessError::Crypto(e) => write!(f, "failed to check the witness: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for WitnessError {}
+
+/// `leaves[index]`, or [`WitnessError::IndexOutOfRange`] when the fixture has
+/// fewer leaves.
+pub fn leaf_at(leaves: &[Vec<ConstraintF>], index: usize) -> Result<&[ConstraintF], WitnessError> {
+    leaves
+        .get(index)
+        .map(Vec::as_slice)
+        .ok_or(WitnessError::IndexOutOfRange {
+            index,
+            leaves: leaves.len(),
+        })
+}
+
+/// Checks that `secret` owns `leaves[index]` and that the leaf is in `tree`,
+/// returning the path to prove with. This catches inconsistent fixtures
+/// before the expensive proving step, which would only fail as unsatisfiable.
+pub fn validate_witness(
+    leaf_params: &<LeafH as CRHScheme>::Parameters,
+    two_to_one_params: &<LeafH as CRHScheme>::Parameters,
+    tree: &MntMerkleTree,
+    leaves: &[Vec<ConstraintF>],
+    index: usize,
+    secret: ConstraintF,
+) -> Result<Path<MntMerkleTreeParams>, WitnessError> {
+    let leaf = leaf_at(leaves, index)?;
+    if leaf != [public_key(secret).x] {
+        return Err(WitnessError::LeafMismatch { index });
+    }
+
+    let path = tree
+        .generate_proof(index)
+        .map_err(|e| WitnessError::Crypto(e.to_string()))?;
+    let valid = path
+        .verify(leaf_params, two_to_one_params, &tree.root(), leaf)
+        .map_err(|e| WitnessError::Crypto(e.to_string()))?;
+    if !valid {
+        return Err(WitnessError::PathMismatch { index });
+    }
+
+    Ok(path)
+}
+
+impl SpendCircuit {
+    /// The spend of the leaf `secret` owns: finds the leaf, builds the tree
+    /// of `leaves` padded as [`build_tree`] does, checks the path with
+    /// [`validate_witness`] and derives the nullifier under
+    /// [`LEGACY_DOMAIN`]. The circuit is strict and pinned to the tree's
+    /// height; the builder methods change the rest.
+    ///
+    /// The circuit spends the [`canonical_secret`] of `secret`, so keys in
+    /// the upper half of the scalar field are spent as `r - s`, and the
+    /// nullifier is the one of that secret.
+    pub fn from_secret(
+        leaves: &[Vec<ConstraintF>],
+        secret: Zeroizing<ConstraintF>,
+        params: &CircuitParams,
+    ) -> Result<Self, WitnessError> {
+        let secret = Zeroizing::new(canonical_secret::<Mnt753Cycle>(*secret));
+        let index = find_leaf_index(leaves, *secret).ok_or(WitnessError::UnownedSecret)?;
+        let height = tree_depth(leaves.len());
+        let (tree, padded) = build_tree(&params.leaf, &params.two_to_one, leaves, height)
+            .map_err(|e| WitnessError::Crypto(e.to_string()))?;
+        let path = validate_witness(
+            &params.leaf,
+            &params.two_to_one,
+            &tree,
+            &padded,
+            index,
+            *secret,
+        )?;
+        let nullifier = nullifier(&params.leaf, *secret, LEGACY_DOMAIN)
+            .map_err(|e| WitnessError::Crypto(e.to_string()))?;
+
+        Ok(params
+            .spend_circuit(tree.root(), path, secret, nullifier)
+            .with_height(height))
+    }
+}
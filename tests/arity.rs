@@ -0,0 +1,207 @@
+//! Spends of a leaf of a 4-ary tree, natively and in-circuit, against the
+//! binary tree over the same leaves.
+#![cfg(feature = "prover")]
+
+use ark_crypto_primitives::crh::{poseidon::CRH, CRHScheme};
+use ark_mnt4_753::Fr as MNT4BigFr;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+use puzzle_gamma_ray::{
+    generate_leaves, nullifier, positional_nullifier, prove, setup, verify_spend, CircuitParams,
+    Mnt753Cycle, PoseidonFamily, SpendCircuit, TreeError, Wide, WideMerkleTree, Zeroizing,
+    LEGACY_DOMAIN,
+};
+
+type QuaternarySpend = SpendCircuit<Mnt753Cycle, PoseidonFamily, Wide<4>>;
+
+/// Whether `circuit` is satisfied, and the constraints it took.
+fn synthesize<C: ConstraintSynthesizer<MNT4BigFr>>(circuit: C) -> (bool, usize) {
+    let cs = ConstraintSystem::new_ref();
+    circuit.generate_constraints(cs.clone()).unwrap();
+    (cs.is_satisfied().unwrap(), cs.num_constraints())
+}
+
+#[test]
+fn every_leaf_of_a_4_ary_tree_is_spent() {
+    let params = CircuitParams::poseidon();
+    let (leaves, secrets) = generate_leaves(16, &mut StdRng::seed_from_u64(0));
+    let tree =
+        WideMerkleTree::<MNT4BigFr, 4>::new(&params.leaf, &params.two_to_one, &leaves).unwrap();
+    assert_eq!(tree.depth(), 2);
+
+    let spend = |path, secret| {
+        let nullifier = nullifier(&params.leaf, secret, LEGACY_DOMAIN).unwrap();
+        QuaternarySpend::new(
+            params.leaf.clone(),
+            params.two_to_one.clone(),
+            tree.root(),
+            path,
+            Zeroizing::new(secret),
+            nullifier,
+        )
+        .with_strict_nullifier(false)
+        .with_height(3)
+    };
+
+    for (i, (leaf, secret)) in leaves.iter().zip(&secrets).enumerate() {
+        let path = tree.generate_proof(i).unwrap();
+        assert!(path
+            .verify::<PoseidonFamily>(&params.leaf, &params.two_to_one, &tree.root(), leaf)
+            .unwrap());
+        assert!(synthesize(spend(path, *secret)).0, "leaf {}", i);
+    }
+
+    let path = tree.generate_proof(5).unwrap();
+    assert!(!path
+        .verify::<PoseidonFamily>(&params.leaf, &params.two_to_one, &tree.root(), &leaves[6])
+        .unwrap());
+    assert!(!synthesize(spend(path, secrets[6])).0);
+}
+
+#[test]
+fn a_4_ary_spend_proves_and_verifies() {
+    let params = CircuitParams::poseidon();
+    let (leaves, secrets) = generate_leaves(16, &mut StdRng::seed_from_u64(0));
+    let tree =
+        WideMerkleTree::<MNT4BigFr, 4>::new(&params.leaf, &params.two_to_one, &leaves).unwrap();
+
+    let spend = |i: usize| {
+        let nullifier = nullifier(&params.leaf, secrets[i], LEGACY_DOMAIN).unwrap();
+        QuaternarySpend::new(
+            params.leaf.clone(),
+            params.two_to_one.clone(),
+            tree.root(),
+            tree.generate_proof(i).unwrap(),
+            Zeroizing::new(secrets[i]),
+            nullifier,
+        )
+        .with_strict_nullifier(false)
+    };
+
+    let (pk, vk) = setup(spend(0), &mut StdRng::seed_from_u64(1)).unwrap();
+    let circuit = spend(9);
+    let (root, nullifier) = (circuit.root, circuit.nullifier);
+    let proof = prove(&pk, circuit, &mut StdRng::seed_from_u64(2)).unwrap();
+
+    verify_spend(&vk, &root, &nullifier, &proof).unwrap();
+    assert!(verify_spend(&vk, &(root + MNT4BigFr::from(1u64)), &nullifier, &proof).is_err());
+}
+
+#[test]
+fn a_path_with_a_short_level_leads_nowhere() {
+    let params = CircuitParams::poseidon();
+    let (leaves, _) = generate_leaves(16, &mut StdRng::seed_from_u64(0));
+    let tree =
+        WideMerkleTree::<MNT4BigFr, 4>::new(&params.leaf, &params.two_to_one, &leaves).unwrap();
+
+    let mut path = tree.generate_proof(11).unwrap();
+    path.siblings[0].clear();
+    assert!(!path
+        .verify::<PoseidonFamily>(&params.leaf, &params.two_to_one, &tree.root(), &leaves[11])
+        .unwrap());
+
+    let mut path = tree.generate_proof(11).unwrap();
+    path.siblings[1].push(MNT4BigFr::from(0u64));
+    assert!(!path
+        .verify::<PoseidonFamily>(&params.leaf, &params.two_to_one, &tree.root(), &leaves[11])
+        .unwrap());
+}
+
+#[test]
+fn a_positional_nullifier_reads_the_index_off_the_4_ary_path() {
+    let params = CircuitParams::poseidon();
+    let (leaves, secrets) = generate_leaves(16, &mut StdRng::seed_from_u64(0));
+    let tree =
+        WideMerkleTree::<MNT4BigFr, 4>::new(&params.leaf, &params.two_to_one, &leaves).unwrap();
+
+    let spend = |index| {
+        let nullifier =
+            positional_nullifier(&params.leaf, secrets[11], index, LEGACY_DOMAIN).unwrap();
+        QuaternarySpend::new(
+            params.leaf.clone(),
+            params.two_to_one.clone(),
+            tree.root(),
+            tree.generate_proof(11).unwrap(),
+            Zeroizing::new(secrets[11]),
+            nullifier,
+        )
+        .with_strict_nullifier(false)
+        .with_positional_nullifier(true)
+    };
+
+    assert!(synthesize(spend(11)).0);
+    assert!(!synthesize(spend(14)).0);
+}
+
+#[test]
+fn a_4_ary_spend_costs_more_than_a_binary_one() {
+    let params = CircuitParams::poseidon();
+    let (leaves, secrets) = generate_leaves(16, &mut StdRng::seed_from_u64(0));
+    let nullifier = nullifier(&params.leaf, secrets[5], LEGACY_DOMAIN).unwrap();
+
+    let tree =
+        WideMerkleTree::<MNT4BigFr, 4>::new(&params.leaf, &params.two_to_one, &leaves).unwrap();
+    let (satisfied, quaternary) = synthesize(QuaternarySpend::new(
+        params.leaf.clone(),
+        params.two_to_one.clone(),
+        tree.root(),
+        tree.generate_proof(5).unwrap(),
+        Zeroizing::new(secrets[5]),
+        nullifier,
+    ));
+    assert!(satisfied);
+
+    let tree = params
+        .merkle_tree(leaves.iter().map(Vec::as_slice))
+        .unwrap();
+    let (satisfied, binary) = synthesize(params.spend_circuit::<Mnt753Cycle>(
+        tree.root(),
+        tree.generate_proof(5).unwrap(),
+        Zeroizing::new(secrets[5]),
+        nullifier,
+    ));
+    assert!(satisfied);
+
+    // Half the levels, but the rate 2 sponge takes three permutations to
+    // absorb the tag and four children, where two binary levels take two
+    assert_eq!((binary, quaternary), (9483, 10019));
+}
+
+#[test]
+fn an_inner_node_is_not_the_digest_of_its_children_as_a_leaf() {
+    let params = CircuitParams::poseidon();
+    let (leaves, _) = generate_leaves(4, &mut StdRng::seed_from_u64(1));
+    let tree =
+        WideMerkleTree::<MNT4BigFr, 4>::new(&params.leaf, &params.two_to_one, &leaves).unwrap();
+
+    let digests: Vec<_> = leaves
+        .iter()
+        .map(|leaf| CRH::evaluate(&params.leaf, leaf.as_slice()).unwrap())
+        .collect();
+    let forged = CRH::evaluate(&params.two_to_one, digests.as_slice()).unwrap();
+    assert_ne!(tree.root(), forged);
+}
+
+#[test]
+fn a_wide_tree_needs_a_power_of_its_arity_leaves() {
+    let params = CircuitParams::poseidon();
+    let (leaves, _) = generate_leaves(8, &mut StdRng::seed_from_u64(2));
+
+    assert!(matches!(
+        WideMerkleTree::<MNT4BigFr, 4>::new(&params.leaf, &params.two_to_one, &leaves),
+        Err(TreeError::UnpaddedWideLeafCount {
+            leaves: 8,
+            arity: 4
+        })
+    ));
+    assert!(matches!(
+        WideMerkleTree::<MNT4BigFr, 8>::new(&params.leaf, &params.two_to_one, &leaves)
+            .map(|tree| tree.depth()),
+        Ok(1)
+    ));
+    assert!(matches!(
+        WideMerkleTree::<MNT4BigFr, 3>::new(&params.leaf, &params.two_to_one, &leaves[..3]),
+        Err(TreeError::UnsupportedArity { arity: 3 })
+    ));
+}
@@ -0,0 +1,45 @@
+//! Proving a batch of spends with the fixture keys, in parallel and one after
+//! the other.
+#![cfg(all(feature = "native-io", feature = "parallel"))]
+
+use ark_groth16::{ProvingKey, VerifyingKey};
+use ark_mnt4_753::{Fr as MNT4BigFr, MNT4_753};
+
+use puzzle_gamma_ray::{
+    find_leaf_index, from_file, nullifier, prove_batch, prove_batch_sequential, verify_spend,
+    CircuitParams, Mnt753Cycle, Zeroizing, LEGACY_DOMAIN,
+};
+
+#[test]
+fn parallel_proofs_are_the_sequential_ones() {
+    let params = CircuitParams::poseidon();
+    let (pk, vk): (ProvingKey<MNT4_753>, VerifyingKey<MNT4_753>) =
+        from_file("./proof_keys.bin").unwrap();
+    let leaves: Vec<Vec<MNT4BigFr>> = from_file("./leaves.bin").unwrap();
+    let secret: MNT4BigFr = from_file("./leaked_secret.bin").unwrap();
+
+    let tree = params
+        .merkle_tree(leaves.iter().map(Vec::as_slice))
+        .unwrap();
+    let i = find_leaf_index(&leaves, secret).unwrap();
+    let nullifier = nullifier(&params.leaf, secret, LEGACY_DOMAIN).unwrap();
+    let circuit = params
+        .spend_circuit::<Mnt753Cycle>(
+            tree.root(),
+            tree.generate_proof(i).unwrap(),
+            Zeroizing::new(secret),
+            nullifier,
+        )
+        .with_strict_nullifier(false);
+
+    let seeds = [1, 2];
+    let circuits = vec![circuit.clone(), circuit];
+    let parallel = prove_batch(&pk, circuits.clone(), &seeds).unwrap();
+    let sequential = prove_batch_sequential(&pk, circuits, &seeds).unwrap();
+
+    assert_eq!(parallel, sequential);
+    assert_ne!(parallel[0], parallel[1]);
+    for proof in &parallel {
+        verify_spend(&vk, &tree.root(), &nullifier, proof).unwrap();
+    }
+}
@@ -0,0 +1,333 @@
+//! The `main` binary against the puzzle fixtures.
+#![cfg(feature = "cli")]
+
+use ark_mnt4_753::Fr as MNT4BigFr;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use assert_cmd::Command;
+
+use puzzle_gamma_ray::{from_file, generate_leaves, to_file};
+
+fn main_bin() -> Command {
+    Command::cargo_bin("main").unwrap()
+}
+
+/// A path in the temp directory no other test of this run writes to.
+fn temp_path(name: &str) -> String {
+    std::env::temp_dir()
+        .join(format!("gamma-ray-cli-{}-{}", std::process::id(), name))
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+#[test]
+fn prove_then_verify() {
+    let proof = temp_path("proof.bin");
+
+    main_bin()
+        .args(["prove", "--proof", &proof])
+        .assert()
+        .success();
+    let verified = main_bin().args(["verify", "--proof", &proof]).assert();
+    std::fs::remove_file(&proof).unwrap();
+
+    let output = verified.success().get_output().stdout.clone();
+    assert!(String::from_utf8(output)
+        .unwrap()
+        .contains("proof accepted"));
+}
+
+#[test]
+fn solve_checks_both_spends() {
+    let output = main_bin()
+        .args(["solve", "--check"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+
+    assert!(output.contains("the honest spend satisfies the circuit"));
+    assert!(output.contains("the double spend satisfies the circuit"));
+}
+
+#[test]
+fn missing_fixtures_are_an_error_not_a_panic() {
+    let dir = temp_path("empty");
+    std::fs::create_dir_all(&dir).unwrap();
+    let assert = main_bin().current_dir(&dir).assert();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let output = assert.code(1).get_output().stderr.clone();
+    let stderr = String::from_utf8(output).unwrap();
+    assert!(
+        stderr.starts_with("error: could not read ./leaves.bin"),
+        "{}",
+        stderr
+    );
+    assert!(!stderr.contains("panicked"));
+}
+
+#[test]
+fn timings_report_each_phase() {
+    let proof = temp_path("timed-proof.bin");
+    let stdout = |args: &[&str]| {
+        let output = main_bin()
+            .args(args)
+            .args(["--proof", &proof, "--timings"])
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        String::from_utf8(output).unwrap()
+    };
+
+    let proved = stdout(&["prove"]);
+    let verified = stdout(&["verify"]);
+    std::fs::remove_file(&proof).unwrap();
+
+    let phase = |output: &str, label: &str| {
+        output
+            .lines()
+            .any(|line| line.starts_with(label) && line.ends_with(" ms"))
+    };
+    assert!(phase(&proved, "tree"), "{}", proved);
+    assert!(phase(&proved, "proving"), "{}", proved);
+    assert!(phase(&verified, "verifying"), "{}", verified);
+}
+
+#[test]
+fn inspect_reports_the_public_inputs_of_a_saved_proof() {
+    use ark_groth16::Proof;
+    use ark_mnt4_753::{Fr as MNT4BigFr, MNT4_753};
+    use puzzle_gamma_ray::save_proof;
+
+    let proof = temp_path("inspected-proof.bin");
+    let public_inputs = [0u64, 5, 7].map(MNT4BigFr::from);
+    save_proof(&proof, &Proof::<MNT4_753>::default(), &public_inputs).unwrap();
+    let assert = main_bin().args(["inspect", "--proof", &proof]).assert();
+    std::fs::remove_file(&proof).unwrap();
+
+    let output = assert.success().get_output().stdout.clone();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("3 public inputs"), "{}", output);
+    assert!(output.contains("  [0] 0\n"), "{}", output);
+    assert!(output.contains("  [2] 7\n"), "{}", output);
+}
+
+#[test]
+fn the_secret_is_read_from_a_file_a_literal_or_stdin() {
+    use ark_ff::{BigInteger, PrimeField};
+    use ark_mnt4_753::Fr as MNT4BigFr;
+    use puzzle_gamma_ray::{from_file, to_file};
+
+    let secret: MNT4BigFr = from_file("./leaked_secret.bin").unwrap();
+    let hex = |bytes: Vec<u8>| {
+        let digits: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+        format!("0x{}", digits)
+    };
+    let literal = hex(secret.into_bigint().to_bytes_be());
+    let file = temp_path("secret.bin");
+    to_file(&file, &secret).unwrap();
+
+    let check = |secret: &str, stdin: &str| {
+        main_bin()
+            .args(["prove", "--check", "--secret", secret])
+            .write_stdin(stdin)
+            .assert()
+    };
+    let read_from_file = check(&file, "");
+    std::fs::remove_file(&file).unwrap();
+    for assert in [read_from_file, check(&literal, ""), check("-", &literal)] {
+        let output = assert.success().get_output().stdout.clone();
+        let output = String::from_utf8(output).unwrap();
+        assert!(
+            output.contains("the spend satisfies the circuit"),
+            "{}",
+            output
+        );
+    }
+
+    // The modulus would reduce to zero
+    let modulus = hex(MNT4BigFr::MODULUS.to_bytes_be());
+    for (secret, stdin) in [(modulus.as_str(), ""), ("-", modulus.as_str())] {
+        let output = check(secret, stdin).code(1).get_output().stderr.clone();
+        let stderr = String::from_utf8(output).unwrap();
+        assert!(stderr.contains("the secret is not canonical"), "{}", stderr);
+        assert!(!stderr.contains(&modulus), "{}", stderr);
+    }
+}
+
+#[test]
+fn keys_load_whichever_way_their_points_are_stored() {
+    use ark_groth16::{ProvingKey, VerifyingKey};
+    use ark_mnt4_753::MNT4_753;
+    use puzzle_gamma_ray::{from_file, save_compressed};
+
+    let proof = temp_path("key-format-proof.bin");
+    let with_header = temp_path("keys-compressed.bin");
+    let headerless = temp_path("keys-headerless.bin");
+    main_bin()
+        .args(["prove", "--proof", &proof])
+        .assert()
+        .success();
+    main_bin()
+        .args(["compress-keys", "--output", &with_header])
+        .assert()
+        .success();
+    let keys: (ProvingKey<MNT4_753>, VerifyingKey<MNT4_753>) =
+        from_file("./proof_keys.bin").unwrap();
+    save_compressed(&headerless, &keys).unwrap();
+
+    let verify = |keys: &str, key_format: &[&str]| {
+        main_bin()
+            .args(["verify", "--proof", &proof, "--keys", keys])
+            .args(key_format)
+            .assert()
+    };
+    let asserts = [
+        verify("./proof_keys.bin", &["--key-format", "uncompressed"]),
+        verify(&with_header, &[]),
+        verify(&headerless, &["--key-format", "compressed"]),
+    ];
+    let misread = verify(&headerless, &[]);
+    for path in [&proof, &with_header, &headerless] {
+        std::fs::remove_file(path).unwrap();
+    }
+
+    for assert in asserts {
+        let output = assert.success().get_output().stdout.clone();
+        assert!(String::from_utf8(output)
+            .unwrap()
+            .contains("proof accepted"));
+    }
+    let output = misread.code(1).get_output().stderr.clone();
+    let stderr = String::from_utf8(output).unwrap();
+    assert!(!stderr.contains("panicked"), "{}", stderr);
+}
+
+#[test]
+fn json_reports_whether_verification_succeeded() {
+    let proof = temp_path("json-proof.bin");
+    main_bin()
+        .args(["prove", "--proof", &proof])
+        .assert()
+        .success();
+    let verified = main_bin()
+        .args(["--json", "verify", "--proof", &proof])
+        .assert();
+    std::fs::remove_file(&proof).unwrap();
+    let missing = main_bin()
+        .args(["--json", "verify", "--proof", &proof])
+        .assert();
+
+    let report = |output: &[u8]| -> serde_json::Value { serde_json::from_slice(output).unwrap() };
+    let accepted = report(&verified.success().get_output().stdout);
+    assert_eq!(accepted["success"], true);
+    let public_inputs = accepted["public_inputs"].as_array().unwrap();
+    assert_eq!(public_inputs.len(), 1);
+    let inputs = public_inputs[0].as_array().unwrap();
+    assert_eq!(inputs.len(), 2);
+    for input in inputs {
+        let input = input.as_str().unwrap();
+        assert!(!input.is_empty() && input.bytes().all(|b| b.is_ascii_digit()));
+    }
+    assert!(accepted["timings_ms"].is_object());
+
+    let rejected = report(&missing.code(1).get_output().stdout);
+    assert_eq!(rejected["success"], false);
+    assert!(rejected["error"].as_str().unwrap().contains(&proof));
+}
+
+#[test]
+fn regenerated_fixtures_prove_verify_and_solve() {
+    let dir = temp_path("fixtures");
+    std::fs::create_dir_all(&dir).unwrap();
+    let in_dir = |args: &[&str]| main_bin().current_dir(&dir).args(args).assert();
+
+    let generated = in_dir(&["--seed", "5", "gen-fixtures", "--output-dir", "."]);
+    let proved = in_dir(&["prove", "--any-keys"]);
+    let verified = in_dir(&["verify", "--any-keys"]);
+    // The run without a subcommand solves the puzzle on them too
+    let refused = in_dir(&[]);
+    let solved = in_dir(&["--any-keys", "--json"]);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    generated.success();
+    proved.success();
+    let output = verified.success().get_output().stdout.clone();
+    assert!(String::from_utf8(output)
+        .unwrap()
+        .contains("proof accepted"));
+
+    let stderr = refused.code(1).get_output().stderr.clone();
+    assert!(String::from_utf8(stderr)
+        .unwrap()
+        .contains("was not generated for this circuit"));
+    let solved: serde_json::Value =
+        serde_json::from_slice(&solved.success().get_output().stdout).unwrap();
+    assert_eq!(solved["success"], true);
+}
+
+#[test]
+fn a_snapshot_proves_after_the_leaves_move_on() {
+    let snapshot = temp_path("snapshot-root.bin");
+    let path = temp_path("snapshot-path.bin");
+    let moved = temp_path("snapshot-leaves.bin");
+    let proof = temp_path("snapshot-proof.bin");
+
+    let snapshotted = main_bin()
+        .args(["snapshot-root", "--output", &snapshot])
+        .args(["--index", "2", "--path", &path])
+        .assert();
+    // Another leaf changes after the snapshot
+    let mut leaves: Vec<Vec<MNT4BigFr>> = from_file("./leaves.bin").unwrap();
+    leaves[0] = generate_leaves(1, &mut StdRng::seed_from_u64(1))
+        .0
+        .remove(0);
+    to_file(&moved, &leaves).unwrap();
+
+    let proved = main_bin()
+        .args(["prove", "--leaves", &moved, "--proof", &proof])
+        .args(["--root-snapshot", &snapshot, "--path", &path])
+        .assert();
+    let against_snapshot = main_bin()
+        .args(["verify", "--proof", &proof, "--root-snapshot", &snapshot])
+        .assert();
+    let against_live = main_bin()
+        .args(["verify", "--leaves", &moved, "--proof", &proof])
+        .assert();
+    for file in [&snapshot, &path, &moved, &proof] {
+        std::fs::remove_file(file).unwrap();
+    }
+
+    snapshotted.success();
+    proved.success();
+    let output = against_snapshot.success().get_output().stdout.clone();
+    assert!(String::from_utf8(output)
+        .unwrap()
+        .contains("proof accepted"));
+    against_live.code(1);
+}
+
+#[test]
+fn an_unpadded_leaf_count_is_an_error_not_a_panic() {
+    let leaves = temp_path("three-leaves.bin");
+    let (three, _) = generate_leaves(3, &mut StdRng::seed_from_u64(3));
+    to_file(&leaves, &three).unwrap();
+    let assert = main_bin()
+        .args(["prove", "--leaves", &leaves, "--any-keys"])
+        .assert();
+    std::fs::remove_file(&leaves).unwrap();
+
+    let output = assert.code(1).get_output().stderr.clone();
+    let stderr = String::from_utf8(output).unwrap();
+    assert!(
+        stderr.contains("could not build the Merkle tree"),
+        "{}",
+        stderr
+    );
+    assert!(!stderr.contains("panicked"));
+}
@@ -0,0 +1,94 @@
+//! Fixtures shared by the integration tests. Each test file only uses some of
+//! them.
+#![allow(dead_code)]
+
+use ark_mnt4_753::Fr as MNT4BigFr;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+use std::sync::OnceLock;
+
+use puzzle_gamma_ray::{
+    generate_leaves, nullifier, CircuitParams, Mnt753Cycle, MntMerkleTree, SpendCircuit, Zeroizing,
+    LEGACY_DOMAIN,
+};
+
+/// A tree of four random leaves and the secrets that own them.
+pub struct Fixture {
+    pub params: CircuitParams,
+    pub tree: MntMerkleTree,
+    pub secrets: Vec<MNT4BigFr>,
+}
+
+impl Fixture {
+    /// The fixture, built once for every test of a file.
+    pub fn get() -> &'static Self {
+        static FIXTURE: OnceLock<Fixture> = OnceLock::new();
+        FIXTURE.get_or_init(|| {
+            let params = CircuitParams::poseidon();
+            let (leaves, secrets) = generate_leaves(4, &mut StdRng::seed_from_u64(0));
+            let tree = params
+                .merkle_tree(leaves.iter().map(Vec::as_slice))
+                .unwrap();
+            Fixture {
+                params,
+                tree,
+                secrets,
+            }
+        })
+    }
+
+    /// A spend of leaf `i` by its owner, revealing `nullifier`.
+    pub fn spend(&self, i: usize, nullifier: MNT4BigFr) -> SpendCircuit {
+        self.params.spend_circuit::<Mnt753Cycle>(
+            self.tree.root(),
+            self.tree.generate_proof(i).unwrap(),
+            Zeroizing::new(self.secrets[i]),
+            nullifier,
+        )
+    }
+
+    /// The honest spend of leaf `i`.
+    pub fn honest_spend(&self, i: usize) -> SpendCircuit {
+        let nullifier = nullifier(&self.params.leaf, self.secrets[i], LEGACY_DOMAIN).unwrap();
+        self.spend(i, nullifier)
+    }
+}
+
+/// The puzzle's `leaves.bin` and `leaked_secret.bin`, and the tree of the
+/// leaves.
+#[cfg(feature = "native-io")]
+pub struct PuzzleFixture {
+    pub params: CircuitParams,
+    pub leaves: Vec<Vec<MNT4BigFr>>,
+    pub leaked_secret: MNT4BigFr,
+    pub tree: MntMerkleTree,
+}
+
+#[cfg(feature = "native-io")]
+impl PuzzleFixture {
+    pub fn load() -> Self {
+        let params = CircuitParams::poseidon();
+        let leaves: Vec<Vec<MNT4BigFr>> = puzzle_gamma_ray::from_file("./leaves.bin").unwrap();
+        let tree = params
+            .merkle_tree(leaves.iter().map(Vec::as_slice))
+            .unwrap();
+        PuzzleFixture {
+            leaked_secret: puzzle_gamma_ray::from_file("./leaked_secret.bin").unwrap(),
+            params,
+            leaves,
+            tree,
+        }
+    }
+
+    /// A spend of the leaked secret's leaf with `secret`, revealing
+    /// `nullifier`.
+    pub fn spend(&self, secret: MNT4BigFr, nullifier: MNT4BigFr) -> SpendCircuit {
+        let i = puzzle_gamma_ray::find_leaf_index(&self.leaves, self.leaked_secret).unwrap();
+        self.params.spend_circuit::<Mnt753Cycle>(
+            self.tree.root(),
+            self.tree.generate_proof(i).unwrap(),
+            Zeroizing::new(secret),
+            nullifier,
+        )
+    }
+}
@@ -0,0 +1,134 @@
+//! The spend circuit's constraint system, without Groth16.
+#![cfg(feature = "prover")]
+
+use ark_mnt4_753::Fr as MNT4BigFr;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+use puzzle_gamma_ray::{
+    check_satisfied, count_constraints, generate_leaves, nullifier, public_inputs,
+    synthesize_and_report, CircuitParams, Mnt753Cycle, Payout, SpendCircuit, Zeroizing,
+    CIRCUIT_VERSION, LEGACY_DOMAIN,
+};
+
+/// The honest spend of leaf 1 of a tree of four random leaves, the shape of
+/// the puzzle's.
+fn spend() -> SpendCircuit {
+    let params = CircuitParams::poseidon();
+    let (leaves, secrets) = generate_leaves(4, &mut StdRng::seed_from_u64(0));
+    let tree = params
+        .merkle_tree(leaves.iter().map(Vec::as_slice))
+        .unwrap();
+    let nullifier: MNT4BigFr = nullifier(&params.leaf, secrets[1], LEGACY_DOMAIN).unwrap();
+
+    params.spend_circuit::<Mnt753Cycle>(
+        tree.root(),
+        tree.generate_proof(1).unwrap(),
+        Zeroizing::new(secrets[1]),
+        nullifier,
+    )
+}
+
+/// Within 1% of `expected`, so gadget upgrades don't fail the test but an
+/// accidental blowup does.
+fn assert_about(constraints: usize, expected: usize) {
+    let tolerance = expected / 100;
+    assert!(
+        constraints.abs_diff(expected) <= tolerance,
+        "{} constraints, expected {} within {}",
+        constraints,
+        expected,
+        tolerance
+    );
+}
+
+#[test]
+fn the_constraint_count_is_stable() {
+    let strict = count_constraints(&spend()).unwrap();
+    let legacy = count_constraints(&spend().with_strict_nullifier(false)).unwrap();
+
+    assert_about(strict.constraints, 8957);
+    assert_about(legacy.constraints, 7828);
+    assert_eq!(strict.public_inputs, 2);
+    assert_eq!(legacy.public_inputs, 2);
+}
+
+#[test]
+fn the_wrapped_secret_is_the_witness() {
+    let circuit = spend();
+    let secret = *circuit.secret;
+
+    let cs = ConstraintSystem::new_ref();
+    circuit.generate_constraints(cs.clone()).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+    assert!(cs.borrow().unwrap().witness_assignment.contains(&secret));
+}
+
+#[test]
+fn check_satisfied_rejects_a_tampered_nullifier() {
+    let mut circuit = spend();
+    assert!(check_satisfied(&circuit).unwrap());
+
+    circuit.nullifier += MNT4BigFr::from(1u64);
+    assert!(!check_satisfied(&circuit).unwrap());
+}
+
+#[test]
+fn synthesize_and_report_pinpoints_a_wrong_nullifier() {
+    let mut circuit = spend();
+    assert_eq!(synthesize_and_report(&circuit).unwrap(), None);
+
+    circuit.nullifier += MNT4BigFr::from(1u64);
+    let unsatisfied = synthesize_and_report(&circuit).unwrap().unwrap();
+    assert!(unsatisfied.contains("nullifier_check"), "{}", unsatisfied);
+    assert!(!unsatisfied.contains("membership"), "{}", unsatisfied);
+}
+
+#[test]
+fn unsatisfied_constraints_are_reported_by_name() {
+    let report = |circuit: &SpendCircuit| synthesize_and_report(circuit).unwrap().unwrap();
+
+    let mut circuit = spend();
+    circuit.nullifier += MNT4BigFr::from(1u64);
+    let wrong_nullifier = report(&circuit);
+    let mut circuit = spend();
+    circuit.root += MNT4BigFr::from(1u64);
+    let wrong_root = report(&circuit);
+
+    assert!(
+        wrong_nullifier.contains("nullifier_check"),
+        "{}",
+        wrong_nullifier
+    );
+    assert!(wrong_root.contains("membership"), "{}", wrong_root);
+    for unsatisfied in [wrong_nullifier, wrong_root] {
+        assert!(!unsatisfied.contains("new_witness"), "{}", unsatisfied);
+        assert!(!unsatisfied.contains("new_digest"), "{}", unsatisfied);
+    }
+}
+
+#[test]
+fn public_inputs_are_the_allocated_inputs() {
+    let payout = Payout {
+        fee: MNT4BigFr::from(3u64),
+        recipient_hash: MNT4BigFr::from(5u64),
+    };
+    let circuits = [
+        spend(),
+        spend().with_payout(payout),
+        spend().with_version(CIRCUIT_VERSION),
+        spend().with_payout(payout).with_version(CIRCUIT_VERSION),
+    ];
+
+    for circuit in circuits {
+        let expected = public_inputs(&circuit);
+        assert_eq!(
+            expected.len(),
+            count_constraints(&circuit).unwrap().public_inputs
+        );
+
+        let cs = ConstraintSystem::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert_eq!(cs.borrow().unwrap().instance_assignment[1..], expected);
+    }
+}
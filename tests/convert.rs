@@ -0,0 +1,38 @@
+//! Conversions between the constraint field and the scalar field of the key
+//! curve, around the scalar modulus `r` where they stop being lossless.
+
+use ark_ff::{One, PrimeField, Zero};
+use ark_mnt4_753::Fr as MNT4BigFr;
+use ark_mnt6_753::Fr as MNT6BigFr;
+
+use puzzle_gamma_ray::{fr_to_scalar, fr_to_scalar_reduced, scalar_to_fr, NotRepresentable};
+
+/// `r`, which fits in the constraint field since `r < p`.
+fn scalar_modulus() -> MNT4BigFr {
+    MNT4BigFr::from_bigint(MNT6BigFr::MODULUS).unwrap()
+}
+
+#[test]
+fn values_below_the_scalar_modulus_round_trip() {
+    let largest = scalar_modulus() - MNT4BigFr::one();
+
+    for x in [MNT4BigFr::zero(), MNT4BigFr::one(), largest] {
+        let scalar = fr_to_scalar(x).unwrap();
+        assert_eq!(scalar, fr_to_scalar_reduced(x));
+        assert_eq!(scalar_to_fr(scalar), x);
+    }
+    assert_eq!(fr_to_scalar(largest).unwrap(), -MNT6BigFr::one());
+    assert_eq!(scalar_to_fr(-MNT6BigFr::one()), largest);
+}
+
+#[test]
+fn values_from_the_scalar_modulus_up_are_not_representable() {
+    let r = scalar_modulus();
+
+    for x in [r, r + MNT4BigFr::one(), -MNT4BigFr::one()] {
+        assert!(matches!(fr_to_scalar(x), Err(NotRepresentable)));
+    }
+    // Reduction wraps them around instead, aliasing values `r` apart
+    assert_eq!(fr_to_scalar_reduced(r), MNT6BigFr::zero());
+    assert_eq!(fr_to_scalar_reduced(r + MNT4BigFr::one()), MNT6BigFr::one());
+}
@@ -0,0 +1,103 @@
+//! The spend circuit instantiated over a second pair of curves: keys on
+//! Jubjub, whose base field is the BLS12-381 scalar field, proven with
+//! Groth16 over BLS12-381. Nothing here uses the MNT-753 defaults.
+#![cfg(feature = "prover")]
+
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_crypto_primitives::sponge::poseidon::{find_poseidon_ark_and_mds, PoseidonConfig};
+use ark_ec::AffineRepr;
+use ark_ed_on_bls12_381::{constraints::EdwardsVar, EdwardsAffine, Fr as JubjubFr};
+use ark_ff::PrimeField;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+use puzzle_gamma_ray::{
+    canonical_secret, check_satisfied, nullifier_with, prove, setup, verify_spend, CircuitParams,
+    CurveCycle, PoseidonFamily, PublicKeyVar, SpendCircuit, Zeroizing, LEGACY_DOMAIN,
+};
+
+#[derive(Clone)]
+struct JubjubCycle;
+
+impl CurveCycle for JubjubCycle {
+    type ConstraintF = Fr;
+    type ScalarField = JubjubFr;
+    type G1Affine = EdwardsAffine;
+    type Pairing = Bls12_381;
+
+    fn public_key_var(
+        cs: ConstraintSystemRef<Fr>,
+        secret_bits: &[Boolean<Fr>],
+    ) -> Result<PublicKeyVar<Fr>, SynthesisError> {
+        let base =
+            EdwardsVar::new_constant(ark_relations::ns!(cs, "base"), EdwardsAffine::generator())?;
+        let pk = base.scalar_mul_le(secret_bits.iter())?;
+
+        Ok((pk.x, pk.y))
+    }
+}
+
+fn poseidon_parameters() -> PoseidonConfig<Fr> {
+    let (full_rounds, partial_rounds) = (8, 57);
+    let (ark, mds) = find_poseidon_ark_and_mds::<Fr>(
+        Fr::MODULUS_BIT_SIZE as u64,
+        2,
+        full_rounds,
+        partial_rounds,
+        0,
+    );
+    PoseidonConfig::new(
+        full_rounds as usize,
+        partial_rounds as usize,
+        5,
+        mds,
+        ark,
+        2,
+        1,
+    )
+}
+
+/// A spend of the second of two leaves with `secret` as the witness, with
+/// the nullifier of `secret`.
+fn spend(owner: Fr, secret: Fr) -> SpendCircuit<JubjubCycle, PoseidonFamily> {
+    let params =
+        CircuitParams::<Fr, PoseidonFamily>::new(poseidon_parameters(), poseidon_parameters());
+    let leaves = [
+        vec![JubjubCycle::public_key(Fr::from(7u64)).x],
+        vec![JubjubCycle::public_key(owner).x],
+    ];
+    let tree = params
+        .merkle_tree(leaves.iter().map(Vec::as_slice))
+        .unwrap();
+    let nullifier =
+        nullifier_with::<_, PoseidonFamily>(&params.leaf, secret, LEGACY_DOMAIN).unwrap();
+
+    params.spend_circuit::<JubjubCycle>(
+        tree.root(),
+        tree.generate_proof(1).unwrap(),
+        Zeroizing::new(secret),
+        nullifier,
+    )
+}
+
+#[test]
+fn a_jubjub_spend_proves_and_verifies() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let secret = canonical_secret::<JubjubCycle>(Fr::from(0x1234_5678_9abc_def0u64));
+    let circuit = spend(secret, secret);
+    assert!(check_satisfied(&circuit).unwrap());
+
+    let (pk, vk) = setup(circuit.clone(), &mut rng).unwrap();
+    let (root, nullifier) = (circuit.root, circuit.nullifier);
+    let proof = prove(&pk, circuit, &mut rng).unwrap();
+    verify_spend(&vk, &root, &nullifier, &proof).unwrap();
+}
+
+#[test]
+fn a_jubjub_spend_needs_the_owner_secret() {
+    let secret = canonical_secret::<JubjubCycle>(Fr::from(0x1234_5678_9abc_def0u64));
+    let other = canonical_secret::<JubjubCycle>(Fr::from(0x0fed_cba9_8765_4321u64));
+
+    assert!(!check_satisfied(&spend(secret, other)).unwrap());
+}
@@ -0,0 +1,55 @@
+//! Pins the double spend the puzzle is about against the shipped fixtures.
+//!
+//! The leaf only commits to `pk.x`, and `secret` and `r - secret` give
+//! opposite keys with the same x, for `r` the MNT6-753 scalar modulus. As
+//! scalars they are negations of each other, but in the circuit they are
+//! MNT4-753 base field elements, and because `r` sits below the MNT4-753
+//! modulus `r - secret` is a second, distinct witness with its own nullifier.
+#![cfg(all(feature = "native-io", feature = "prover"))]
+
+use ark_groth16::{ProvingKey, VerifyingKey};
+use ark_mnt4_753::MNT4_753;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+use puzzle_gamma_ray::{
+    check_satisfied, from_file, nullifier, prove, solve, verify_spend, LEGACY_DOMAIN,
+};
+
+mod common;
+use common::PuzzleFixture;
+
+#[test]
+fn the_legacy_circuit_accepts_a_second_nullifier() {
+    let fixtures = PuzzleFixture::load();
+    let (pk, vk): (ProvingKey<MNT4_753>, VerifyingKey<MNT4_753>) =
+        from_file("./proof_keys.bin").unwrap();
+
+    let honest = nullifier(&fixtures.params.leaf, fixtures.leaked_secret, LEGACY_DOMAIN).unwrap();
+    let (secret_hack, nullifier_hack) = solve(&fixtures.leaves, fixtures.leaked_secret).unwrap();
+    assert_ne!(nullifier_hack, honest);
+
+    // proof_keys.bin was generated for the non-strict circuit, which only
+    // range checks the secret against the MNT6-753 scalar modulus
+    let circuit = fixtures
+        .spend(secret_hack, nullifier_hack)
+        .with_strict_nullifier(false);
+    let root = circuit.root;
+    let proof = prove(&pk, circuit, &mut StdRng::seed_from_u64(0)).unwrap();
+
+    verify_spend(&vk, &root, &nullifier_hack, &proof).unwrap();
+}
+
+#[test]
+fn the_strict_circuit_accepts_only_one_nullifier() {
+    let fixtures = PuzzleFixture::load();
+    let honest = nullifier(&fixtures.params.leaf, fixtures.leaked_secret, LEGACY_DOMAIN).unwrap();
+    let (secret_hack, nullifier_hack) = solve(&fixtures.leaves, fixtures.leaked_secret).unwrap();
+
+    // The strict circuit only accepts secrets up to (r - 1) / 2, so exactly
+    // one of `secret` and `r - secret` is a valid witness. The leaked secret
+    // happens to be the upper one, so it is the one rejected.
+    let leaked = check_satisfied(&fixtures.spend(fixtures.leaked_secret, honest)).unwrap();
+    let hack = check_satisfied(&fixtures.spend(secret_hack, nullifier_hack)).unwrap();
+    assert!(!leaked, "the leaked secret is in the upper half");
+    assert!(hack, "r - secret is the canonical representative");
+}
@@ -0,0 +1,104 @@
+//! A windowed fixed-base multiplication against the generic one the spend
+//! circuits use. Run with `--nocapture` to see the constraint counts.
+//!
+//! arkworks already precomputes the doublings of a constant base, so the
+//! window tables don't pay off: for the 753 bits of a secret they take 6204
+//! constraints to the 3774 of `scalar_mul_le`. The gadget stays here as the
+//! measurement rather than in the library.
+#![cfg(feature = "prover")]
+
+use ark_ec::AffineRepr;
+use ark_ff::Zero;
+use ark_mnt4_753::Fr as MNT4BigFr;
+use ark_mnt6_753::{constraints::G1Var, G1Affine, G1Projective};
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSystem, ConstraintSystemRef, SynthesisError};
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+use puzzle_gamma_ray::{public_key, sample_secret};
+
+/// Window size, in bits, of [`fixed_base_mul`].
+const WINDOW: usize = 4;
+
+/// Computes `G1Affine::generator() * secret_bits` with the multiples of the
+/// generator for each window precomputed as circuit constants. Each window
+/// then costs a constant lookup and one addition.
+fn fixed_base_mul(
+    cs: ConstraintSystemRef<MNT4BigFr>,
+    secret_bits: &[Boolean<MNT4BigFr>],
+) -> Result<G1Var, SynthesisError> {
+    let mut acc = G1Var::zero();
+    // `2^(WINDOW * i) * generator` for the current window `i`
+    let mut base = G1Projective::from(G1Affine::generator());
+
+    for window in secret_bits.chunks(WINDOW) {
+        let mut table = Vec::with_capacity(1 << window.len());
+        let mut multiple = G1Projective::zero();
+        for _ in 0..1 << window.len() {
+            table.push(G1Var::new_constant(
+                ark_relations::ns!(cs, "window_table"),
+                multiple,
+            )?);
+            multiple += base;
+        }
+
+        // The selector takes its position bits most significant first
+        let position: Vec<_> = window.iter().rev().cloned().collect();
+        acc += G1Var::conditionally_select_power_of_two_vector(&position, &table)?;
+        base = multiple;
+    }
+
+    Ok(acc)
+}
+
+fn secret_bits(cs: ConstraintSystemRef<MNT4BigFr>, secret: MNT4BigFr) -> Vec<Boolean<MNT4BigFr>> {
+    FpVar::new_witness(cs, || Ok(secret))
+        .unwrap()
+        .to_bits_le()
+        .unwrap()
+}
+
+/// The affine public key of `secret` from the fixed-base gadget or the
+/// generic one, and the constraints the multiplication alone took.
+fn multiply(secret: MNT4BigFr, fixed: bool) -> (G1Affine, usize) {
+    let cs = ConstraintSystem::new_ref();
+    let bits = secret_bits(cs.clone(), secret);
+    let before = cs.num_constraints();
+
+    let pk = if fixed {
+        fixed_base_mul(cs.clone(), &bits).unwrap()
+    } else {
+        G1Var::new_constant(cs.clone(), G1Affine::generator())
+            .unwrap()
+            .scalar_mul_le(bits.iter())
+            .unwrap()
+    };
+    let pk = pk.value().unwrap();
+
+    assert!(cs.is_satisfied().unwrap());
+    (pk.into(), cs.num_constraints() - before)
+}
+
+#[test]
+fn fixed_base_mul_agrees_with_scalar_mul_le() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let secrets = [
+        MNT4BigFr::from(1u64),
+        MNT4BigFr::from(0x1234_5678u64),
+        sample_secret(&mut rng),
+        sample_secret(&mut rng),
+    ];
+
+    for secret in secrets {
+        let (fixed, fixed_constraints) = multiply(secret, true);
+        let (generic, generic_constraints) = multiply(secret, false);
+
+        assert_eq!(fixed, generic);
+        assert_eq!(fixed, public_key(secret));
+        println!(
+            "fixed_base_mul: {} constraints, scalar_mul_le: {}",
+            fixed_constraints, generic_constraints
+        );
+    }
+}
@@ -0,0 +1,216 @@
+//! The Poseidon family against arkworks' own Poseidon tree and the fixture
+//! keys, and the Rescue family against its gadget. Run with `--nocapture`
+//! to see the constraint counts of both.
+#![cfg(all(feature = "native-io", feature = "prover"))]
+
+use ark_crypto_primitives::crh::{poseidon, CRHScheme, CRHSchemeGadget, TwoToOneCRHSchemeGadget};
+use ark_crypto_primitives::merkle_tree::constraints::PathVar;
+use ark_crypto_primitives::merkle_tree::{Config, IdentityDigestConverter, MerkleTree};
+use ark_groth16::{ProvingKey, VerifyingKey};
+use ark_mnt4_753::{Fr as MNT4BigFr, MNT4_753};
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::ConstraintSystem;
+use ark_serialize::CanonicalSerialize;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+use puzzle_gamma_ray::rescue::{
+    RescueCRH, RescueCRHGadget, RescueConfig, RescueConfigVar, RescueTwoToOneCRH,
+    RescueTwoToOneCRHGadget,
+};
+use puzzle_gamma_ray::{
+    check_satisfied, count_constraints, find_leaf_index, from_file, generate_leaves, nullifier,
+    nullifier_with, poseidon_parameters::poseidon_parameters, prove, verify_spend, CircuitParams,
+    IdentityConverter, MerkleTreeParams, MerkleTreeParamsVar, Mnt753Cycle, PoseidonFamily,
+    RescueFamily, SpendCircuit, Zeroizing, LEGACY_DOMAIN,
+};
+
+/// The tree of the original puzzle, spelled out with arkworks' types.
+struct PuzzleTree;
+
+impl Config for PuzzleTree {
+    type Leaf = [MNT4BigFr];
+
+    type LeafDigest = MNT4BigFr;
+    type LeafInnerDigestConverter = IdentityDigestConverter<MNT4BigFr>;
+    type InnerDigest = MNT4BigFr;
+
+    type LeafHash = poseidon::CRH<MNT4BigFr>;
+    type TwoToOneHash = poseidon::TwoToOneCRH<MNT4BigFr>;
+}
+
+fn compressed<T: CanonicalSerialize>(value: &T) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    value.serialize_compressed(&mut bytes).unwrap();
+    bytes
+}
+
+#[test]
+fn the_poseidon_family_is_the_puzzle_tree() {
+    let params = CircuitParams::poseidon();
+    let leaves: Vec<Vec<MNT4BigFr>> = from_file("./leaves.bin").unwrap();
+    let secret: MNT4BigFr = from_file("./leaked_secret.bin").unwrap();
+
+    let tree = params
+        .merkle_tree(leaves.iter().map(Vec::as_slice))
+        .unwrap();
+    let puzzle = MerkleTree::<PuzzleTree>::new(
+        &poseidon_parameters(),
+        &poseidon_parameters(),
+        leaves.iter().map(Vec::as_slice),
+    )
+    .unwrap();
+    assert_eq!(compressed(&tree.root()), compressed(&puzzle.root()));
+
+    let i = find_leaf_index(&leaves, secret).unwrap();
+    assert_eq!(
+        compressed(&tree.generate_proof(i).unwrap()),
+        compressed(&puzzle.generate_proof(i).unwrap())
+    );
+    assert_eq!(
+        nullifier(&params.leaf, secret, LEGACY_DOMAIN).unwrap(),
+        poseidon::CRH::evaluate(&poseidon_parameters(), [secret]).unwrap()
+    );
+}
+
+#[test]
+fn the_identity_converter_keeps_the_puzzle_root() {
+    type Identity = MerkleTreeParams<MNT4BigFr, PoseidonFamily, IdentityConverter>;
+    type IdentityVar = MerkleTreeParamsVar<MNT4BigFr, PoseidonFamily, IdentityConverter>;
+
+    let params = CircuitParams::poseidon();
+    let leaves: Vec<Vec<MNT4BigFr>> = from_file("./leaves.bin").unwrap();
+    let puzzle = MerkleTree::<PuzzleTree>::new(
+        &poseidon_parameters(),
+        &poseidon_parameters(),
+        leaves.iter().map(Vec::as_slice),
+    )
+    .unwrap();
+    let tree = MerkleTree::<Identity>::new(
+        &params.leaf,
+        &params.two_to_one,
+        leaves.iter().map(Vec::as_slice),
+    )
+    .unwrap();
+    assert_eq!(tree.root(), puzzle.root());
+
+    let cs = ConstraintSystem::<MNT4BigFr>::new_ref();
+    let root = FpVar::new_input(cs.clone(), || Ok(tree.root())).unwrap();
+    let (leaf_params, two_to_one_params) = params.allocate(cs.clone()).unwrap();
+    let leaf: Vec<_> = leaves[1]
+        .iter()
+        .map(|x| FpVar::new_witness(cs.clone(), || Ok(*x)).unwrap())
+        .collect();
+    let path = PathVar::<Identity, MNT4BigFr, IdentityVar>::new_witness(cs.clone(), || {
+        Ok(tree.generate_proof(1).unwrap())
+    })
+    .unwrap();
+    assert!(path
+        .verify_membership(&leaf_params, &two_to_one_params, &root, &leaf)
+        .unwrap()
+        .value()
+        .unwrap());
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn the_poseidon_family_proves_under_the_fixture_keys() {
+    let params = CircuitParams::poseidon();
+    let leaves: Vec<Vec<MNT4BigFr>> = from_file("./leaves.bin").unwrap();
+    let secret: MNT4BigFr = from_file("./leaked_secret.bin").unwrap();
+    let (pk, vk): (ProvingKey<MNT4_753>, VerifyingKey<MNT4_753>) =
+        from_file("./proof_keys.bin").unwrap();
+
+    let tree = params
+        .merkle_tree(leaves.iter().map(Vec::as_slice))
+        .unwrap();
+    let i = find_leaf_index(&leaves, secret).unwrap();
+    let nullifier = nullifier(&params.leaf, secret, LEGACY_DOMAIN).unwrap();
+    let circuit = params
+        .spend_circuit::<Mnt753Cycle>(
+            tree.root(),
+            tree.generate_proof(i).unwrap(),
+            Zeroizing::new(secret),
+            nullifier,
+        )
+        .with_strict_nullifier(false);
+
+    let proof = prove(&pk, circuit, &mut StdRng::seed_from_u64(0)).unwrap();
+    verify_spend(&vk, &tree.root(), &nullifier, &proof).unwrap();
+}
+
+#[test]
+fn the_rescue_gadgets_agree_with_the_native_hashes() {
+    let parameters = RescueConfig::<MNT4BigFr>::default_for_tree();
+    let inputs: Vec<MNT4BigFr> = (1..=5u64).map(MNT4BigFr::from).collect();
+
+    for len in 0..=inputs.len() {
+        let cs = ConstraintSystem::new_ref();
+        let params_var = RescueConfigVar::new_constant(cs.clone(), &parameters).unwrap();
+        let input_var = Vec::<FpVar<_>>::new_witness(cs.clone(), || Ok(&inputs[..len])).unwrap();
+        let digest = RescueCRHGadget::evaluate(&params_var, &input_var).unwrap();
+
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(
+            digest.value().unwrap(),
+            RescueCRH::evaluate(&parameters, &inputs[..len]).unwrap()
+        );
+    }
+
+    let cs = ConstraintSystem::new_ref();
+    let params_var = RescueConfigVar::new_constant(cs.clone(), &parameters).unwrap();
+    let left = FpVar::new_witness(cs.clone(), || Ok(inputs[0])).unwrap();
+    let right = FpVar::new_witness(cs.clone(), || Ok(inputs[1])).unwrap();
+    let digest = RescueTwoToOneCRHGadget::compress(&params_var, &left, &right).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+    assert_eq!(
+        digest.value().unwrap(),
+        <RescueTwoToOneCRH<_> as ark_crypto_primitives::crh::TwoToOneCRHScheme>::compress(
+            &parameters,
+            inputs[0],
+            inputs[1]
+        )
+        .unwrap()
+    );
+}
+
+#[test]
+fn a_rescue_spend_is_satisfied() {
+    let params = CircuitParams::<MNT4BigFr, RescueFamily>::new(
+        RescueConfig::default_for_tree(),
+        RescueConfig::default_for_tree(),
+    );
+    let (leaves, secrets) = generate_leaves(4, &mut StdRng::seed_from_u64(0));
+    let tree = params
+        .merkle_tree(leaves.iter().map(Vec::as_slice))
+        .unwrap();
+    let nullifier =
+        nullifier_with::<_, RescueFamily>(&params.leaf, secrets[2], LEGACY_DOMAIN).unwrap();
+    let spend = |nullifier| -> SpendCircuit<Mnt753Cycle, RescueFamily> {
+        params.spend_circuit(
+            tree.root(),
+            tree.generate_proof(2).unwrap(),
+            Zeroizing::new(secrets[2]),
+            nullifier,
+        )
+    };
+
+    assert!(check_satisfied(&spend(nullifier)).unwrap());
+    assert!(!check_satisfied(&spend(nullifier + MNT4BigFr::from(1u64))).unwrap());
+
+    let poseidon = CircuitParams::poseidon();
+    let poseidon_tree = poseidon
+        .merkle_tree(leaves.iter().map(Vec::as_slice))
+        .unwrap();
+    let poseidon_spend = poseidon.spend_circuit::<Mnt753Cycle>(
+        poseidon_tree.root(),
+        poseidon_tree.generate_proof(2).unwrap(),
+        Zeroizing::new(secrets[2]),
+        puzzle_gamma_ray::nullifier(&poseidon.leaf, secrets[2], LEGACY_DOMAIN).unwrap(),
+    );
+    println!(
+        "rescue: {}\nposeidon: {}",
+        count_constraints(&spend(nullifier)).unwrap(),
+        count_constraints(&poseidon_spend).unwrap()
+    );
+}
@@ -0,0 +1,104 @@
+//! Inserting into the incremental tree, and proving against its root window.
+#![cfg(feature = "prover")]
+
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+use puzzle_gamma_ray::tree::empty_leaf;
+use puzzle_gamma_ray::{build_tree, generate_leaves, CircuitParams, IncrementalTree, TreeError};
+
+#[test]
+fn each_insertion_matches_a_rebuilt_tree() {
+    let params = CircuitParams::poseidon();
+    let (leaves, _) = generate_leaves(4, &mut StdRng::seed_from_u64(0));
+    let mut tree = IncrementalTree::new(&params.leaf, &params.two_to_one, 2, 4).unwrap();
+
+    for (i, leaf) in leaves.iter().enumerate() {
+        assert_eq!(tree.insert(leaf).unwrap(), i);
+        let (rebuilt, _) = build_tree(&params.leaf, &params.two_to_one, &leaves[..=i], 2).unwrap();
+        assert_eq!(tree.root(), rebuilt.root());
+
+        let path = tree.generate_proof(i).unwrap();
+        assert!(path
+            .verify(
+                &params.leaf,
+                &params.two_to_one,
+                &tree.root(),
+                leaf.as_slice()
+            )
+            .unwrap());
+    }
+    assert_eq!(tree.len(), 4);
+    assert!(tree.insert(&leaves[0]).is_err());
+}
+
+#[test]
+fn paths_against_a_prior_root_verify_until_it_leaves_the_window() {
+    let params = CircuitParams::poseidon();
+    let (leaves, _) = generate_leaves(4, &mut StdRng::seed_from_u64(1));
+    let mut tree = IncrementalTree::new(&params.leaf, &params.two_to_one, 2, 2).unwrap();
+
+    tree.insert(&leaves[0]).unwrap();
+    let prior_root = tree.root();
+    let prior_path = tree.generate_proof(0).unwrap();
+
+    // The window holds the prior root and the current one
+    tree.insert(&leaves[1]).unwrap();
+    assert_ne!(tree.root(), prior_root);
+    assert_eq!(
+        tree.recent_roots().copied().collect::<Vec<_>>(),
+        vec![prior_root, tree.root()]
+    );
+    assert!(!prior_path
+        .verify(
+            &params.leaf,
+            &params.two_to_one,
+            &tree.root(),
+            leaves[0].as_slice()
+        )
+        .unwrap());
+    assert!(tree.verify_membership(&prior_path, &leaves[0]).unwrap());
+    let current_path = tree.generate_proof(0).unwrap();
+    assert!(tree.verify_membership(&current_path, &leaves[0]).unwrap());
+
+    tree.insert(&leaves[2]).unwrap();
+    assert!(!tree.verify_membership(&prior_path, &leaves[0]).unwrap());
+    assert!(tree.verify_membership(&current_path, &leaves[0]).unwrap());
+}
+
+#[test]
+fn a_tall_tree_only_stores_its_leaves() {
+    let params = CircuitParams::poseidon();
+    let (leaves, _) = generate_leaves(3, &mut StdRng::seed_from_u64(2));
+    let mut tree = IncrementalTree::new(&params.leaf, &params.two_to_one, 32, 1).unwrap();
+    assert_eq!(tree.capacity(), 1 << 32);
+
+    for (i, leaf) in leaves.iter().enumerate() {
+        tree.insert(leaf).unwrap();
+        let path = tree.generate_proof(i).unwrap();
+        assert_eq!(path.auth_path.len(), 31);
+        assert!(path
+            .verify(
+                &params.leaf,
+                &params.two_to_one,
+                &tree.root(),
+                leaf.as_slice()
+            )
+            .unwrap());
+    }
+    // An empty position far from the inserted leaves opens to the empty leaf
+    let last = tree.capacity() - 1;
+    assert!(tree
+        .generate_proof(last)
+        .unwrap()
+        .verify(
+            &params.leaf,
+            &params.two_to_one,
+            &tree.root(),
+            empty_leaf().as_slice()
+        )
+        .unwrap());
+    assert!(matches!(
+        tree.generate_proof(tree.capacity()),
+        Err(TreeError::IndexOutOfRange { .. })
+    ));
+}
@@ -0,0 +1,194 @@
+//! Reading and writing the fixture formats.
+#![cfg(all(feature = "native-io", feature = "prover"))]
+
+use ark_groth16::{Proof, ProvingKey, VerifyingKey};
+use ark_mnt4_753::{Fr as MNT4BigFr, MNT4_753};
+use ark_serialize::Compress;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+use std::sync::OnceLock;
+
+use puzzle_gamma_ray::io::{KEYS_MAGIC, KEYS_VERSION};
+use puzzle_gamma_ray::{
+    find_leaf_index, from_file, leaves_from_json, leaves_to_json, load_compressed, load_keys,
+    load_proof, nullifier, prove, save_compressed, save_keys, save_proof, to_file,
+    verify_public_inputs, CircuitParams, LoadError, Mnt753Cycle, Zeroizing, LEGACY_DOMAIN,
+};
+
+type Keys = (ProvingKey<MNT4_753>, VerifyingKey<MNT4_753>);
+
+/// A path in the temp directory no other test of this run writes to.
+fn temp_path(name: &str) -> String {
+    std::env::temp_dir()
+        .join(format!("gamma-ray-io-{}-{}", std::process::id(), name))
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+fn fixture_keys() -> &'static Keys {
+    static KEYS: OnceLock<Keys> = OnceLock::new();
+    KEYS.get_or_init(|| from_file("./proof_keys.bin").unwrap())
+}
+
+/// The honest spend of the leaked secret under the fixture keys, proven once
+/// for every test that needs a proof.
+fn fixture_proof() -> &'static (Proof<MNT4_753>, Vec<MNT4BigFr>) {
+    static PROOF: OnceLock<(Proof<MNT4_753>, Vec<MNT4BigFr>)> = OnceLock::new();
+    PROOF.get_or_init(|| {
+        let params = CircuitParams::poseidon();
+        let leaves: Vec<Vec<MNT4BigFr>> = from_file("./leaves.bin").unwrap();
+        let secret: MNT4BigFr = from_file("./leaked_secret.bin").unwrap();
+        let tree = params
+            .merkle_tree(leaves.iter().map(Vec::as_slice))
+            .unwrap();
+        let i = find_leaf_index(&leaves, secret).unwrap();
+        let nullifier = nullifier(&params.leaf, secret, LEGACY_DOMAIN).unwrap();
+        let circuit = params
+            .spend_circuit::<Mnt753Cycle>(
+                tree.root(),
+                tree.generate_proof(i).unwrap(),
+                Zeroizing::new(secret),
+                nullifier,
+            )
+            .with_strict_nullifier(false);
+
+        let proof = prove(&fixture_keys().0, circuit, &mut StdRng::seed_from_u64(0)).unwrap();
+        (proof, vec![tree.root(), nullifier])
+    })
+}
+
+#[test]
+fn a_missing_file_is_an_io_error() {
+    let path = temp_path("missing.bin");
+    assert!(matches!(
+        from_file::<MNT4BigFr>(&path),
+        Err(LoadError::Io { .. })
+    ));
+}
+
+#[test]
+fn a_truncated_file_fails_to_deserialize() {
+    let path = temp_path("truncated.bin");
+    to_file(&path, &vec![MNT4BigFr::from(1u64), MNT4BigFr::from(2u64)]).unwrap();
+    let bytes = std::fs::read(&path).unwrap();
+    std::fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+
+    let truncated = from_file::<Vec<MNT4BigFr>>(&path);
+    std::fs::write(&path, []).unwrap();
+    let empty = from_file::<Vec<MNT4BigFr>>(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(matches!(truncated, Err(LoadError::Deserialize { .. })));
+    assert!(matches!(empty, Err(LoadError::Empty { .. })));
+}
+
+#[test]
+fn a_saved_proof_verifies_under_a_saved_vk() {
+    let (proof, public_inputs) = fixture_proof();
+    let (proof_path, vk_path) = (temp_path("proof.bin"), temp_path("vk.bin"));
+    save_proof(&proof_path, proof, public_inputs).unwrap();
+    to_file(&vk_path, &fixture_keys().1).unwrap();
+
+    let loaded = load_proof::<MNT4_753>(&proof_path);
+    let vk: Result<VerifyingKey<MNT4_753>, _> = from_file(&vk_path);
+    std::fs::remove_file(&proof_path).unwrap();
+    std::fs::remove_file(&vk_path).unwrap();
+
+    let (loaded_proof, loaded_inputs) = loaded.unwrap();
+    assert_eq!(&loaded_proof, proof);
+    assert_eq!(&loaded_inputs, public_inputs);
+    verify_public_inputs(&vk.unwrap(), &loaded_inputs, &loaded_proof).unwrap();
+}
+
+#[test]
+fn compressed_files_verify_like_uncompressed_ones_and_are_smaller() {
+    let (proof, public_inputs) = fixture_proof();
+    let vk = &fixture_keys().1;
+    let loaded = |compressed: bool| {
+        let (proof_path, vk_path) = if compressed {
+            (temp_path("proof.compressed"), temp_path("vk.compressed"))
+        } else {
+            (
+                temp_path("proof.uncompressed"),
+                temp_path("vk.uncompressed"),
+            )
+        };
+        let (loaded_proof, loaded_vk): (Proof<MNT4_753>, VerifyingKey<MNT4_753>) = if compressed {
+            save_compressed(&proof_path, proof).unwrap();
+            save_compressed(&vk_path, vk).unwrap();
+            (
+                load_compressed(&proof_path).unwrap(),
+                load_compressed(&vk_path).unwrap(),
+            )
+        } else {
+            to_file(&proof_path, proof).unwrap();
+            to_file(&vk_path, vk).unwrap();
+            (
+                from_file(&proof_path).unwrap(),
+                from_file(&vk_path).unwrap(),
+            )
+        };
+        let size = |path: &str| std::fs::metadata(path).unwrap().len();
+        let sizes = (size(&proof_path), size(&vk_path));
+        std::fs::remove_file(&proof_path).unwrap();
+        std::fs::remove_file(&vk_path).unwrap();
+        (loaded_proof, loaded_vk, sizes)
+    };
+
+    let (compressed_proof, compressed_vk, compressed_sizes) = loaded(true);
+    let (uncompressed_proof, uncompressed_vk, uncompressed_sizes) = loaded(false);
+    assert_eq!(compressed_proof, uncompressed_proof);
+    assert_eq!(compressed_vk, uncompressed_vk);
+    verify_public_inputs(&compressed_vk, public_inputs, &compressed_proof).unwrap();
+    verify_public_inputs(&uncompressed_vk, public_inputs, &uncompressed_proof).unwrap();
+
+    assert!(compressed_sizes.0 < uncompressed_sizes.0);
+    assert!(compressed_sizes.1 < uncompressed_sizes.1);
+}
+
+#[test]
+fn headerless_keys_load_as_version_1() {
+    let (pk, vk) = fixture_keys();
+
+    let (keys, version) = load_keys::<MNT4_753>("./proof_keys.bin", Compress::No).unwrap();
+    assert_eq!(version, 1);
+    assert!(keys.0 == *pk && keys.1 == *vk);
+
+    let path = temp_path("keys.bin");
+    save_keys(&path, pk, vk).unwrap();
+    let (keys, version) = load_keys::<MNT4_753>(&path, Compress::No).unwrap();
+    assert_eq!(version, KEYS_VERSION);
+    assert!(keys.0 == *pk && keys.1 == *vk);
+
+    let mut future = std::fs::read(&path).unwrap();
+    future[KEYS_MAGIC.len()] = u8::MAX;
+    std::fs::write(&path, future).unwrap();
+    let result = load_keys::<MNT4_753>(&path, Compress::No);
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(
+        result,
+        Err(LoadError::UnsupportedVersion {
+            version: u8::MAX,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn leaves_round_trip_through_json() {
+    let leaves: Vec<Vec<MNT4BigFr>> = from_file("./leaves.bin").unwrap();
+    let edges = vec![vec![MNT4BigFr::from(0u64), -MNT4BigFr::from(1u64)]];
+    let (json, binary) = (temp_path("leaves.json"), temp_path("leaves.bin"));
+
+    leaves_to_json(&json, &leaves).unwrap();
+    to_file(&binary, &leaves_from_json(&json).unwrap()).unwrap();
+    let rewritten = std::fs::read(&binary).unwrap();
+    leaves_to_json(&json, &edges).unwrap();
+    let edges_from_json = leaves_from_json(&json).unwrap();
+    std::fs::remove_file(&json).unwrap();
+    std::fs::remove_file(&binary).unwrap();
+
+    assert_eq!(rewritten, std::fs::read("./leaves.bin").unwrap());
+    assert_eq!(edges_from_json, edges);
+}
@@ -0,0 +1,108 @@
+//! Leaves of several field elements, built natively and in-circuit from the
+//! same layout.
+#![cfg(feature = "prover")]
+
+use ark_mnt4_753::Fr as MNT4BigFr;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+use puzzle_gamma_ray::{
+    check_satisfied, fr_to_scalar, full_public_key_leaf, generate_leaves, nullifier, public_key,
+    scalar_to_fr, CircuitParams, LeafField, LeafLayout, LeafValues, Mnt753Cycle, SpendCircuit,
+    Zeroizing, LEGACY_DOMAIN,
+};
+
+/// The values of the note owned by `secret`, with a value and commitment
+/// that tell its leaf apart from other notes of the key.
+fn values(secret: MNT4BigFr, note: u64) -> LeafValues {
+    let pk = public_key(secret);
+    LeafValues {
+        pk_x: pk.x,
+        pk_y: pk.y,
+        value: MNT4BigFr::from(100 + note),
+        commitment: MNT4BigFr::from(200 + note),
+    }
+}
+
+/// The spend of leaf 1 of a tree of four leaves in `layout`, checking the
+/// native path first.
+fn spend(layout: &LeafLayout) -> SpendCircuit {
+    let params = CircuitParams::poseidon();
+    let (_, secrets) = generate_leaves(4, &mut StdRng::seed_from_u64(0));
+    let values: Vec<_> = (0..4).map(|i| values(secrets[i], i as u64)).collect();
+    let leaves: Vec<_> = values.iter().map(|values| layout.leaf(values)).collect();
+    let tree = params
+        .merkle_tree(leaves.iter().map(Vec::as_slice))
+        .unwrap();
+
+    let path = tree.generate_proof(1).unwrap();
+    assert!(path
+        .verify(
+            &params.leaf,
+            &params.two_to_one,
+            &tree.root(),
+            leaves[1].as_slice()
+        )
+        .unwrap());
+
+    params
+        .spend_circuit::<Mnt753Cycle>(
+            tree.root(),
+            path,
+            Zeroizing::new(secrets[1]),
+            nullifier(&params.leaf, secrets[1], LEGACY_DOMAIN).unwrap(),
+        )
+        .with_strict_nullifier(false)
+        .with_leaf_layout(layout.clone(), values[1])
+}
+
+#[test]
+fn a_single_element_leaf_is_a_member() {
+    let layout = LeafLayout::default();
+    assert_eq!(layout.fields(), [LeafField::PublicKeyX]);
+
+    assert!(check_satisfied(&spend(&layout)).unwrap());
+}
+
+#[test]
+fn a_three_element_leaf_is_a_member() {
+    let layout = LeafLayout::new(vec![
+        LeafField::PublicKeyX,
+        LeafField::Value,
+        LeafField::Commitment,
+    ])
+    .unwrap();
+    assert!(check_satisfied(&spend(&layout)).unwrap());
+
+    // Every element is hashed, so a value the leaf doesn't hold fails
+    let mut circuit = spend(&layout);
+    circuit.leaf_values.value += MNT4BigFr::from(1u64);
+    assert!(!check_satisfied(&circuit).unwrap());
+}
+
+#[test]
+fn a_two_coordinate_leaf_only_opens_to_its_own_key() {
+    let layout = LeafLayout::public_key();
+    let circuit = spend(&layout);
+    assert_eq!(
+        layout.leaf(&circuit.leaf_values),
+        full_public_key_leaf(&public_key(*circuit.secret))
+    );
+    assert!(check_satisfied(&circuit).unwrap());
+
+    // `r - secret` has the same x-coordinate, which is all an x-only leaf holds
+    let replay = |layout: &LeafLayout| {
+        let mut circuit = spend(layout);
+        let negated = scalar_to_fr(-fr_to_scalar(*circuit.secret).unwrap());
+        circuit.nullifier =
+            nullifier(&CircuitParams::poseidon().leaf, negated, LEGACY_DOMAIN).unwrap();
+        circuit.secret = Zeroizing::new(negated);
+        check_satisfied(&circuit).unwrap()
+    };
+    assert!(replay(&LeafLayout::default()));
+    assert!(!replay(&layout));
+
+    // Nor does an x-only leaf open a two-coordinate tree
+    let mut x_only = circuit;
+    x_only.leaf_layout = LeafLayout::default();
+    assert!(!check_satisfied(&x_only).unwrap());
+}
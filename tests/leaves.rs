@@ -0,0 +1,124 @@
+//! Leaves and the secrets that own them.
+#![cfg(feature = "prover")]
+
+use ark_ec::short_weierstrass::SWCurveConfig;
+use ark_ff::Field;
+use ark_mnt4_753::Fr as MNT4BigFr;
+use ark_mnt6_753::g1::Config;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::ConstraintSystem;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+use puzzle_gamma_ray::{
+    canonical_secret, find_leaf_index, generate_leaves, points_from_x, public_key,
+    secret_from_seed, CircuitParams, CurveCycle, Mnt753Cycle,
+};
+
+/// The key the spend circuit derives from `secret`, as values.
+fn public_key_in_circuit(secret: MNT4BigFr) -> (MNT4BigFr, MNT4BigFr) {
+    let cs = ConstraintSystem::new_ref();
+    let bits = FpVar::new_witness(cs.clone(), || Ok(secret))
+        .unwrap()
+        .to_bits_le()
+        .unwrap();
+    let (x, y) = Mnt753Cycle::public_key_var(cs.clone(), &bits).unwrap();
+
+    assert!(cs.is_satisfied().unwrap());
+    (x.value().unwrap(), y.value().unwrap())
+}
+
+#[test]
+fn the_native_key_is_the_leaf_the_circuit_opens() {
+    let (leaves, secrets) = generate_leaves(4, &mut StdRng::seed_from_u64(0));
+    let pk = public_key(secrets[2]);
+
+    assert_eq!(find_leaf_index(&leaves, secrets[2]), Some(2));
+    assert_eq!(leaves[2], vec![pk.x]);
+    assert_eq!(public_key_in_circuit(secrets[2]), (pk.x, pk.y));
+}
+
+#[test]
+fn generated_leaves_are_members_of_their_tree() {
+    let params = CircuitParams::poseidon();
+    let (leaves, secrets) = generate_leaves(8, &mut StdRng::seed_from_u64(1));
+    let tree = params
+        .merkle_tree(leaves.iter().map(Vec::as_slice))
+        .unwrap();
+
+    for (i, (leaf, secret)) in leaves.iter().zip(&secrets).enumerate() {
+        assert_eq!(*leaf, vec![public_key(*secret).x]);
+        let proof = tree.generate_proof(i).unwrap();
+        assert!(proof
+            .verify(
+                &params.leaf,
+                &params.two_to_one,
+                &tree.root(),
+                leaf.as_slice()
+            )
+            .unwrap());
+    }
+}
+
+#[test]
+fn find_leaf_index_finds_present_secrets_only() {
+    let (leaves, secrets) = generate_leaves(4, &mut StdRng::seed_from_u64(2));
+    let (_, others) = generate_leaves(1, &mut StdRng::seed_from_u64(3));
+
+    for (i, secret) in secrets.iter().enumerate() {
+        assert_eq!(find_leaf_index(&leaves, *secret), Some(i));
+    }
+    assert_eq!(find_leaf_index(&leaves, others[0]), None);
+    assert_eq!(find_leaf_index(&[], secrets[0]), None);
+}
+
+#[test]
+fn find_leaf_index_returns_the_first_duplicate() {
+    let (mut leaves, secrets) = generate_leaves(4, &mut StdRng::seed_from_u64(4));
+    leaves[3] = leaves[1].clone();
+
+    assert_eq!(find_leaf_index(&leaves, secrets[1]), Some(1));
+    assert_eq!(find_leaf_index(&leaves, secrets[3]), None);
+}
+
+#[test]
+fn secret_from_seed_is_stable_and_canonical() {
+    let seeds: [&[u8]; 4] = [b"", b"gamma-ray", b"gamma-raz", &[0; 64]];
+    let secrets: Vec<_> = seeds.iter().map(|seed| secret_from_seed(seed)).collect();
+
+    assert_eq!(secret_from_seed(b"gamma-ray"), secrets[1]);
+    // Pinned, since changing the derivation changes the keys of every seed
+    assert_eq!(
+        secrets[1].to_string(),
+        "11967272625115846110646191988526929384481448448006265203398528751323750165491534603136248373920565066086720976166716678512269493468202595619460282659565927364523746563572463525179135514401143028504608431146900972549948766816809"
+    );
+    for (i, secret) in secrets.iter().enumerate() {
+        assert!(secrets[i + 1..].iter().all(|other| other != secret));
+        assert_eq!(canonical_secret::<Mnt753Cycle>(*secret), *secret);
+    }
+}
+
+#[test]
+fn a_leaf_is_opened_by_a_key_and_its_negation() {
+    let (leaves, secrets) = generate_leaves(1, &mut StdRng::seed_from_u64(5));
+    let pk = public_key(secrets[0]);
+
+    let points = points_from_x(leaves[0][0]);
+    assert_eq!(points.len(), 2);
+    assert!(points.contains(&pk));
+    assert!(points.contains(&-pk));
+    assert!(points.iter().all(|point| point.is_on_curve()));
+}
+
+#[test]
+fn an_x_off_the_curve_has_no_points() {
+    // About half of all x-coordinates solve to a non-square y^2
+    let (x, y_squared) = (0u64..64)
+        .map(MNT4BigFr::from)
+        .map(|x| (x, x * x * x + Config::COEFF_A * x + Config::COEFF_B))
+        .find(|(_, y_squared)| y_squared.legendre().is_qnr())
+        .unwrap();
+
+    assert!(y_squared.sqrt().is_none());
+    assert!(points_from_x(x).is_empty());
+}
@@ -0,0 +1,51 @@
+//! Several notes spent in one proof.
+#![cfg(feature = "prover")]
+
+use ark_mnt4_753::Fr as MNT4BigFr;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+use puzzle_gamma_ray::{
+    generate_leaves, nullifier, CircuitParams, MultiSpendCircuit, LEGACY_DOMAIN,
+};
+
+/// A spend of the leaves at `indices` of a tree of four, each by its owner.
+fn multi_spend(indices: &[usize]) -> MultiSpendCircuit {
+    let params = CircuitParams::poseidon();
+    let (leaves, secrets) = generate_leaves(4, &mut StdRng::seed_from_u64(0));
+    let tree = params
+        .merkle_tree(leaves.iter().map(Vec::as_slice))
+        .unwrap();
+
+    let inputs = indices
+        .iter()
+        .map(|&i| {
+            (
+                secrets[i],
+                tree.generate_proof(i).unwrap(),
+                nullifier(&params.leaf, secrets[i], LEGACY_DOMAIN).unwrap(),
+            )
+        })
+        .collect();
+    MultiSpendCircuit::new(params.leaf, params.two_to_one, tree.root(), inputs)
+}
+
+fn satisfied(circuit: MultiSpendCircuit) -> bool {
+    let cs = ConstraintSystem::<MNT4BigFr>::new_ref();
+    circuit.generate_constraints(cs.clone()).unwrap();
+    cs.is_satisfied().unwrap()
+}
+
+#[test]
+fn one_two_and_four_inputs_are_satisfied() {
+    for indices in [&[2][..], &[0, 3], &[0, 1, 2, 3]] {
+        let circuit = multi_spend(indices);
+        assert_eq!(circuit.public_inputs().len(), 1 + indices.len());
+        assert!(satisfied(circuit), "spending {:?}", indices);
+    }
+}
+
+#[test]
+fn one_secret_cannot_be_spent_twice() {
+    assert!(!satisfied(multi_spend(&[1, 1])));
+}
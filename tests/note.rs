@@ -0,0 +1,64 @@
+//! Notes committed to by their trapdoor, as the tree's leaves.
+#![cfg(feature = "prover")]
+
+use ark_mnt4_753::Fr as MNT4BigFr;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+use puzzle_gamma_ray::{
+    check_satisfied, commitment, generate_leaves, nullifier, public_key, CircuitParams,
+    Mnt753Cycle, Note, Zeroizing, LEGACY_DOMAIN,
+};
+
+#[test]
+fn distinct_trapdoors_give_distinct_commitments() {
+    let params = CircuitParams::poseidon();
+    let mut rng = StdRng::seed_from_u64(0);
+    let pk_x = MNT4BigFr::from(7u64);
+
+    let first = Note::random(pk_x, 10, &mut rng);
+    let second = Note::random(pk_x, 10, &mut rng);
+    assert_ne!(first.rho, second.rho);
+    assert_ne!(
+        commitment(&params.leaf, &first).unwrap(),
+        commitment(&params.leaf, &second).unwrap()
+    );
+    assert_eq!(
+        commitment(&params.leaf, &first).unwrap(),
+        commitment(&params.leaf, &first).unwrap()
+    );
+}
+
+#[test]
+fn the_circuit_opens_a_committed_note() {
+    let params = CircuitParams::poseidon();
+    let mut rng = StdRng::seed_from_u64(1);
+    let (_, secrets) = generate_leaves(4, &mut rng);
+    let notes: Vec<_> = secrets
+        .iter()
+        .map(|secret| Note::random(public_key(*secret).x, 5, &mut rng))
+        .collect();
+    let leaves: Vec<_> = notes
+        .iter()
+        .map(|note| note.leaf(&params.leaf).unwrap())
+        .collect();
+    let tree = params
+        .merkle_tree(leaves.iter().map(Vec::as_slice))
+        .unwrap();
+    let spend = |note: Note| {
+        params
+            .spend_circuit::<Mnt753Cycle>(
+                tree.root(),
+                tree.generate_proof(2).unwrap(),
+                Zeroizing::new(secrets[2]),
+                nullifier(&params.leaf, secrets[2], LEGACY_DOMAIN).unwrap(),
+            )
+            .with_note(note)
+    };
+
+    assert!(check_satisfied(&spend(notes[2])).unwrap());
+    // The right key, but a trapdoor or a value the leaf doesn't commit to
+    let other_rho = Note::new(notes[2].pk_x, 5, notes[1].rho);
+    let other_value = Note::new(notes[2].pk_x, 6, notes[2].rho);
+    assert!(!check_satisfied(&spend(other_rho)).unwrap());
+    assert!(!check_satisfied(&spend(other_value)).unwrap());
+}
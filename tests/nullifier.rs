@@ -0,0 +1,243 @@
+//! How nullifiers are derived, natively and by the spend circuit.
+#![cfg(feature = "prover")]
+
+use ark_crypto_primitives::crh::CRHScheme;
+use ark_ff::PrimeField;
+use ark_mnt4_753::Fr as MNT4BigFr;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSystem, SynthesisError};
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+use puzzle_gamma_ray::{
+    check_nullifier, check_satisfied, domain_tag, enforce_all_distinct, expected_nullifiers,
+    fr_to_scalar, generate_leaves, named_domain, nullifier, nullifier_var_with_layout,
+    nullifier_with_layout, positional_nullifier, scalar_nullifier, scalar_to_fr, CircuitParams,
+    LeafH, MissingLeafIndex, Mnt753Cycle, NullifierInput, PoseidonFamily, Zeroizing, LEGACY_DOMAIN,
+};
+
+mod common;
+use common::Fixture;
+
+#[test]
+fn domains_separate_the_nullifiers_of_one_secret() {
+    let fixture = Fixture::get();
+    let (params, secret) = (&fixture.params, fixture.secrets[1]);
+    let legacy = nullifier(&params.leaf, secret, LEGACY_DOMAIN).unwrap();
+    let first = nullifier(&params.leaf, secret, 1).unwrap();
+    let second = nullifier(&params.leaf, secret, 2).unwrap();
+
+    assert_ne!(first, second);
+    assert_ne!(first, legacy);
+    assert_ne!(second, legacy);
+
+    assert!(check_satisfied(&fixture.spend(1, first).with_nullifier_domain(1)).unwrap());
+    assert!(!check_satisfied(&fixture.spend(1, second).with_nullifier_domain(1)).unwrap());
+}
+
+#[test]
+fn check_nullifier_agrees_with_the_circuit() {
+    let fixture = Fixture::get();
+    let (params, secret) = (&fixture.params, fixture.secrets[2]);
+    let honest = nullifier(&params.leaf, secret, LEGACY_DOMAIN).unwrap();
+    let claims = [
+        honest,
+        honest + MNT4BigFr::from(1u64),
+        nullifier(&params.leaf, fixture.secrets[0], LEGACY_DOMAIN).unwrap(),
+        nullifier(&params.leaf, secret, 1).unwrap(),
+    ];
+
+    for (i, claim) in claims.into_iter().enumerate() {
+        let native = check_nullifier(&params.leaf, secret, claim);
+        assert_eq!(native, i == 0);
+        assert_eq!(
+            native,
+            check_satisfied(&fixture.spend(2, claim)).unwrap(),
+            "claim {}",
+            i
+        );
+    }
+}
+
+#[test]
+fn each_expected_nullifier_is_spent_by_its_secret() {
+    let fixture = Fixture::get();
+    let nullifiers = expected_nullifiers(&fixture.params.leaf, &fixture.secrets).unwrap();
+
+    assert_eq!(nullifiers.len(), fixture.secrets.len());
+    for (i, nullifier) in nullifiers.iter().enumerate() {
+        assert!(
+            check_satisfied(&fixture.spend(i, *nullifier)).unwrap(),
+            "leaf {}",
+            i
+        );
+    }
+    assert!(!check_satisfied(&fixture.spend(0, nullifiers[1])).unwrap());
+}
+
+#[test]
+fn positional_nullifiers_separate_the_notes_of_one_key() {
+    let params = CircuitParams::poseidon();
+    let (mut leaves, secrets) = generate_leaves(4, &mut StdRng::seed_from_u64(1));
+    // A second note of the key at position 1
+    leaves[3] = leaves[1].clone();
+    let tree = params
+        .merkle_tree(leaves.iter().map(Vec::as_slice))
+        .unwrap();
+    let secret = secrets[1];
+    let spend = |path, nullifier| {
+        params
+            .spend_circuit::<Mnt753Cycle>(tree.root(), path, Zeroizing::new(secret), nullifier)
+            .with_positional_nullifier(true)
+    };
+
+    let at_1 = positional_nullifier(&params.leaf, secret, 1, LEGACY_DOMAIN).unwrap();
+    let at_3 = positional_nullifier(&params.leaf, secret, 3, LEGACY_DOMAIN).unwrap();
+    assert_ne!(at_1, at_3);
+    assert_ne!(
+        at_1,
+        nullifier(&params.leaf, secret, LEGACY_DOMAIN).unwrap()
+    );
+
+    assert!(check_satisfied(&spend(tree.generate_proof(1).unwrap(), at_1)).unwrap());
+    assert!(check_satisfied(&spend(tree.generate_proof(3).unwrap(), at_3)).unwrap());
+
+    // The nullifier of another position
+    assert!(!check_satisfied(&spend(tree.generate_proof(3).unwrap(), at_1)).unwrap());
+    // The path of position 3, claimed as position 1's
+    let mut path = tree.generate_proof(3).unwrap();
+    path.leaf_index = 1;
+    assert!(!check_satisfied(&spend(path, at_1)).unwrap());
+}
+
+#[test]
+fn only_distinct_nullifiers_pass() {
+    let distinct = |values: &[u64]| {
+        let cs = ConstraintSystem::<MNT4BigFr>::new_ref();
+        let values: Vec<_> = values
+            .iter()
+            .map(|x| FpVar::new_witness(cs.clone(), || Ok(MNT4BigFr::from(*x))).unwrap())
+            .collect();
+        enforce_all_distinct(&values).unwrap();
+        (cs.is_satisfied().unwrap(), cs.num_constraints())
+    };
+
+    // Six pairs at four constraints each
+    assert_eq!(distinct(&[1, 2, 3, 4]), (true, 24));
+    assert_eq!(distinct(&[1, 2, 3, 2]), (false, 24));
+    assert!(distinct(&[]).0 && distinct(&[5]).0);
+}
+
+#[test]
+fn domain_tags_are_distinct_and_stable() {
+    let labels = ["", "spend", "spend-v2", "transfer"];
+    let tags: Vec<_> = labels.iter().map(|label| domain_tag(label)).collect();
+
+    for (i, tag) in tags.iter().enumerate() {
+        assert!(tags[i + 1..].iter().all(|other| other != tag));
+        // The digest is far shorter than the field, so it is never reduced
+        assert_eq!(named_domain(labels[i]), tag.into_bigint().0[0].max(1));
+    }
+    // Pinned, since changing the hash changes the nullifiers of every domain
+    assert_eq!(
+        tags[1].to_string(),
+        "108940648896629702821844131637360805841818297460702187170786710437731942350444"
+    );
+}
+
+#[test]
+fn each_layout_is_the_same_natively_and_in_circuit() {
+    let fixture = Fixture::get();
+    let (params, secret, index) = (&fixture.params, fixture.secrets[1], 1);
+    let layouts = [
+        NullifierInput::Secret,
+        NullifierInput::Tagged { domain: 7 },
+        NullifierInput::Positional,
+        NullifierInput::TaggedPositional { domain: 7 },
+    ];
+
+    let cs = ConstraintSystem::<MNT4BigFr>::new_ref();
+    let (leaf_params, _) = params.allocate(cs.clone()).unwrap();
+    let witness = |x: MNT4BigFr| FpVar::new_witness(cs.clone(), || Ok(x)).unwrap();
+    let mut nullifiers = Vec::new();
+    for layout in layouts {
+        let index = layout.is_positional().then_some(index);
+        let native =
+            nullifier_with_layout::<_, PoseidonFamily>(&params.leaf, layout, secret, index)
+                .unwrap();
+        let circuit = nullifier_var_with_layout::<_, PoseidonFamily>(
+            &leaf_params,
+            layout,
+            witness(secret),
+            index.map(|i| witness(MNT4BigFr::from(i as u64))),
+        )
+        .unwrap();
+        assert_eq!(circuit.value().unwrap(), native, "{:?}", layout);
+        assert!(
+            nullifiers.iter().all(|other| *other != native),
+            "{:?}",
+            layout
+        );
+        nullifiers.push(native);
+    }
+    assert!(cs.is_satisfied().unwrap());
+
+    assert_eq!(
+        nullifiers[0],
+        nullifier(&params.leaf, secret, LEGACY_DOMAIN).unwrap()
+    );
+    // The order is part of the layout: the index after the secret, not before
+    let reversed = LeafH::evaluate(&params.leaf, [MNT4BigFr::from(index as u64), secret]).unwrap();
+    assert_ne!(reversed, nullifiers[2]);
+
+    // A positional layout can't be assembled without the index
+    let positional = NullifierInput::Positional;
+    assert_eq!(
+        positional.assemble(MNT4BigFr::from, secret, None),
+        Err(MissingLeafIndex)
+    );
+    assert!(
+        nullifier_with_layout::<_, PoseidonFamily>(&params.leaf, positional, secret, None).is_err()
+    );
+    assert!(matches!(
+        nullifier_var_with_layout::<_, PoseidonFamily>(
+            &leaf_params,
+            positional,
+            witness(secret),
+            None
+        ),
+        Err(SynthesisError::AssignmentMissing)
+    ));
+}
+
+#[test]
+fn both_keys_of_a_leaf_share_the_scalar_nullifier() {
+    let fixture = Fixture::get();
+    let params = &fixture.params.leaf;
+    let secret = fixture.secrets[1];
+    // `r - secret`, whose key has the same x-coordinate
+    let negated = scalar_to_fr(-fr_to_scalar(secret).unwrap());
+    let spend = |secret: MNT4BigFr, nullifier: MNT4BigFr, scalar: bool| {
+        let mut circuit = fixture
+            .spend(1, nullifier)
+            .with_strict_nullifier(false)
+            .with_scalar_nullifier(scalar);
+        circuit.secret = Zeroizing::new(secret);
+        check_satisfied(&circuit).unwrap()
+    };
+
+    // Hashed as field elements, the two secrets give two nullifiers
+    let legacy = nullifier(params, negated, LEGACY_DOMAIN).unwrap();
+    assert_ne!(legacy, nullifier(params, secret, LEGACY_DOMAIN).unwrap());
+    assert!(spend(negated, legacy, false));
+
+    // As scalars they are one key, with one nullifier
+    let shared = scalar_nullifier(params, secret, LEGACY_DOMAIN).unwrap();
+    assert_eq!(
+        scalar_nullifier(params, negated, LEGACY_DOMAIN).unwrap(),
+        shared
+    );
+    assert!(spend(secret, shared, true));
+    assert!(spend(negated, shared, true));
+    assert!(!spend(negated, legacy, true));
+}
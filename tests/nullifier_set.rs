@@ -0,0 +1,41 @@
+//! Remembering spent nullifiers, in memory and across a reload.
+
+use ark_mnt4_753::Fr as MNT4BigFr;
+
+use puzzle_gamma_ray::NullifierSet;
+
+#[test]
+fn a_nullifier_is_only_inserted_once() {
+    let (first, second) = (MNT4BigFr::from(1u64), -MNT4BigFr::from(1u64));
+    let mut spent = NullifierSet::new();
+    assert!(spent.is_empty());
+
+    assert!(spent.insert(&first));
+    assert!(spent.contains(&first));
+    assert!(!spent.contains(&second));
+    assert!(!spent.insert(&first));
+    assert!(spent.insert(&second));
+    assert_eq!(spent.len(), 2);
+}
+
+#[cfg(feature = "native-io")]
+#[test]
+fn a_reloaded_set_still_rejects_a_reused_nullifier() {
+    let path = std::env::temp_dir()
+        .join(format!("gamma-ray-nullifiers-{}.bin", std::process::id()))
+        .to_str()
+        .unwrap()
+        .to_string();
+    let mut spent = NullifierSet::new();
+    for i in 0..3u64 {
+        spent.insert(&MNT4BigFr::from(i));
+    }
+
+    spent.save(&path).unwrap();
+    let mut reloaded = NullifierSet::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(reloaded, spent);
+    assert!(!reloaded.insert(&MNT4BigFr::from(2u64)));
+    assert!(reloaded.insert(&MNT4BigFr::from(3u64)));
+}
@@ -0,0 +1,46 @@
+#![cfg(all(feature = "native-io", feature = "prover"))]
+
+use ark_mnt4_753::Fr as MNT4BigFr;
+use ark_std::str::FromStr;
+
+use puzzle_gamma_ray::{from_file, nullifier, poseidon_parameters};
+
+/// Known nullifiers, produced with the parameters of
+/// `poseidon_parameters::poseidon_parameters`. Any change to those parameters
+/// or to how the nullifier input is laid out changes every one of them.
+const VECTORS: &str = include_str!("nullifier_vectors.json");
+
+fn element(value: &serde_json::Value) -> MNT4BigFr {
+    let value = value.as_str().expect("field elements are decimal strings");
+    MNT4BigFr::from_str(value).unwrap_or_else(|_| panic!("{} is not a field element", value))
+}
+
+#[test]
+fn nullifiers_match_the_test_vectors() {
+    let params = poseidon_parameters::poseidon_parameters();
+    let vectors: Vec<serde_json::Value> = serde_json::from_str(VECTORS).unwrap();
+    assert!(vectors.len() >= 3);
+
+    for vector in &vectors {
+        let secret = element(&vector["secret"]);
+        let domain = vector["domain"].as_u64().unwrap();
+        assert_eq!(
+            nullifier(&params, secret, domain).unwrap(),
+            element(&vector["nullifier"]),
+            "the nullifier of {} under domain {} changed: were the Poseidon parameters or the \
+             nullifier derivation modified?",
+            secret,
+            domain
+        );
+    }
+}
+
+#[test]
+fn the_leaked_secret_has_a_test_vector() {
+    let leaked: MNT4BigFr = from_file("./leaked_secret.bin").unwrap();
+    let vectors: Vec<serde_json::Value> = serde_json::from_str(VECTORS).unwrap();
+
+    assert!(vectors
+        .iter()
+        .any(|vector| element(&vector["secret"]) == leaked));
+}
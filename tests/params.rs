@@ -0,0 +1,70 @@
+//! The tree and the circuit hash with the same parameters.
+#![cfg(feature = "prover")]
+
+use ark_crypto_primitives::crh::{
+    CRHScheme, CRHSchemeGadget, TwoToOneCRHScheme, TwoToOneCRHSchemeGadget,
+};
+use ark_mnt4_753::Fr as MNT4BigFr;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::ConstraintSystem;
+
+use puzzle_gamma_ray::poseidon_parameters::poseidon_parameters;
+use puzzle_gamma_ray::{CircuitParams, CompressH, CompressHG, LeafH, LeafHG};
+
+#[test]
+fn native_and_circuit_hashes_agree() {
+    let params = CircuitParams::poseidon();
+    let expected = poseidon_parameters();
+    // `PoseidonConfig` doesn't implement `PartialEq`
+    for parameters in [&params.leaf, &params.two_to_one] {
+        assert_eq!(
+            (&parameters.ark, &parameters.mds),
+            (&expected.ark, &expected.mds)
+        );
+        assert_eq!(
+            (
+                parameters.full_rounds,
+                parameters.partial_rounds,
+                parameters.alpha
+            ),
+            (
+                expected.full_rounds,
+                expected.partial_rounds,
+                expected.alpha
+            )
+        );
+        assert_eq!(
+            (parameters.rate, parameters.capacity),
+            (expected.rate, expected.capacity)
+        );
+    }
+
+    let leaf = [MNT4BigFr::from(3u64), -MNT4BigFr::from(1u64)];
+    let (left, right) = (MNT4BigFr::from(5u64), MNT4BigFr::from(7u64));
+    let native_leaf = LeafH::evaluate(&params.leaf, leaf.as_slice()).unwrap();
+    let native_node = CompressH::compress(&params.two_to_one, left, right).unwrap();
+
+    let cs = ConstraintSystem::new_ref();
+    let (leaf_params, two_to_one_params) = params.allocate(cs.clone()).unwrap();
+    let witness = |x: MNT4BigFr| FpVar::new_witness(cs.clone(), || Ok(x)).unwrap();
+    let leaf_var: Vec<_> = leaf.iter().map(|x| witness(*x)).collect();
+    let circuit_leaf = LeafHG::evaluate(&leaf_params, &leaf_var).unwrap();
+    let circuit_node =
+        CompressHG::compress(&two_to_one_params, &witness(left), &witness(right)).unwrap();
+
+    assert!(cs.is_satisfied().unwrap());
+    assert_eq!(circuit_leaf.value().unwrap(), native_leaf);
+    assert_eq!(circuit_node.value().unwrap(), native_node);
+}
+
+#[test]
+fn allocating_the_parameters_takes_no_variables() {
+    let cs = ConstraintSystem::<MNT4BigFr>::new_ref();
+    CircuitParams::poseidon().allocate(cs.clone()).unwrap();
+
+    assert_eq!(cs.num_constraints(), 0);
+    assert_eq!(cs.num_witness_variables(), 0);
+    // Only the constant one
+    assert_eq!(cs.num_instance_variables(), 1);
+}
@@ -0,0 +1,53 @@
+//! A spend bound to a fee and recipient can't be redirected.
+#![cfg(feature = "prover")]
+
+use ark_mnt4_753::Fr as MNT4BigFr;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+use puzzle_gamma_ray::{
+    generate_leaves, nullifier, prove, setup, verify_public_inputs, CircuitParams, Mnt753Cycle,
+    Payout, SpendCircuit, VerifyError, Zeroizing, LEGACY_DOMAIN,
+};
+
+fn spend(payout: Payout<MNT4BigFr>) -> SpendCircuit {
+    let params = CircuitParams::poseidon();
+    let (leaves, secrets) = generate_leaves(2, &mut StdRng::seed_from_u64(0));
+    let tree = params
+        .merkle_tree(leaves.iter().map(Vec::as_slice))
+        .unwrap();
+
+    params
+        .spend_circuit::<Mnt753Cycle>(
+            tree.root(),
+            tree.generate_proof(0).unwrap(),
+            Zeroizing::new(secrets[0]),
+            nullifier(&params.leaf, secrets[0], LEGACY_DOMAIN).unwrap(),
+        )
+        .with_payout(payout)
+}
+
+#[test]
+fn a_proof_for_one_recipient_is_rejected_for_another() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let payout = Payout {
+        fee: MNT4BigFr::from(3u64),
+        recipient_hash: MNT4BigFr::from(5u64),
+    };
+    let circuit = spend(payout);
+    let (pk, vk) = setup(circuit.clone(), &mut rng).unwrap();
+    let public_inputs = circuit.public_inputs();
+    let proof = prove(&pk, circuit, &mut rng).unwrap();
+
+    verify_public_inputs(&vk, &public_inputs, &proof).unwrap();
+
+    let redirected = spend(Payout {
+        recipient_hash: MNT4BigFr::from(6u64),
+        ..payout
+    })
+    .public_inputs();
+    assert_eq!(redirected.len(), public_inputs.len());
+    assert!(matches!(
+        verify_public_inputs(&vk, &redirected, &proof),
+        Err(VerifyError::Rejected)
+    ));
+}
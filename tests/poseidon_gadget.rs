@@ -0,0 +1,72 @@
+#![cfg(feature = "prover")]
+
+use ark_crypto_primitives::crh::{CRHScheme, CRHSchemeGadget};
+use ark_mnt4_753::Fr as MNT4BigFr;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::ConstraintSystem;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use ark_std::UniformRand;
+
+use proptest::prelude::*;
+
+use puzzle_gamma_ray::poseidon_parameters::{self, RATE};
+use puzzle_gamma_ray::{LeafH, LeafHG};
+
+/// Hashes `input` natively and in-circuit, returning both digests and
+/// whether the circuit's constraints hold.
+fn hash_both_ways(input: &[MNT4BigFr]) -> (MNT4BigFr, MNT4BigFr, bool) {
+    let params = poseidon_parameters::poseidon_parameters();
+    let native = LeafH::evaluate(&params, input).unwrap();
+
+    let cs = ConstraintSystem::new_ref();
+    let params_var = <LeafHG as CRHSchemeGadget<LeafH, MNT4BigFr>>::ParametersVar::new_constant(
+        cs.clone(),
+        &params,
+    )
+    .unwrap();
+    let input_var = input
+        .iter()
+        .map(|x| FpVar::new_witness(cs.clone(), || Ok(*x)))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let in_circuit = LeafHG::evaluate(&params_var, &input_var)
+        .unwrap()
+        .value()
+        .unwrap();
+
+    (native, in_circuit, cs.is_satisfied().unwrap())
+}
+
+fn elements() -> impl Strategy<Value = MNT4BigFr> {
+    prop_oneof![
+        any::<u64>().prop_map(|seed| MNT4BigFr::rand(&mut StdRng::seed_from_u64(seed))),
+        any::<u64>().prop_map(MNT4BigFr::from),
+        // The top of the field, where a reduction slip would show
+        any::<u64>().prop_map(|delta| -MNT4BigFr::from(delta)),
+    ]
+}
+
+proptest! {
+    // Each case allocates and hashes a few inputs in a fresh constraint system
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    // One to three elements cover an input below, at and past the sponge's
+    // rate, so the last crosses into a second permutation
+    #[test]
+    fn native_and_circuit_hashes_agree(input in prop::collection::vec(elements(), 1..=RATE + 1)) {
+        let (native, in_circuit, satisfied) = hash_both_ways(&input);
+        prop_assert_eq!(native, in_circuit, "the hashes of {:?} diverge", input);
+        prop_assert!(satisfied);
+    }
+}
+
+#[test]
+fn zero_inputs_of_each_length_agree() {
+    for len in 1..=RATE + 1 {
+        let input = vec![MNT4BigFr::from(0u64); len];
+        let (native, in_circuit, satisfied) = hash_both_ways(&input);
+        assert_eq!(native, in_circuit);
+        assert!(satisfied);
+    }
+}
@@ -0,0 +1,121 @@
+//! Generated Poseidon parameters, and the checks on the puzzle's static table.
+#![cfg(feature = "prover")]
+
+use ark_crypto_primitives::crh::{poseidon::CRH, CRHScheme};
+use ark_crypto_primitives::sponge::poseidon::PoseidonSponge;
+use ark_crypto_primitives::sponge::CryptographicSponge;
+use ark_mnt4_753::Fr as MNT4BigFr;
+
+use puzzle_gamma_ray::poseidon_parameters::{
+    generate_poseidon_parameters, parameters_for_arity, poseidon_parameters, validate_parameters,
+    PoseidonParamsError, CAPACITY, RATE, WIDTH,
+};
+
+#[test]
+fn generated_parameters_have_the_requested_shape() {
+    for (rate, full_rounds, partial_rounds) in [(1, 8, 31), (2, 8, 29), (4, 8, 60)] {
+        let params = generate_poseidon_parameters(rate, full_rounds, partial_rounds, 17).unwrap();
+
+        assert_eq!((params.rate, params.capacity), (rate, 1));
+        assert_eq!(params.ark.len(), full_rounds + partial_rounds);
+        assert!(params.ark.iter().all(|row| row.len() == rate + 1));
+        assert_eq!(params.mds.len(), rate + 1);
+        assert!(params.mds.iter().all(|row| row.len() == rate + 1));
+    }
+    assert_eq!(
+        generate_poseidon_parameters(0, 8, 29, 17).unwrap_err(),
+        PoseidonParamsError::ZeroRate
+    );
+}
+
+#[test]
+fn generation_is_deterministic() {
+    let params = generate_poseidon_parameters(2, 8, 29, 17).unwrap();
+    let again = generate_poseidon_parameters(2, 8, 29, 17).unwrap();
+    assert_eq!((&params.ark, &params.mds), (&again.ark, &again.mds));
+
+    let input = [MNT4BigFr::from(1u64), MNT4BigFr::from(2u64)];
+    assert_eq!(
+        CRH::evaluate(&params, input.as_slice()).unwrap(),
+        CRH::evaluate(&again, input.as_slice()).unwrap()
+    );
+
+    // Other rounds draw other constants from the LFSR
+    let other = generate_poseidon_parameters(2, 8, 31, 17).unwrap();
+    assert_ne!(params.ark[0], other.ark[0]);
+}
+
+#[test]
+fn the_static_table_hashes_to_the_reference_digest() {
+    assert_eq!(validate_parameters(&poseidon_parameters()), Ok(()));
+}
+
+#[test]
+fn an_edited_table_is_rejected() {
+    let mut truncated = poseidon_parameters();
+    truncated.ark.pop();
+    assert!(matches!(
+        validate_parameters(&truncated),
+        Err(PoseidonParamsError::ArkShape { .. })
+    ));
+
+    let mut edited = poseidon_parameters();
+    edited.ark[0][0] += MNT4BigFr::from(1u64);
+    assert_eq!(
+        validate_parameters(&edited),
+        Err(PoseidonParamsError::ReferenceMismatch)
+    );
+
+    // The state is as wide as the rate and capacity together
+    let mut wider = poseidon_parameters();
+    wider.capacity += 1;
+    assert_eq!(
+        validate_parameters(&wider),
+        Err(PoseidonParamsError::ArkShape {
+            expected: (wider.ark.len(), WIDTH + 1)
+        })
+    );
+}
+
+#[test]
+fn the_table_has_the_declared_shape() {
+    let params = poseidon_parameters();
+    assert_eq!((params.rate, params.capacity), (RATE, CAPACITY));
+    assert_eq!(params.mds.len(), WIDTH);
+    assert!(params.ark.iter().all(|row| row.len() == WIDTH));
+
+    assert!(parameters_for_arity(RATE).is_ok());
+    assert_eq!(
+        parameters_for_arity(RATE + 1).unwrap_err(),
+        PoseidonParamsError::ArityExceedsRate {
+            arity: RATE + 1,
+            rate: RATE
+        }
+    );
+}
+
+#[test]
+fn inputs_past_the_rate_are_absorbed_over_several_permutations() {
+    let params = poseidon_parameters();
+    let input: Vec<_> = (1..=2 * RATE as u64 + 1).map(MNT4BigFr::from).collect();
+
+    // Absorbing one element at a time permutes whenever the rate fills up,
+    // which is what the hash does with the whole input
+    let mut sponge = PoseidonSponge::new(&params);
+    for element in &input {
+        sponge.absorb(element);
+    }
+    let squeezed: Vec<MNT4BigFr> = sponge.squeeze_field_elements(1);
+    assert_eq!(
+        CRH::evaluate(&params, input.as_slice()).unwrap(),
+        squeezed[0]
+    );
+
+    // Every element past the rate still changes the digest
+    for len in RATE..input.len() {
+        assert_ne!(
+            CRH::evaluate(&params, &input[..len]).unwrap(),
+            CRH::evaluate(&params, &input[..len + 1]).unwrap()
+        );
+    }
+}
@@ -0,0 +1,120 @@
+//! A prover holding only its path proves the same spend as one holding the
+//! whole fixture tree, including against a root the tree has since moved on
+//! from.
+#![cfg(all(feature = "native-io", feature = "prover"))]
+
+use ark_crypto_primitives::merkle_tree::Path;
+use ark_groth16::{ProvingKey, VerifyingKey};
+use ark_mnt4_753::{Fr as MNT4BigFr, MNT4_753};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+use puzzle_gamma_ray::{
+    find_leaf_index, from_file, generate_leaves, load_root_snapshot, nullifier, prove,
+    prove_from_path, save_root_snapshot, tree_depth, verify_spend, CircuitParams, Mnt753Cycle,
+    MntMerkleTreeParams, VerifyError, Zeroizing, LEGACY_DOMAIN,
+};
+
+#[test]
+fn a_standalone_path_proves_like_the_full_tree() {
+    let params = CircuitParams::poseidon();
+    let leaves: Vec<Vec<MNT4BigFr>> = from_file("./leaves.bin").unwrap();
+    let secret: MNT4BigFr = from_file("./leaked_secret.bin").unwrap();
+    let (pk, vk): (ProvingKey<MNT4_753>, VerifyingKey<MNT4_753>) =
+        from_file("./proof_keys.bin").unwrap();
+
+    let tree = params
+        .merkle_tree(leaves.iter().map(Vec::as_slice))
+        .unwrap();
+    let i = find_leaf_index(&leaves, secret).unwrap();
+    let honest = nullifier(&params.leaf, secret, LEGACY_DOMAIN).unwrap();
+    let height = tree_depth(leaves.len());
+
+    // The full-tree prover, against the keys of the non-strict circuit
+    let circuit = params
+        .spend_circuit::<Mnt753Cycle>(
+            tree.root(),
+            tree.generate_proof(i).unwrap(),
+            Zeroizing::new(secret),
+            honest,
+        )
+        .with_strict_nullifier(false)
+        .with_height(height);
+    let full = prove(&pk, circuit, &mut StdRng::seed_from_u64(0)).unwrap();
+
+    // The standalone prover only ever sees the serialized path and the root
+    let mut bytes = Vec::new();
+    tree.generate_proof(i)
+        .unwrap()
+        .serialize_compressed(&mut bytes)
+        .unwrap();
+    let root = tree.root();
+    drop(tree);
+    let path = Path::<MntMerkleTreeParams>::deserialize_compressed(bytes.as_slice()).unwrap();
+
+    let (standalone, nullifier) = prove_from_path::<Mnt753Cycle, _, _>(
+        &pk,
+        root,
+        path,
+        Zeroizing::new(secret),
+        &params,
+        false,
+        Some(height),
+        &mut StdRng::seed_from_u64(0),
+    )
+    .unwrap();
+
+    assert_eq!(nullifier, honest);
+    assert_eq!(standalone, full, "the same witness and seed prove the same");
+    verify_spend(&vk, &root, &nullifier, &standalone).unwrap();
+}
+
+#[test]
+fn a_path_proves_against_the_snapshot_it_was_taken_with() {
+    let params = CircuitParams::poseidon();
+    let mut leaves: Vec<Vec<MNT4BigFr>> = from_file("./leaves.bin").unwrap();
+    let secret: MNT4BigFr = from_file("./leaked_secret.bin").unwrap();
+    let (pk, vk): (ProvingKey<MNT4_753>, VerifyingKey<MNT4_753>) =
+        from_file("./proof_keys.bin").unwrap();
+    let i = find_leaf_index(&leaves, secret).unwrap();
+
+    let tree = params
+        .merkle_tree(leaves.iter().map(Vec::as_slice))
+        .unwrap();
+    let snapshot = std::env::temp_dir()
+        .join(format!("gamma-ray-path-{}-root.bin", std::process::id()))
+        .to_str()
+        .unwrap()
+        .to_string();
+    save_root_snapshot(&snapshot, &tree.root()).unwrap();
+    let path = tree.generate_proof(i).unwrap();
+
+    // Another leaf changes after the snapshot
+    let other = (i + 1) % leaves.len();
+    leaves[other] = generate_leaves(1, &mut StdRng::seed_from_u64(1)).0[0].clone();
+    let live = params
+        .merkle_tree(leaves.iter().map(Vec::as_slice))
+        .unwrap()
+        .root();
+    let root = load_root_snapshot(&snapshot).unwrap();
+    std::fs::remove_file(&snapshot).unwrap();
+    assert_ne!(root, live);
+
+    let (proof, nullifier) = prove_from_path::<Mnt753Cycle, _, _>(
+        &pk,
+        root,
+        path,
+        Zeroizing::new(secret),
+        &params,
+        false,
+        Some(tree_depth(leaves.len())),
+        &mut StdRng::seed_from_u64(0),
+    )
+    .unwrap();
+
+    verify_spend(&vk, &root, &nullifier, &proof).unwrap();
+    assert!(matches!(
+        verify_spend(&vk, &live, &nullifier, &proof),
+        Err(VerifyError::Rejected)
+    ));
+}
@@ -0,0 +1,89 @@
+//! Public inputs are rejected unless they are canonical field elements,
+//! whether they are parsed from decimal or built from raw limbs.
+#![cfg(feature = "prover")]
+
+use ark_crypto_primitives::snark::SNARK;
+use ark_ff::PrimeField;
+use ark_groth16::Groth16;
+use ark_mnt4_753::{Fr as MNT4BigFr, MNT4_753};
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+use puzzle_gamma_ray::{
+    build_tree, generate_leaves, nullifier, prove, public_inputs_from_strings,
+    public_inputs_to_strings, setup, validate_public_inputs, CircuitParams, Mnt753Cycle,
+    VerifyError, Zeroizing, LEGACY_DOMAIN,
+};
+
+/// `p` as the raw limbs of an element, which stands for zero but isn't
+/// reduced.
+fn crafted() -> MNT4BigFr {
+    MNT4BigFr::new_unchecked(MNT4BigFr::MODULUS)
+}
+
+#[test]
+fn an_unreduced_input_is_rejected() {
+    let inputs = [MNT4BigFr::from(1u64), crafted()];
+    assert!(matches!(
+        validate_public_inputs(&inputs),
+        Err(VerifyError::NonCanonicalInput { index: 1 })
+    ));
+    assert!(validate_public_inputs(&[MNT4BigFr::from(1u64), MNT4BigFr::from(0u64)]).is_ok());
+}
+
+#[test]
+fn decimal_strings_round_trip() {
+    let inputs = [
+        MNT4BigFr::from(0u64),
+        MNT4BigFr::from(1u64),
+        -MNT4BigFr::from(1u64),
+        MNT4BigFr::from(u64::MAX),
+    ];
+    let strings = public_inputs_to_strings(&inputs);
+
+    assert_eq!(strings[1], "1");
+    assert_eq!(public_inputs_from_strings(&strings).unwrap(), inputs);
+    // The modulus itself would reduce to zero
+    let modulus = [MNT4BigFr::MODULUS.to_string()];
+    assert_eq!(public_inputs_from_strings(&modulus).unwrap_err().index, 0);
+}
+
+#[test]
+fn parsed_strings_verify() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let params = CircuitParams::poseidon();
+    let (leaves, secrets) = generate_leaves(2, &mut rng);
+    let (tree, _) = build_tree(&params.leaf, &params.two_to_one, &leaves, 1).unwrap();
+    let nullifier = nullifier(&params.leaf, secrets[1], LEGACY_DOMAIN).unwrap();
+    let circuit = params.spend_circuit::<Mnt753Cycle>(
+        tree.root(),
+        tree.generate_proof(1).unwrap(),
+        Zeroizing::new(secrets[1]),
+        nullifier,
+    );
+    let (pk, vk) = setup(circuit.clone(), &mut rng).unwrap();
+    let public_inputs = circuit.public_inputs();
+    let proof = prove(&pk, circuit, &mut rng).unwrap();
+
+    let parsed = public_inputs_from_strings(&public_inputs_to_strings(&public_inputs)).unwrap();
+    assert!(Groth16::<MNT4_753>::verify(&vk, &parsed, &proof).unwrap());
+    let mut swapped = parsed;
+    swapped.swap(0, 1);
+    assert!(!Groth16::<MNT4_753>::verify(&vk, &swapped, &proof).unwrap());
+}
+
+#[cfg(feature = "native-io")]
+#[test]
+fn verification_rejects_an_unreduced_input_before_verifying() {
+    use ark_groth16::{Proof, ProvingKey, VerifyingKey};
+    use ark_mnt4_753::MNT4_753;
+    use puzzle_gamma_ray::{from_file, verify_public_inputs};
+
+    let (_, vk): (ProvingKey<MNT4_753>, VerifyingKey<MNT4_753>) =
+        from_file("./proof_keys.bin").unwrap();
+
+    let result = verify_public_inputs(&vk, &[crafted(), MNT4BigFr::from(0u64)], &Proof::default());
+    assert!(matches!(
+        result,
+        Err(VerifyError::NonCanonicalInput { index: 0 })
+    ));
+}
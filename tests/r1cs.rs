@@ -0,0 +1,83 @@
+//! Dumping the spend circuit's R1CS and checking assignments against the
+//! reloaded matrices.
+#![cfg(all(feature = "native-io", feature = "prover"))]
+
+use ark_mnt4_753::Fr as MNT4BigFr;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+use puzzle_gamma_ray::{
+    constraint_matrices, generate_leaves, load_r1cs, matrices_satisfied, nullifier, save_r1cs,
+    CircuitParams, Mnt753Cycle, SpendCircuit, Zeroizing, LEGACY_DOMAIN,
+};
+
+fn spend() -> SpendCircuit {
+    let params = CircuitParams::poseidon();
+    let (leaves, secrets) = generate_leaves(4, &mut StdRng::seed_from_u64(0));
+    let tree = params
+        .merkle_tree(leaves.iter().map(Vec::as_slice))
+        .unwrap();
+
+    params
+        .spend_circuit::<Mnt753Cycle>(
+            tree.root(),
+            tree.generate_proof(1).unwrap(),
+            Zeroizing::new(secrets[1]),
+            nullifier(&params.leaf, secrets[1], LEGACY_DOMAIN).unwrap(),
+        )
+        .with_strict_nullifier(false)
+}
+
+/// Whether `circuit` is satisfied, and its instance and witness assignments.
+fn synthesize(circuit: SpendCircuit) -> (bool, Vec<MNT4BigFr>, Vec<MNT4BigFr>) {
+    let cs = ConstraintSystem::new_ref();
+    circuit.generate_constraints(cs.clone()).unwrap();
+    cs.finalize();
+
+    let satisfied = cs.is_satisfied().unwrap();
+    let cs = cs.borrow().unwrap();
+    (
+        satisfied,
+        cs.instance_assignment.clone(),
+        cs.witness_assignment.clone(),
+    )
+}
+
+#[test]
+fn a_dumped_r1cs_reloads_and_agrees_with_is_satisfied() {
+    let matrices = constraint_matrices(&spend()).unwrap();
+    let path = std::env::temp_dir()
+        .join(format!("gamma-ray-r1cs-{}-spend.r1cs", std::process::id()))
+        .to_str()
+        .unwrap()
+        .to_string();
+    save_r1cs(&path, &matrices).unwrap();
+    let reloaded = load_r1cs(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    let reloaded = reloaded.unwrap();
+    assert_eq!(
+        (
+            reloaded.num_instance_variables,
+            reloaded.num_witness_variables,
+            reloaded.num_constraints
+        ),
+        (
+            matrices.num_instance_variables,
+            matrices.num_witness_variables,
+            matrices.num_constraints
+        )
+    );
+    assert_eq!(
+        (&reloaded.a, &reloaded.b, &reloaded.c),
+        (&matrices.a, &matrices.b, &matrices.c)
+    );
+
+    let mut tampered = spend();
+    tampered.nullifier += MNT4BigFr::from(1u64);
+    for (circuit, expected) in [(spend(), true), (tampered, false)] {
+        let (satisfied, instance, witness) = synthesize(circuit);
+        assert_eq!(satisfied, expected);
+        assert_eq!(matrices_satisfied(&reloaded, &instance, &witness), expected);
+    }
+}
@@ -0,0 +1,65 @@
+//! The range check at the edges of a few bit widths, and the canonical
+//! encoding check at the modulus.
+#![cfg(feature = "prover")]
+
+use ark_ff::{BigInteger, Field, PrimeField};
+use ark_mnt4_753::Fr as MNT4BigFr;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSystem, SynthesisError};
+
+use puzzle_gamma_ray::{enforce_canonical, enforce_in_range};
+
+fn in_range(value: MNT4BigFr, bits: usize) -> Result<bool, SynthesisError> {
+    let cs = ConstraintSystem::new_ref();
+    let value = FpVar::new_witness(cs.clone(), || Ok(value))?;
+    enforce_in_range(&value, bits)?;
+    cs.is_satisfied()
+}
+
+#[test]
+fn the_largest_value_of_a_width_passes_and_the_next_fails() {
+    for bits in [1, 8, 64, 128] {
+        let bound = MNT4BigFr::from(2u64).pow([bits as u64]);
+        let largest = bound - MNT4BigFr::from(1u64);
+
+        assert!(in_range(MNT4BigFr::from(0u64), bits).unwrap());
+        assert!(in_range(largest, bits).unwrap(), "2^{} - 1", bits);
+        assert!(!in_range(bound, bits).unwrap(), "2^{}", bits);
+    }
+}
+
+#[test]
+fn widths_the_field_cant_bound_are_misuse() {
+    let bits = MNT4BigFr::MODULUS_BIT_SIZE as usize;
+    assert!(matches!(
+        in_range(MNT4BigFr::from(1u64), bits),
+        Err(SynthesisError::Unsatisfiable)
+    ));
+    // The widest bound still excludes the top of the field
+    assert!(!in_range(-MNT4BigFr::from(1u64), bits - 1).unwrap());
+}
+
+#[test]
+fn only_bits_below_the_modulus_are_canonical() {
+    let canonical = |bits: Vec<bool>| {
+        let cs = ConstraintSystem::<MNT4BigFr>::new_ref();
+        let bits: Vec<_> = bits
+            .iter()
+            .map(|bit| Boolean::new_witness(cs.clone(), || Ok(*bit)).unwrap())
+            .collect();
+        enforce_canonical(&bits).unwrap();
+        cs.is_satisfied().unwrap()
+    };
+    let width = MNT4BigFr::MODULUS_BIT_SIZE as usize;
+    let bits_of = |value: <MNT4BigFr as PrimeField>::BigInt| value.to_bits_le()[..width].to_vec();
+
+    let mut modulus_plus_one = MNT4BigFr::MODULUS;
+    modulus_plus_one.add_with_carry(&1u64.into());
+
+    assert!(canonical(vec![false; width]));
+    assert!(canonical(bits_of((-MNT4BigFr::from(1u64)).into_bigint())));
+    assert!(!canonical(bits_of(MNT4BigFr::MODULUS)));
+    assert!(!canonical(bits_of(modulus_plus_one)));
+    assert!(!canonical(vec![true; width]));
+}
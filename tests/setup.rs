@@ -0,0 +1,106 @@
+//! Keys from a fresh setup, rather than the fixture keys.
+#![cfg(feature = "prover")]
+
+use ark_mnt4_753::{Fr as MNT4BigFr, MNT4_753};
+use ark_serialize::CanonicalSerialize;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+use std::sync::OnceLock;
+
+use puzzle_gamma_ray::{
+    proofs_equal, prove, prove_with_seed, setup, verify_spend, vk_fingerprint, CircuitShape, Keys,
+};
+
+mod common;
+use common::Fixture;
+
+/// Keys set up from the spend of leaf 0 of the [`Fixture`], once for every
+/// test.
+fn keys() -> &'static Keys<MNT4_753> {
+    static KEYS: OnceLock<Keys<MNT4_753>> = OnceLock::new();
+    KEYS.get_or_init(|| {
+        setup(
+            Fixture::get().honest_spend(0),
+            &mut StdRng::seed_from_u64(1),
+        )
+        .unwrap()
+    })
+}
+
+#[test]
+fn fresh_keys_prove_and_verify_another_spend() {
+    let fixture = Fixture::get();
+    let (pk, vk) = keys();
+
+    let circuit = fixture.honest_spend(3);
+    let (root, nullifier) = (circuit.root, circuit.nullifier);
+    let proof = prove(pk, circuit, &mut StdRng::seed_from_u64(2)).unwrap();
+
+    verify_spend(vk, &root, &nullifier, &proof).unwrap();
+    assert!(verify_spend(vk, &root, &(nullifier + MNT4BigFr::from(1u64)), &proof).is_err());
+}
+
+#[test]
+fn proofs_are_reproducible_from_their_seed() {
+    let fixture = Fixture::get();
+    let (pk, _) = keys();
+    let bytes = |seed| {
+        let proof = prove_with_seed(pk, fixture.honest_spend(1), seed).unwrap();
+        let mut bytes = Vec::new();
+        proof.serialize_compressed(&mut bytes).unwrap();
+        bytes
+    };
+
+    let first = bytes(7);
+    assert_eq!(first, bytes(7));
+    assert_ne!(first, bytes(8));
+}
+
+#[test]
+fn only_same_seed_proofs_compare_equal() {
+    let fixture = Fixture::get();
+    let (pk, _) = keys();
+    let proof = |seed| prove_with_seed(pk, fixture.honest_spend(1), seed).unwrap();
+
+    let first = proof(7);
+    assert!(proofs_equal(&first, &first));
+    assert!(proofs_equal(&first, &proof(7)));
+    assert!(!proofs_equal(&first, &proof(8)));
+}
+
+#[test]
+fn a_modified_circuit_changes_the_fingerprint() {
+    let fixture = Fixture::get();
+    let fingerprint = vk_fingerprint(&keys().1);
+    let fresh = |circuit| {
+        let (_, vk) = setup(circuit, &mut StdRng::seed_from_u64(1)).unwrap();
+        vk_fingerprint(&vk)
+    };
+
+    // Setup is deterministic in its seed, so only the circuit differs
+    assert_eq!(fresh(fixture.honest_spend(2)), fingerprint);
+    assert_ne!(
+        fresh(fixture.honest_spend(0).with_strict_nullifier(false)),
+        fingerprint
+    );
+}
+
+#[test]
+fn a_cached_shape_sets_up_the_circuits_keys() {
+    let fixture = Fixture::get();
+    let shape = CircuitShape::of(&fixture.honest_spend(0)).unwrap();
+
+    // The witness doesn't change the shape
+    let other = CircuitShape::of(&fixture.honest_spend(2)).unwrap();
+    assert!(shape.matrices() == other.matrices());
+
+    let (pk, vk) = shape
+        .setup::<MNT4_753, _>(&mut StdRng::seed_from_u64(1))
+        .unwrap();
+    assert_eq!(vk_fingerprint(&vk), vk_fingerprint(&keys().1));
+
+    let circuit = fixture.honest_spend(3);
+    let (root, nullifier) = (circuit.root, circuit.nullifier);
+    let proof = prove(&pk, circuit, &mut StdRng::seed_from_u64(2)).unwrap();
+    verify_spend(&vk, &root, &nullifier, &proof).unwrap();
+}
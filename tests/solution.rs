@@ -0,0 +1,45 @@
+//! The double spend as [`solve`] computes it, on a tree of random leaves.
+#![cfg(feature = "prover")]
+
+use ark_mnt4_753::Fr as MNT4BigFr;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+use puzzle_gamma_ray::{
+    check_satisfied, generate_leaves, nullifier, solve, CircuitParams, Mnt753Cycle, Zeroizing,
+    LEGACY_DOMAIN,
+};
+
+#[test]
+fn the_solution_satisfies_the_legacy_circuit_with_a_new_nullifier() {
+    let params = CircuitParams::poseidon();
+    let (leaves, secrets) = generate_leaves(4, &mut StdRng::seed_from_u64(0));
+    let tree = params
+        .merkle_tree(leaves.iter().map(Vec::as_slice))
+        .unwrap();
+    let spend = |secret: MNT4BigFr, nullifier: MNT4BigFr| {
+        params
+            .spend_circuit::<Mnt753Cycle>(
+                tree.root(),
+                tree.generate_proof(1).unwrap(),
+                Zeroizing::new(secret),
+                nullifier,
+            )
+            .with_strict_nullifier(false)
+    };
+
+    let honest = nullifier(&params.leaf, secrets[1], LEGACY_DOMAIN).unwrap();
+    let (secret_hack, nullifier_hack) = solve(&leaves, secrets[1]).unwrap();
+    assert_ne!(secret_hack, secrets[1]);
+    assert_ne!(nullifier_hack, honest);
+
+    assert!(check_satisfied(&spend(secrets[1], honest)).unwrap());
+    assert!(check_satisfied(&spend(secret_hack, nullifier_hack)).unwrap());
+}
+
+#[test]
+fn a_secret_owning_no_leaf_has_no_solution() {
+    let (leaves, _) = generate_leaves(4, &mut StdRng::seed_from_u64(1));
+    let (_, others) = generate_leaves(1, &mut StdRng::seed_from_u64(2));
+
+    assert_eq!(solve(&leaves, others[0]), None);
+}
@@ -0,0 +1,94 @@
+//! Membership and non-membership in the sorted-key tree.
+#![cfg(feature = "prover")]
+
+use ark_mnt4_753::Fr as MNT4BigFr;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+
+use puzzle_gamma_ray::{CircuitParams, NonMembershipCircuit, NonMembershipProof, SparseMerkleTree};
+
+fn key(value: u64) -> MNT4BigFr {
+    MNT4BigFr::from(value)
+}
+
+fn tree(params: &CircuitParams) -> SparseMerkleTree {
+    SparseMerkleTree::new(
+        &params.leaf,
+        &params.two_to_one,
+        &[key(30), key(10), key(20)],
+        3,
+    )
+    .unwrap()
+}
+
+fn circuit_accepts(
+    params: &CircuitParams,
+    root: MNT4BigFr,
+    absent: MNT4BigFr,
+    proof: NonMembershipProof,
+) -> bool {
+    let circuit = NonMembershipCircuit::new(
+        params.leaf.clone(),
+        params.two_to_one.clone(),
+        root,
+        absent,
+        proof,
+    );
+    let cs = ConstraintSystem::new_ref();
+    circuit.generate_constraints(cs.clone()).unwrap();
+    cs.is_satisfied().unwrap()
+}
+
+#[test]
+fn stored_keys_open_their_leaf() {
+    let params = CircuitParams::poseidon();
+    let tree = tree(&params);
+
+    let path = tree.prove_membership(&key(20)).unwrap().unwrap();
+    assert!(path
+        .verify(
+            &params.leaf,
+            &params.two_to_one,
+            &tree.root(),
+            [key(20), key(30)]
+        )
+        .unwrap());
+    assert!(tree.prove_non_membership(&key(20)).unwrap().is_none());
+}
+
+#[test]
+fn absent_keys_fall_in_a_gap() {
+    let params = CircuitParams::poseidon();
+    let tree = tree(&params);
+
+    assert!(tree.prove_membership(&key(25)).unwrap().is_none());
+    let proof = tree.prove_non_membership(&key(25)).unwrap().unwrap();
+    assert_eq!((proof.low, proof.next), (key(20), key(30)));
+    assert!(proof
+        .verify(&params.leaf, &params.two_to_one, &tree.root(), &key(25))
+        .unwrap());
+    assert!(circuit_accepts(&params, tree.root(), key(25), proof));
+}
+
+#[test]
+fn a_gap_excludes_its_boundary_keys() {
+    let params = CircuitParams::poseidon();
+    let tree = tree(&params);
+
+    // The closest absent keys on either side of a stored one
+    for absent in [key(19), key(21)] {
+        let proof = tree.prove_non_membership(&absent).unwrap().unwrap();
+        assert!(circuit_accepts(&params, tree.root(), absent, proof));
+    }
+
+    // The gap (20, 30) can't show that either of its ends is absent
+    let gap = tree.prove_non_membership(&key(25)).unwrap().unwrap();
+    for stored in [key(20), key(30)] {
+        assert!(!gap
+            .verify(&params.leaf, &params.two_to_one, &tree.root(), &stored)
+            .unwrap());
+        assert!(!circuit_accepts(&params, tree.root(), stored, gap.clone()));
+    }
+
+    // Nor can anything show that a sentinel is absent
+    assert!(tree.prove_non_membership(&key(0)).unwrap().is_none());
+}
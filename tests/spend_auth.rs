@@ -0,0 +1,118 @@
+//! Signed spends, and the spend options carrying over into them.
+#![cfg(feature = "prover")]
+
+use ark_mnt4_753::Fr as MNT4BigFr;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem, SynthesisError};
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+use puzzle_gamma_ray::{
+    build_tree, generate_leaves, nullifier, public_key, sign, verify_signature, CircuitParams,
+    Mnt753Cycle, Payout, SpendAuthCircuit, Zeroizing, CIRCUIT_VERSION, LEGACY_DOMAIN,
+};
+
+fn spend_auth(rng: &mut StdRng) -> SpendAuthCircuit {
+    let params = CircuitParams::poseidon();
+    let (leaves, secrets) = generate_leaves(2, rng);
+    let (tree, _) = build_tree(&params.leaf, &params.two_to_one, &leaves, 1).unwrap();
+
+    let spend = params.spend_circuit::<Mnt753Cycle>(
+        tree.root(),
+        tree.generate_proof(0).unwrap(),
+        Zeroizing::new(secrets[0]),
+        nullifier(&params.leaf, secrets[0], LEGACY_DOMAIN).unwrap(),
+    );
+    let message = MNT4BigFr::from(7u64);
+    let signature = sign(&params.leaf, secrets[0], message, rng).unwrap();
+
+    SpendAuthCircuit::new(spend, message, signature)
+}
+
+/// The instance assignment of `circuit`, without the leading one.
+fn synthesize(circuit: SpendAuthCircuit) -> Result<Vec<MNT4BigFr>, SynthesisError> {
+    let cs = ConstraintSystem::new_ref();
+    circuit.generate_constraints(cs.clone())?;
+    assert!(cs.is_satisfied()?);
+
+    let instance = cs.borrow().unwrap().instance_assignment[1..].to_vec();
+    Ok(instance)
+}
+
+fn is_satisfied(circuit: SpendAuthCircuit) -> bool {
+    let cs = ConstraintSystem::new_ref();
+    circuit.generate_constraints(cs.clone()).unwrap();
+    cs.is_satisfied().unwrap()
+}
+
+#[test]
+fn a_valid_signature_is_accepted() {
+    let params = CircuitParams::poseidon();
+    let circuit = spend_auth(&mut StdRng::seed_from_u64(3));
+    let pk = public_key(*circuit.spend.secret);
+
+    assert!(verify_signature(&params.leaf, &pk, circuit.message, &circuit.signature).unwrap());
+    assert!(is_satisfied(circuit));
+}
+
+#[test]
+fn a_signature_by_another_key_is_rejected() {
+    let params = CircuitParams::poseidon();
+    let mut rng = StdRng::seed_from_u64(4);
+    let mut circuit = spend_auth(&mut rng);
+    let (_, others) = generate_leaves(1, &mut rng);
+    circuit.signature = sign(&params.leaf, others[0], circuit.message, &mut rng).unwrap();
+
+    let pk = public_key(*circuit.spend.secret);
+    assert!(!verify_signature(&params.leaf, &pk, circuit.message, &circuit.signature).unwrap());
+    assert!(!is_satisfied(circuit));
+}
+
+#[test]
+fn a_tampered_message_is_rejected() {
+    let params = CircuitParams::poseidon();
+    let mut circuit = spend_auth(&mut StdRng::seed_from_u64(5));
+    circuit.message += MNT4BigFr::from(1u64);
+
+    let pk = public_key(*circuit.spend.secret);
+    assert!(!verify_signature(&params.leaf, &pk, circuit.message, &circuit.signature).unwrap());
+    assert!(!is_satisfied(circuit));
+}
+
+#[test]
+fn payout_and_version_are_allocated_around_the_message() {
+    let mut circuit = spend_auth(&mut StdRng::seed_from_u64(0));
+    circuit.spend = circuit
+        .spend
+        .with_payout(Payout {
+            fee: MNT4BigFr::from(3u64),
+            recipient_hash: MNT4BigFr::from(5u64),
+        })
+        .with_version(CIRCUIT_VERSION);
+
+    let expected = circuit.public_inputs();
+    assert_eq!(expected.len(), 6);
+    assert_eq!(expected[4], circuit.message);
+    assert_eq!(synthesize(circuit).unwrap(), expected);
+}
+
+#[test]
+fn default_spend_inputs_end_with_the_message() {
+    let circuit = spend_auth(&mut StdRng::seed_from_u64(1));
+    let expected = circuit.public_inputs();
+
+    assert_eq!(
+        expected,
+        [circuit.spend.root, circuit.spend.nullifier, circuit.message]
+    );
+    assert_eq!(synthesize(circuit).unwrap(), expected);
+}
+
+#[test]
+fn a_path_of_the_wrong_height_is_rejected() {
+    let mut circuit = spend_auth(&mut StdRng::seed_from_u64(2));
+    circuit.spend = circuit.spend.with_height(2);
+
+    assert!(matches!(
+        synthesize(circuit),
+        Err(SynthesisError::Unsatisfiable)
+    ));
+}
@@ -0,0 +1,95 @@
+#![cfg(feature = "prover")]
+
+use ark_ff::{BigInteger, PrimeField};
+use ark_mnt4_753::Fr as MNT4BigFr;
+use ark_mnt6_753::Fr as MNT6BigFr;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+use proptest::prelude::*;
+
+use puzzle_gamma_ray::{
+    build_tree, canonical_secret, nullifier, poseidon_parameters, public_key, sample_secret,
+    Mnt753Cycle, SpendCircuit, Zeroizing, LEGACY_DOMAIN,
+};
+
+/// Synthesizes a spend of the leaf owned by `owner` with `secret` as the
+/// witness, and reports whether every constraint holds.
+fn accepts(owner: MNT4BigFr, secret: MNT4BigFr, strict: bool) -> bool {
+    let params = poseidon_parameters::poseidon_parameters();
+    let leaves = vec![vec![public_key(owner).x]];
+    let (tree, _) = build_tree(&params, &params, &leaves, 1).unwrap();
+
+    let circuit = SpendCircuit::<Mnt753Cycle>::new(
+        params.clone(),
+        params.clone(),
+        tree.root(),
+        tree.generate_proof(0).unwrap(),
+        Zeroizing::new(secret),
+        nullifier(&params, secret, LEGACY_DOMAIN).unwrap(),
+    )
+    .with_strict_nullifier(strict);
+
+    let cs = ConstraintSystem::new_ref();
+    circuit.generate_constraints(cs.clone()).unwrap();
+    cs.is_satisfied().unwrap()
+}
+
+/// `r - secret` for the MNT6-753 modulus `r`, which owns the same leaf.
+fn negated(secret: MNT4BigFr) -> MNT4BigFr {
+    let scalar = MNT6BigFr::from_bigint(secret.into_bigint()).unwrap();
+    MNT4BigFr::from_bigint((-scalar).into_bigint()).unwrap()
+}
+
+/// `secret + r`, when it is still below the MNT4-753 modulus.
+fn shifted(secret: MNT4BigFr) -> Option<MNT4BigFr> {
+    let mut bigint = secret.into_bigint();
+    bigint.add_with_carry(&MNT6BigFr::MODULUS);
+    MNT4BigFr::from_bigint(bigint)
+}
+
+fn secrets() -> impl Strategy<Value = MNT4BigFr> {
+    let half = MNT4BigFr::from_bigint(MNT6BigFr::MODULUS_MINUS_ONE_DIV_TWO).unwrap();
+    prop_oneof![
+        any::<u64>().prop_map(|seed| sample_secret(&mut StdRng::seed_from_u64(seed))),
+        // Small secrets, whose `secret + r` alias wraps just past the modulus
+        (1..u64::MAX).prop_map(MNT4BigFr::from),
+        // The top of the accepted lower half, whose negation sits just above it
+        (0..1u64 << 16).prop_map(move |delta| half - MNT4BigFr::from(delta)),
+    ]
+}
+
+proptest! {
+    // Each case synthesizes a few full spend circuits
+    #![proptest_config(ProptestConfig::with_cases(24))]
+
+    #[test]
+    fn strict_circuit_accepts_only_the_canonical_secret(secret in secrets()) {
+        prop_assert!(accepts(secret, secret, true));
+
+        let alias = negated(secret);
+        prop_assert_ne!(
+            nullifier(&poseidon_parameters::poseidon_parameters(), alias, LEGACY_DOMAIN).unwrap(),
+            nullifier(&poseidon_parameters::poseidon_parameters(), secret, LEGACY_DOMAIN).unwrap()
+        );
+        prop_assert!(accepts(secret, alias, false), "the alias attacks the legacy circuit");
+        prop_assert!(!accepts(secret, alias, true));
+
+        if let Some(alias) = shifted(secret) {
+            prop_assert!(!accepts(secret, alias, true));
+        }
+    }
+}
+
+#[test]
+fn strict_mode_rejects_the_upper_half() {
+    let secret = sample_secret(&mut StdRng::seed_from_u64(0));
+    let upper = negated(secret);
+
+    assert!(accepts(secret, secret, true));
+    assert!(!accepts(upper, upper, true));
+
+    // The owner of an upper-half key spends it as `r - s`
+    assert_eq!(canonical_secret::<Mnt753Cycle>(upper), secret);
+    assert!(accepts(upper, canonical_secret::<Mnt753Cycle>(upper), true));
+}
@@ -0,0 +1,122 @@
+//! The spans the proving pipeline emits, and the sizes they record.
+#![cfg(all(feature = "native-io", feature = "prover"))]
+
+use ark_groth16::{ProvingKey, VerifyingKey};
+use ark_mnt4_753::{Fr as MNT4BigFr, MNT4_753};
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::Subscriber;
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+
+use puzzle_gamma_ray::{
+    build_tree, count_constraints, find_leaf_index, from_file, nullifier, prove, tree_depth,
+    verify, CircuitParams, Mnt753Cycle, Zeroizing, LEGACY_DOMAIN,
+};
+
+/// A span name, the name of an integer field recorded on it and its value.
+type SpanField = (&'static str, &'static str, u64);
+
+/// Span names in creation order, and the integer fields recorded on them.
+#[derive(Clone, Default)]
+struct SpanLog {
+    names: Arc<Mutex<Vec<&'static str>>>,
+    fields: Arc<Mutex<Vec<SpanField>>>,
+}
+
+struct FieldVisitor<'a> {
+    span: &'static str,
+    fields: &'a Mutex<Vec<SpanField>>,
+}
+
+impl Visit for FieldVisitor<'_> {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        let mut fields = self.fields.lock().unwrap();
+        fields.push((self.span, field.name(), value));
+    }
+
+    fn record_debug(&mut self, _: &Field, _: &dyn Debug) {}
+}
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for SpanLog {
+    fn new_span(&self, attrs: &Attributes<'_>, _: &Id, _: Context<'_, S>) {
+        let span = attrs.metadata().name();
+        self.names.lock().unwrap().push(span);
+        attrs.record(&mut FieldVisitor {
+            span,
+            fields: &self.fields,
+        });
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let span = ctx.span(id).unwrap().name();
+        values.record(&mut FieldVisitor {
+            span,
+            fields: &self.fields,
+        });
+    }
+}
+
+#[test]
+fn the_pipeline_emits_its_spans() {
+    let log = SpanLog::default();
+    let subscriber = tracing_subscriber::registry().with(log.clone());
+
+    tracing::subscriber::with_default(subscriber, || {
+        let params = CircuitParams::poseidon();
+        let (pk, vk): (ProvingKey<MNT4_753>, VerifyingKey<MNT4_753>) =
+            from_file("./proof_keys.bin").unwrap();
+        let leaves: Vec<Vec<MNT4BigFr>> = from_file("./leaves.bin").unwrap();
+        let secret: MNT4BigFr = from_file("./leaked_secret.bin").unwrap();
+
+        let height = tree_depth(leaves.len());
+        let (tree, _) = build_tree(&params.leaf, &params.two_to_one, &leaves, height).unwrap();
+        let i = find_leaf_index(&leaves, secret).unwrap();
+        let nullifier = nullifier(&params.leaf, secret, LEGACY_DOMAIN).unwrap();
+        let circuit = params
+            .spend_circuit::<Mnt753Cycle>(
+                tree.root(),
+                tree.generate_proof(i).unwrap(),
+                Zeroizing::new(secret),
+                nullifier,
+            )
+            .with_strict_nullifier(false);
+
+        count_constraints(&circuit).unwrap();
+        let proof = prove(&pk, circuit, &mut StdRng::seed_from_u64(0)).unwrap();
+        assert!(verify(&vk, &tree.root(), &nullifier, &proof).unwrap());
+    });
+
+    let names = log.names.lock().unwrap();
+    for span in ["build_tree", "count_constraints", "prove", "verify"] {
+        assert!(names.contains(&span), "no {} span in {:?}", span, names);
+    }
+
+    let fields = log.fields.lock().unwrap();
+    assert!(
+        fields.contains(&("build_tree", "leaves", 4)),
+        "{:?}",
+        fields
+    );
+    assert!(
+        fields.contains(&("build_tree", "height", 2)),
+        "{:?}",
+        fields
+    );
+    assert!(
+        fields.contains(&("prove", "public_inputs", 2)),
+        "{:?}",
+        fields
+    );
+    assert!(
+        fields.iter().any(|&(span, field, count)| (span, field)
+            == ("count_constraints", "constraints")
+            && count > 0),
+        "{:?}",
+        fields
+    );
+}
@@ -0,0 +1,160 @@
+//! The balance check of the transfer circuit: values in must equal values
+//! out plus the fee, summed in the field rather than in `u64`.
+#![cfg(feature = "prover")]
+
+use ark_ff::Field;
+use ark_mnt4_753::Fr as MNT4BigFr;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+use puzzle_gamma_ray::{
+    commitment, enforce_in_range, nullifier, public_key, sample_secret, CircuitParams, InputNote,
+    Note, TransferCircuit, LEGACY_DOMAIN, VALUE_BITS,
+};
+
+/// The tree holding each of `notes` as its [`Note::leaf`], as a
+/// coordinator inserts a transfer's outputs, and the inputs spending them.
+fn spend_notes(
+    params: &CircuitParams,
+    secrets: &[MNT4BigFr],
+    notes: &[Note],
+) -> (MNT4BigFr, Vec<InputNote<MNT4BigFr>>) {
+    let leaves: Vec<Vec<MNT4BigFr>> = notes
+        .iter()
+        .map(|note| note.leaf(&params.leaf).unwrap())
+        .collect();
+    let tree = params
+        .merkle_tree(leaves.iter().map(Vec::as_slice))
+        .unwrap();
+
+    let inputs = secrets
+        .iter()
+        .zip(notes)
+        .enumerate()
+        .map(|(i, (secret, note))| InputNote {
+            secret: *secret,
+            value: note.value,
+            rho: note.rho,
+            proof: tree.generate_proof(i).unwrap(),
+            nullifier: nullifier(&params.leaf, *secret, LEGACY_DOMAIN).unwrap(),
+        })
+        .collect();
+    (tree.root(), inputs)
+}
+
+/// A transfer of notes of `inputs` values, held in one tree, to notes of
+/// `outputs` values.
+fn transfer(inputs: &[u64], outputs: &[u64], fee: u64) -> TransferCircuit {
+    let mut rng = StdRng::seed_from_u64(0);
+    let params = CircuitParams::poseidon();
+
+    let secrets: Vec<MNT4BigFr> = inputs.iter().map(|_| sample_secret(&mut rng)).collect();
+    let notes: Vec<Note> = secrets
+        .iter()
+        .zip(inputs)
+        .map(|(secret, value)| Note::random(public_key(*secret).x, *value, &mut rng))
+        .collect();
+    let (root, inputs) = spend_notes(&params, &secrets, &notes);
+    let outputs = outputs
+        .iter()
+        .map(|value| Note::random(MNT4BigFr::from(7u64), *value, &mut rng))
+        .collect();
+
+    TransferCircuit::new(params.leaf, params.two_to_one, root, fee, inputs, outputs)
+}
+
+fn satisfied(circuit: TransferCircuit) -> bool {
+    let cs = ConstraintSystem::new_ref();
+    circuit.generate_constraints(cs.clone()).unwrap();
+    cs.is_satisfied().unwrap()
+}
+
+#[test]
+fn a_balanced_transfer_is_satisfied() {
+    assert!(satisfied(transfer(&[30, 12], &[25, 15], 2)));
+}
+
+#[test]
+fn an_unbalanced_transfer_is_not() {
+    assert!(!satisfied(transfer(&[30, 12], &[25, 16], 2)));
+    assert!(!satisfied(transfer(&[30, 12], &[25, 15], 0)));
+}
+
+#[test]
+fn values_past_u64_do_not_wrap() {
+    // The inputs sum to 2^64, which wraps to 0 in `u64`
+    assert!(satisfied(transfer(&[u64::MAX, 1], &[u64::MAX], 1)));
+    assert!(!satisfied(transfer(&[u64::MAX, 1], &[0], 0)));
+}
+
+#[test]
+fn outputs_are_public_commitments() {
+    let circuit = transfer(&[30, 12], &[25, 15], 2);
+    let params = CircuitParams::poseidon();
+    let public_inputs = circuit.public_inputs().unwrap();
+    assert_eq!(public_inputs.len(), 6);
+    for (output, input) in circuit.outputs.iter().zip(&public_inputs[4..]) {
+        assert_eq!(commitment(&params.leaf, output).unwrap(), *input);
+    }
+
+    let cs = ConstraintSystem::new_ref();
+    circuit.generate_constraints(cs.clone()).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+    assert_eq!(cs.borrow().unwrap().instance_assignment[1..], public_inputs);
+}
+
+#[test]
+fn the_outputs_of_one_transfer_are_spent_by_the_next() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let params = CircuitParams::poseidon();
+
+    let first = transfer(&[30, 12], &[25, 15], 2);
+    let secrets: Vec<MNT4BigFr> = (0..2).map(|_| sample_secret(&mut rng)).collect();
+    let outputs: Vec<Note> = secrets
+        .iter()
+        .zip(&first.outputs)
+        .map(|(secret, output)| Note::new(public_key(*secret).x, output.value, output.rho))
+        .collect();
+    let first = TransferCircuit {
+        outputs: outputs.clone(),
+        ..first
+    };
+    // The coordinator only sees the output commitments
+    let commitments = first.public_inputs().unwrap()[4..].to_vec();
+    assert!(satisfied(first));
+
+    let (root, inputs) = spend_notes(&params, &secrets, &outputs);
+    let tree = params
+        .merkle_tree(commitments.iter().map(std::slice::from_ref))
+        .unwrap();
+    assert_eq!(tree.root(), root);
+
+    let second = TransferCircuit::new(
+        params.leaf,
+        params.two_to_one,
+        root,
+        1,
+        inputs,
+        vec![Note::random(MNT4BigFr::from(7u64), 39, &mut rng)],
+    );
+    assert!(satisfied(second));
+}
+
+#[test]
+fn an_output_value_past_the_bound_is_rejected() {
+    // Note values are `u64`s, so an over-range output is only reachable by a
+    // prover assigning the witness directly
+    let in_range = |value: MNT4BigFr| {
+        let cs = ConstraintSystem::new_ref();
+        let value = FpVar::new_witness(cs.clone(), || Ok(value)).unwrap();
+        enforce_in_range(&value, VALUE_BITS).unwrap();
+        cs.is_satisfied().unwrap()
+    };
+
+    assert!(in_range(MNT4BigFr::from(u64::MAX)));
+    assert!(!in_range(MNT4BigFr::from(2u64).pow([VALUE_BITS as u64])));
+    // The value that would balance a transfer minting one unit
+    assert!(!in_range(-MNT4BigFr::from(1u64)));
+}
@@ -0,0 +1,230 @@
+//! Fixed-height trees: padded when under-full, refused when over-full.
+#![cfg(feature = "prover")]
+
+use ark_crypto_primitives::crh::{CRHScheme, TwoToOneCRHScheme};
+use ark_relations::r1cs::SynthesisError;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+use puzzle_gamma_ray::tree::empty_leaf;
+use puzzle_gamma_ray::{
+    build_tree, check_path_height, check_satisfied, generate_leaves, nullifier, path_height,
+    public_key, tree_depth, tree_from_public_keys, CircuitParams, CompressH, LeafH, Mnt753Cycle,
+    MntMerkleTree, TreeError, Zeroizing, LEGACY_DOMAIN, MAX_TREE_HEIGHT,
+};
+
+#[test]
+fn a_full_tree_holds_exactly_its_leaves() {
+    let params = CircuitParams::poseidon();
+    let (leaves, _) = generate_leaves(4, &mut StdRng::seed_from_u64(0));
+    let (tree, padded) = build_tree(&params.leaf, &params.two_to_one, &leaves, 2).unwrap();
+
+    assert_eq!(padded, leaves);
+    for (i, leaf) in leaves.iter().enumerate() {
+        let proof = tree.generate_proof(i).unwrap();
+        assert!(proof
+            .verify(
+                &params.leaf,
+                &params.two_to_one,
+                &tree.root(),
+                leaf.as_slice()
+            )
+            .unwrap());
+    }
+}
+
+#[test]
+fn an_under_full_tree_is_padded_with_empty_leaves() {
+    let params = CircuitParams::poseidon();
+    let (leaves, _) = generate_leaves(3, &mut StdRng::seed_from_u64(0));
+    let (tree, padded) = build_tree(&params.leaf, &params.two_to_one, &leaves, 3).unwrap();
+
+    assert_eq!(padded.len(), 8);
+    assert_eq!(padded[..3], leaves[..]);
+    assert!(padded[3..].iter().all(|leaf| *leaf == empty_leaf()));
+    let proof = tree.generate_proof(2).unwrap();
+    assert!(proof
+        .verify(
+            &params.leaf,
+            &params.two_to_one,
+            &tree.root(),
+            leaves[2].as_slice()
+        )
+        .unwrap());
+}
+
+#[test]
+fn an_over_full_tree_is_refused() {
+    let params = CircuitParams::poseidon();
+    let (leaves, _) = generate_leaves(5, &mut StdRng::seed_from_u64(0));
+
+    assert!(matches!(
+        build_tree(&params.leaf, &params.two_to_one, &leaves, 2),
+        Err(TreeError::TooManyLeaves {
+            leaves: 5,
+            capacity: 4
+        })
+    ));
+    assert!(matches!(
+        build_tree(&params.leaf, &params.two_to_one, &leaves, 0),
+        Err(TreeError::ZeroHeight)
+    ));
+}
+
+#[test]
+fn a_tree_too_tall_to_materialize_is_refused() {
+    let params = CircuitParams::poseidon();
+    let (leaves, _) = generate_leaves(2, &mut StdRng::seed_from_u64(0));
+
+    for height in [MAX_TREE_HEIGHT + 1, 32, 64, usize::MAX] {
+        assert!(matches!(
+            build_tree(&params.leaf, &params.two_to_one, &leaves, height),
+            Err(TreeError::HeightTooLarge {
+                max: MAX_TREE_HEIGHT,
+                ..
+            })
+        ));
+    }
+}
+
+#[test]
+fn trees_of_no_one_and_two_leaves() {
+    let params = CircuitParams::poseidon();
+    let (leaves, secrets) = generate_leaves(2, &mut StdRng::seed_from_u64(0));
+    let opens = |tree: &MntMerkleTree, i: usize| {
+        tree.generate_proof(i)
+            .unwrap()
+            .verify(
+                &params.leaf,
+                &params.two_to_one,
+                &tree.root(),
+                leaves[i].as_slice(),
+            )
+            .unwrap()
+    };
+
+    assert!(matches!(
+        build_tree(&params.leaf, &params.two_to_one, &[], 1),
+        Err(TreeError::NoLeaves)
+    ));
+    assert!(matches!(
+        tree_from_public_keys(&[], &params),
+        Err(TreeError::NoLeaves)
+    ));
+
+    // A single leaf is hashed with one empty leaf beside it
+    let (tree, padded) = tree_from_public_keys(&[public_key(secrets[0])], &params).unwrap();
+    assert_eq!(padded, [leaves[0].clone(), empty_leaf()]);
+    let digest = |leaf: &[_]| LeafH::evaluate(&params.leaf, leaf).unwrap();
+    assert_eq!(
+        tree.root(),
+        CompressH::compress(
+            &params.two_to_one,
+            digest(&leaves[0]),
+            digest(&empty_leaf())
+        )
+        .unwrap()
+    );
+    assert!(opens(&tree, 0));
+
+    let (tree, padded) = build_tree(&params.leaf, &params.two_to_one, &leaves, 1).unwrap();
+    assert_eq!(padded, leaves);
+    assert!(opens(&tree, 0) && opens(&tree, 1));
+}
+
+#[test]
+fn a_tree_of_public_keys_proves_membership_in_the_circuit() {
+    let params = CircuitParams::poseidon();
+    let (leaves, secrets) = generate_leaves(3, &mut StdRng::seed_from_u64(0));
+    let pks: Vec<_> = secrets.iter().map(|s| public_key(*s)).collect();
+    let (tree, padded) = tree_from_public_keys(&pks, &params).unwrap();
+
+    assert_eq!(padded.len(), 4);
+    assert_eq!(padded[..3], leaves[..]);
+    for (i, secret) in secrets.iter().enumerate() {
+        let circuit = params.spend_circuit::<Mnt753Cycle>(
+            tree.root(),
+            tree.generate_proof(i).unwrap(),
+            Zeroizing::new(*secret),
+            nullifier(&params.leaf, *secret, LEGACY_DOMAIN).unwrap(),
+        );
+        assert!(check_satisfied(&circuit).unwrap(), "leaf {}", i);
+    }
+}
+
+#[test]
+fn only_a_path_of_the_declared_height_is_spent() {
+    let params = CircuitParams::poseidon();
+    let (leaves, secrets) = generate_leaves(5, &mut StdRng::seed_from_u64(0));
+    let (tree, _) = build_tree(&params.leaf, &params.two_to_one, &leaves, 3).unwrap();
+    let spend = |path| {
+        params
+            .spend_circuit::<Mnt753Cycle>(
+                tree.root(),
+                path,
+                Zeroizing::new(secrets[1]),
+                nullifier(&params.leaf, secrets[1], LEGACY_DOMAIN).unwrap(),
+            )
+            .with_height(3)
+    };
+
+    let path = tree.generate_proof(1).unwrap();
+    assert_eq!(path_height(&path), 3);
+    check_path_height(&path, 3).unwrap();
+    assert!(check_satisfied(&spend(path.clone())).unwrap());
+
+    let mut truncated = path;
+    truncated.auth_path.pop();
+    assert!(matches!(
+        check_path_height(&truncated, 3),
+        Err(SynthesisError::Unsatisfiable)
+    ));
+    assert!(matches!(
+        check_satisfied(&spend(truncated)),
+        Err(SynthesisError::Unsatisfiable)
+    ));
+}
+
+#[test]
+fn the_depth_is_the_smallest_height_that_fits() {
+    for (leaf_count, depth) in [
+        (1, 1),
+        (2, 1),
+        (3, 2),
+        (4, 2),
+        (5, 3),
+        (8, 3),
+        (9, 4),
+        (1024, 10),
+    ] {
+        assert_eq!(tree_depth(leaf_count), depth, "{} leaves", leaf_count);
+    }
+
+    let params = CircuitParams::poseidon();
+    let (leaves, _) = generate_leaves(5, &mut StdRng::seed_from_u64(0));
+    let depth = tree_depth(leaves.len());
+    let (_, padded) = build_tree(&params.leaf, &params.two_to_one, &leaves, depth).unwrap();
+    assert_eq!(padded.len(), 8);
+    assert!(build_tree(&params.leaf, &params.two_to_one, &leaves, depth - 1).is_err());
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn the_parallel_build_has_the_sequential_root() {
+    use ark_mnt4_753::Fr as MNT4BigFr;
+    use ark_std::UniformRand;
+    use puzzle_gamma_ray::build_tree_parallel;
+
+    let params = CircuitParams::poseidon();
+    let mut rng = StdRng::seed_from_u64(0);
+    let leaves: Vec<Vec<MNT4BigFr>> = (0..4096).map(|_| vec![MNT4BigFr::rand(&mut rng)]).collect();
+
+    assert!(matches!(
+        build_tree_parallel(&params.leaf, &params.two_to_one, &leaves[..1]),
+        Err(TreeError::UnpaddedLeafCount { leaves: 1 })
+    ));
+    let parallel = build_tree_parallel(&params.leaf, &params.two_to_one, &leaves).unwrap();
+    let sequential = params
+        .merkle_tree(leaves.iter().map(Vec::as_slice))
+        .unwrap();
+    assert_eq!(parallel.root(), sequential.root());
+}
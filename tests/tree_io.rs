@@ -0,0 +1,170 @@
+//! Trees saved and loaded again, or streamed from their leaves.
+#![cfg(all(feature = "native-io", feature = "prover"))]
+
+use ark_serialize::CanonicalSerialize;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+use puzzle_gamma_ray::io::{TREE_MAGIC, TREE_VERSION};
+
+use puzzle_gamma_ray::{
+    build_tree, build_tree_streaming, generate_leaves, load_tree, read_leaves_streaming, save_tree,
+    to_file, CircuitParams, LoadError, MAX_TREE_HEIGHT,
+};
+
+#[test]
+fn a_loaded_tree_opens_every_leaf_as_the_original() {
+    let params = CircuitParams::poseidon();
+    let (leaves, _) = generate_leaves(3, &mut StdRng::seed_from_u64(0));
+    let (tree, padded) = build_tree(&params.leaf, &params.two_to_one, &leaves, 2).unwrap();
+
+    let path = std::env::temp_dir().join(format!("gamma-ray-tree-{}.bin", std::process::id()));
+    let path = path.to_str().unwrap();
+    save_tree(path, &tree).unwrap();
+    let loaded = load_tree(path, &params.leaf, &params.two_to_one);
+    std::fs::remove_file(path).unwrap();
+    let loaded = loaded.unwrap();
+
+    assert_eq!(loaded.root(), tree.root());
+    for (index, leaf) in padded.iter().enumerate() {
+        let proof = loaded.generate_proof(index).unwrap();
+        assert_eq!(
+            proof.auth_path,
+            tree.generate_proof(index).unwrap().auth_path
+        );
+        assert!(proof
+            .verify(
+                &params.leaf,
+                &params.two_to_one,
+                &tree.root(),
+                leaf.as_slice()
+            )
+            .unwrap());
+    }
+}
+
+#[test]
+fn a_tree_loaded_under_other_parameters_is_rejected() {
+    let params = CircuitParams::poseidon();
+    let (leaves, _) = generate_leaves(2, &mut StdRng::seed_from_u64(1));
+    let (tree, _) = build_tree(&params.leaf, &params.two_to_one, &leaves, 1).unwrap();
+
+    let mut other = params.two_to_one.clone();
+    other.ark[0][0] += ark_mnt4_753::Fr::from(1u64);
+
+    let path =
+        std::env::temp_dir().join(format!("gamma-ray-tree-other-{}.bin", std::process::id()));
+    let path = path.to_str().unwrap();
+    save_tree(path, &tree).unwrap();
+    let loaded = load_tree(path, &params.leaf, &other);
+    std::fs::remove_file(path).unwrap();
+
+    assert!(matches!(loaded, Err(LoadError::RootMismatch { .. })));
+}
+
+#[test]
+fn a_tree_file_with_an_unpadded_digest_count_is_rejected() {
+    let params = CircuitParams::poseidon();
+    let path = std::env::temp_dir().join(format!(
+        "gamma-ray-tree-unpadded-{}.bin",
+        std::process::id()
+    ));
+    let path = path.to_str().unwrap();
+
+    for count in [0, 1, 3] {
+        let mut buffer = TREE_MAGIC.to_vec();
+        buffer.push(TREE_VERSION);
+        (
+            ark_mnt4_753::Fr::from(0u64),
+            vec![ark_mnt4_753::Fr::from(1u64); count],
+        )
+            .serialize_uncompressed(&mut buffer)
+            .unwrap();
+        std::fs::write(path, &buffer).unwrap();
+        let loaded = load_tree(path, &params.leaf, &params.two_to_one);
+        std::fs::remove_file(path).unwrap();
+
+        assert!(
+            matches!(loaded, Err(LoadError::Tree { .. })),
+            "{} digests",
+            count
+        );
+    }
+}
+
+#[test]
+fn a_streamed_tree_has_the_buffered_root() {
+    let params = CircuitParams::poseidon();
+    let (leaves, _) = generate_leaves(64, &mut StdRng::seed_from_u64(2));
+    let buffered = params
+        .merkle_tree(leaves.iter().map(Vec::as_slice))
+        .unwrap();
+
+    let path =
+        std::env::temp_dir().join(format!("gamma-ray-tree-leaves-{}.bin", std::process::id()));
+    let path = path.to_str().unwrap();
+    to_file(path, &leaves).unwrap();
+    let reader = read_leaves_streaming(path).unwrap();
+    assert_eq!(reader.remaining(), 64);
+    let streamed_leaves: Result<Vec<_>, _> = reader.collect();
+    let streamed = build_tree_streaming(path, &params.leaf, &params.two_to_one);
+    std::fs::remove_file(path).unwrap();
+
+    assert_eq!(streamed_leaves.unwrap(), leaves);
+    assert_eq!(streamed.unwrap().root(), buffered.root());
+}
+
+#[test]
+fn streaming_an_unpadded_leaf_count_is_an_error() {
+    let params = CircuitParams::poseidon();
+    let (leaves, _) = generate_leaves(3, &mut StdRng::seed_from_u64(3));
+
+    let path = std::env::temp_dir().join(format!(
+        "gamma-ray-tree-three-leaves-{}.bin",
+        std::process::id()
+    ));
+    let path = path.to_str().unwrap();
+    to_file(path, &leaves).unwrap();
+    let streamed = build_tree_streaming(path, &params.leaf, &params.two_to_one);
+    std::fs::remove_file(path).unwrap();
+
+    assert!(matches!(streamed, Err(LoadError::Tree { .. })));
+}
+
+#[test]
+fn a_leaf_count_the_file_cannot_hold_is_refused_before_allocating() {
+    let params = CircuitParams::poseidon();
+    let path = std::env::temp_dir().join(format!(
+        "gamma-ray-tree-huge-count-{}.bin",
+        std::process::id()
+    ));
+    let path = path.to_str().unwrap();
+
+    let mut buffer = Vec::new();
+    (1u64 << 40).serialize_uncompressed(&mut buffer).unwrap();
+    buffer.extend_from_slice(&[0; 16]);
+    std::fs::write(path, &buffer).unwrap();
+    let read = read_leaves_streaming(path).map(|reader| reader.remaining());
+    let streamed = build_tree_streaming(path, &params.leaf, &params.two_to_one);
+    std::fs::remove_file(path).unwrap();
+
+    assert!(matches!(
+        read,
+        Err(LoadError::LeafCountTooLarge {
+            leaves: 1099511627776,
+            len: 24,
+            ..
+        })
+    ));
+    assert!(matches!(streamed, Err(LoadError::LeafCountTooLarge { .. })));
+
+    // Empty leaves, enough of them to fit the file but not the tallest tree
+    let count = 1u64 << (MAX_TREE_HEIGHT + 1);
+    let mut buffer = Vec::new();
+    count.serialize_uncompressed(&mut buffer).unwrap();
+    buffer.resize(8 + 8 * count as usize, 0);
+    std::fs::write(path, &buffer).unwrap();
+    let streamed = build_tree_streaming(path, &params.leaf, &params.two_to_one);
+    std::fs::remove_file(path).unwrap();
+
+    assert!(matches!(streamed, Err(LoadError::Tree { .. })));
+}
@@ -0,0 +1,53 @@
+//! Replacing a leaf in-circuit, against the root the native tree moves to.
+#![cfg(feature = "prover")]
+
+use ark_mnt4_753::Fr as MNT4BigFr;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::ConstraintSystem;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+use puzzle_gamma_ray::{generate_leaves, verify_update, CircuitParams, MerklePathVar};
+
+/// Whether the circuit accepts replacing leaf 2 of a tree of four random
+/// leaves, with `old_offset` and `new_offset` added to the honest old and new
+/// roots.
+fn updates(old_offset: u64, new_offset: u64) -> bool {
+    let params = CircuitParams::poseidon();
+    let (leaves, _) = generate_leaves(5, &mut StdRng::seed_from_u64(0));
+    let mut tree = params
+        .merkle_tree(leaves[..4].iter().map(Vec::as_slice))
+        .unwrap();
+    let old_root = tree.root();
+    let path = tree.generate_proof(2).unwrap();
+    tree.update(2, &leaves[4]).unwrap();
+    let new_root = tree.root();
+
+    let cs = ConstraintSystem::<MNT4BigFr>::new_ref();
+    let input = |x: MNT4BigFr| FpVar::new_input(cs.clone(), || Ok(x)).unwrap();
+    let witness = |leaf: &[MNT4BigFr]| -> Vec<_> {
+        leaf.iter()
+            .map(|x| FpVar::new_witness(cs.clone(), || Ok(*x)).unwrap())
+            .collect()
+    };
+    let (leaf_params, two_to_one_params) = params.allocate(cs.clone()).unwrap();
+    let path = MerklePathVar::new_witness(cs.clone(), || Ok(path)).unwrap();
+    verify_update(
+        &leaf_params,
+        &two_to_one_params,
+        &path,
+        &input(old_root + MNT4BigFr::from(old_offset)),
+        &witness(&leaves[2]),
+        &witness(&leaves[4]),
+        &input(new_root + MNT4BigFr::from(new_offset)),
+    )
+    .unwrap();
+    cs.is_satisfied().unwrap()
+}
+
+#[test]
+fn only_the_updated_trees_root_is_accepted() {
+    assert!(updates(0, 0));
+    assert!(!updates(0, 1));
+    assert!(!updates(1, 0));
+}
@@ -0,0 +1,90 @@
+//! The `verifier` binary against a proof made with the fixture keys.
+#![cfg(all(feature = "verifier", feature = "prover"))]
+
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::One;
+use ark_groth16::{Proof, ProvingKey, VerifyingKey};
+use ark_mnt4_753::{Fq, Fr as MNT4BigFr, G1Affine, MNT4_753};
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+use puzzle_gamma_ray::{
+    find_leaf_index, from_file, nullifier, prove, public_inputs_to_strings, save_proof, to_file,
+    CircuitParams, Mnt753Cycle, Zeroizing, LEGACY_DOMAIN,
+};
+
+fn verify(proof: &str, vk: &str, public_inputs: &[MNT4BigFr]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_verifier"))
+        .arg(proof)
+        .arg(vk)
+        .args(public_inputs_to_strings(public_inputs))
+        .output()
+        .unwrap()
+}
+
+fn accepts(proof: &str, vk: &str, public_inputs: &[MNT4BigFr]) -> bool {
+    verify(proof, vk, public_inputs).status.success()
+}
+
+#[test]
+fn the_verifier_accepts_a_valid_proof_and_rejects_a_tampered_one() {
+    let params = CircuitParams::poseidon();
+    let leaves: Vec<Vec<MNT4BigFr>> = from_file("./leaves.bin").unwrap();
+    let secret: MNT4BigFr = from_file("./leaked_secret.bin").unwrap();
+    let (pk, vk): (ProvingKey<MNT4_753>, VerifyingKey<MNT4_753>) =
+        from_file("./proof_keys.bin").unwrap();
+
+    let tree = params
+        .merkle_tree(leaves.iter().map(Vec::as_slice))
+        .unwrap();
+    let i = find_leaf_index(&leaves, secret).unwrap();
+    let nullifier = nullifier(&params.leaf, secret, LEGACY_DOMAIN).unwrap();
+    let circuit = params
+        .spend_circuit::<Mnt753Cycle>(
+            tree.root(),
+            tree.generate_proof(i).unwrap(),
+            Zeroizing::new(secret),
+            nullifier,
+        )
+        .with_strict_nullifier(false);
+    let proof = prove(&pk, circuit, &mut StdRng::seed_from_u64(0)).unwrap();
+    let public_inputs = [tree.root(), nullifier];
+
+    let dir: PathBuf =
+        std::env::temp_dir().join(format!("gamma-ray-verifier-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+
+    to_file(&path("vk.bin"), &vk).unwrap();
+    save_proof(&path("proof.bin"), &proof, &public_inputs).unwrap();
+    // A proof of valid points that isn't one for these inputs
+    let tampered = Proof {
+        a: (-proof.a.into_group()).into_affine(),
+        ..proof.clone()
+    };
+    save_proof(&path("tampered.bin"), &tampered, &public_inputs).unwrap();
+    // A key with a point moved off the curve
+    let mut invalid_vk = vk.clone();
+    let point = invalid_vk.gamma_abc_g1[0];
+    invalid_vk.gamma_abc_g1[0] = G1Affine::new_unchecked(point.x, point.y + Fq::one());
+    to_file(&path("invalid_vk.bin"), &invalid_vk).unwrap();
+
+    let accepted = accepts(&path("proof.bin"), &path("vk.bin"), &public_inputs);
+    let other_nullifier = accepts(
+        &path("proof.bin"),
+        &path("vk.bin"),
+        &[tree.root(), nullifier + MNT4BigFr::from(1u64)],
+    );
+    let tampered = accepts(&path("tampered.bin"), &path("vk.bin"), &public_inputs);
+    let invalid_vk = verify(&path("proof.bin"), &path("invalid_vk.bin"), &public_inputs);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert!(accepted);
+    assert!(!other_nullifier);
+    assert!(!tampered);
+    assert!(!invalid_vk.status.success());
+    let stderr = String::from_utf8(invalid_vk.stderr).unwrap();
+    assert!(stderr.contains("could not deserialize"), "{}", stderr);
+}
@@ -0,0 +1,49 @@
+//! The browser's verification path, with the filesystem code compiled out:
+//!
+//!     cargo test --no-default-features --features prover --test verify_bytes
+#![cfg(all(not(feature = "native-io"), feature = "prover"))]
+
+use ark_mnt4_753::{Fr as MNT4BigFr, MNT4_753};
+use ark_serialize::CanonicalSerialize;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+use puzzle_gamma_ray::{
+    build_tree, generate_leaves, nullifier, prove, setup, verify_proof_bytes, CircuitParams,
+    Mnt753Cycle, VerifyError, Zeroizing, LEGACY_DOMAIN,
+};
+
+#[test]
+fn proofs_verify_from_bytes() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let params = CircuitParams::poseidon();
+    let (leaves, secrets) = generate_leaves(2, &mut rng);
+    let (tree, _) = build_tree(&params.leaf, &params.two_to_one, &leaves, 1).unwrap();
+    let nullifier = nullifier(&params.leaf, secrets[0], LEGACY_DOMAIN).unwrap();
+    let circuit = params.spend_circuit::<Mnt753Cycle>(
+        tree.root(),
+        tree.generate_proof(0).unwrap(),
+        Zeroizing::new(secrets[0]),
+        nullifier,
+    );
+
+    let (pk, vk) = setup(circuit.clone(), &mut rng).unwrap();
+    let proof = prove(&pk, circuit, &mut rng).unwrap();
+    let (mut proof_bytes, mut vk_bytes) = (Vec::new(), Vec::new());
+    proof.serialize_uncompressed(&mut proof_bytes).unwrap();
+    vk.serialize_uncompressed(&mut vk_bytes).unwrap();
+
+    let root = tree.root();
+    verify_proof_bytes::<MNT4_753>(&proof_bytes, &vk_bytes, &[root, nullifier]).unwrap();
+    assert!(matches!(
+        verify_proof_bytes::<MNT4_753>(
+            &proof_bytes,
+            &vk_bytes,
+            &[root, nullifier + MNT4BigFr::from(1u64)]
+        ),
+        Err(VerifyError::Rejected)
+    ));
+    assert!(matches!(
+        verify_proof_bytes::<MNT4_753>(&proof_bytes[1..], &vk_bytes, &[root, nullifier]),
+        Err(VerifyError::Deserialize(_))
+    ));
+}
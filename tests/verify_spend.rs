@@ -0,0 +1,205 @@
+//! Verifying a spend made with the fixture keys, and telling apart why one
+//! fails.
+#![cfg(all(feature = "native-io", feature = "prover"))]
+
+use ark_ff::One;
+use ark_groth16::{Proof, ProvingKey, VerifyingKey};
+use ark_mnt4_753::{Fq, Fr as MNT4BigFr, G1Affine, MNT4_753};
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+use std::sync::OnceLock;
+
+use puzzle_gamma_ray::{
+    compose_proof, decompose_proof, from_file, nullifier, prove, public_inputs, verify_detailed,
+    verify_public_inputs, verify_spend, verify_spend_multi_root, SpendTransaction, VerifyError,
+    VerifyReport, LEGACY_DOMAIN,
+};
+
+mod common;
+use common::PuzzleFixture;
+
+/// The fixture verifying key and the honest spend of the leaked secret,
+/// proven once for every test.
+struct Fixture {
+    vk: VerifyingKey<MNT4_753>,
+    root: MNT4BigFr,
+    nullifier: MNT4BigFr,
+    proof: Proof<MNT4_753>,
+    /// The [`public_inputs`] of the proven circuit.
+    public_inputs: Vec<MNT4BigFr>,
+}
+
+impl Fixture {
+    fn get() -> &'static Self {
+        static FIXTURE: OnceLock<Fixture> = OnceLock::new();
+        FIXTURE.get_or_init(|| {
+            let (pk, vk): (ProvingKey<MNT4_753>, VerifyingKey<MNT4_753>) =
+                from_file("./proof_keys.bin").unwrap();
+            let puzzle = PuzzleFixture::load();
+            let secret = puzzle.leaked_secret;
+            let nullifier = nullifier(&puzzle.params.leaf, secret, LEGACY_DOMAIN).unwrap();
+            let circuit = puzzle.spend(secret, nullifier).with_strict_nullifier(false);
+            let circuit_root = circuit.root;
+            let public_inputs = public_inputs(&circuit);
+            let proof = prove(&pk, circuit, &mut StdRng::seed_from_u64(0)).unwrap();
+
+            Fixture {
+                vk,
+                root: circuit_root,
+                nullifier,
+                proof,
+                public_inputs,
+            }
+        })
+    }
+}
+
+#[test]
+fn rejections_and_malformed_inputs_are_different_errors() {
+    let Fixture {
+        vk,
+        root,
+        nullifier,
+        proof,
+        ..
+    } = Fixture::get();
+
+    verify_spend(vk, root, nullifier, proof).unwrap();
+    assert!(matches!(
+        verify_spend(vk, root, &(*nullifier + MNT4BigFr::from(1u64)), proof),
+        Err(VerifyError::Rejected)
+    ));
+    assert!(matches!(
+        verify_public_inputs(vk, &[*root], proof),
+        Err(VerifyError::PublicInputLength {
+            expected: 2,
+            actual: 1
+        })
+    ));
+}
+
+#[test]
+fn a_transaction_verifies_only_in_its_own_order() {
+    let fixture = Fixture::get();
+    let transaction = SpendTransaction::new(fixture.root, fixture.nullifier, fixture.proof.clone());
+
+    transaction.verify(&fixture.vk).unwrap();
+    let decoded = SpendTransaction::from_bytes(&transaction.to_bytes().unwrap()).unwrap();
+    assert_eq!(decoded, transaction);
+    decoded.verify(&fixture.vk).unwrap();
+
+    let swapped = SpendTransaction::new(fixture.nullifier, fixture.root, fixture.proof.clone());
+    assert!(matches!(
+        swapped.verify(&fixture.vk),
+        Err(VerifyError::Rejected)
+    ));
+}
+
+#[test]
+fn any_allowed_root_is_accepted() {
+    let Fixture {
+        vk,
+        root,
+        nullifier,
+        proof,
+        ..
+    } = Fixture::get();
+    let other = |i: u64| *root + MNT4BigFr::from(i);
+
+    assert_eq!(
+        verify_spend_multi_root(vk, &[other(1), *root, other(2)], nullifier, proof).unwrap(),
+        1
+    );
+    assert!(matches!(
+        verify_spend_multi_root(vk, &[other(1), other(2)], nullifier, proof),
+        Err(VerifyError::Rejected)
+    ));
+    assert!(matches!(
+        verify_spend_multi_root(vk, &[], nullifier, proof),
+        Err(VerifyError::Rejected)
+    ));
+}
+
+#[test]
+fn the_circuits_public_inputs_verify() {
+    let fixture = Fixture::get();
+
+    assert_eq!(
+        fixture.public_inputs.len(),
+        fixture.vk.gamma_abc_g1.len() - 1
+    );
+    assert_eq!(fixture.public_inputs, [fixture.root, fixture.nullifier]);
+    verify_public_inputs(&fixture.vk, &fixture.public_inputs, &fixture.proof).unwrap();
+}
+
+#[test]
+fn a_recomposed_proof_verifies_like_the_original() {
+    let Fixture {
+        vk,
+        root,
+        nullifier,
+        proof,
+        ..
+    } = Fixture::get();
+
+    let (a, b, c) = decompose_proof(proof);
+    let recomposed = compose_proof::<MNT4_753>(a, b, c);
+    assert_eq!(recomposed, *proof);
+    verify_spend(vk, root, nullifier, &recomposed).unwrap();
+
+    let swapped = compose_proof::<MNT4_753>(c, b, a);
+    assert!(matches!(
+        verify_spend(vk, root, nullifier, &swapped),
+        Err(VerifyError::Rejected)
+    ));
+}
+
+#[test]
+fn a_wrong_root_and_a_corrupted_proof_fail_differently() {
+    let Fixture {
+        vk,
+        root,
+        nullifier,
+        proof,
+        ..
+    } = Fixture::get();
+
+    assert_eq!(
+        verify_detailed(vk, &[*root, *nullifier], proof),
+        VerifyReport::Accepted
+    );
+    assert_eq!(
+        verify_detailed(vk, &[*root + MNT4BigFr::one(), *nullifier], proof),
+        VerifyReport::PairingFailed
+    );
+    assert_eq!(
+        verify_detailed(vk, &[*root], proof),
+        VerifyReport::InputCount {
+            expected: 2,
+            actual: 1
+        }
+    );
+
+    // A point moved off the curve
+    let mut corrupted = proof.clone();
+    corrupted.a = G1Affine::new_unchecked(proof.a.x, proof.a.y + Fq::one());
+    assert_eq!(
+        verify_detailed(vk, &[*root, *nullifier], &corrupted),
+        VerifyReport::InvalidProof
+    );
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn a_mapped_verifying_key_verifies() {
+    use ark_serialize::Compress;
+    use puzzle_gamma_ray::load_verifying_key_mmap;
+
+    let fixture = Fixture::get();
+    let (vk, version) =
+        load_verifying_key_mmap::<MNT4_753>("./proof_keys.bin", Compress::No).unwrap();
+
+    assert_eq!(version, 1);
+    assert_eq!(vk, fixture.vk);
+    verify_spend(&vk, &fixture.root, &fixture.nullifier, &fixture.proof).unwrap();
+}
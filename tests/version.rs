@@ -0,0 +1,48 @@
+//! A proof is bound to the circuit version it was made for.
+#![cfg(feature = "prover")]
+
+use ark_mnt4_753::Fr as MNT4BigFr;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+use puzzle_gamma_ray::{
+    generate_leaves, nullifier, prove, setup, verify_public_inputs, CircuitParams, Mnt753Cycle,
+    SpendCircuit, VerifyError, Zeroizing, CIRCUIT_VERSION, LEGACY_DOMAIN,
+};
+
+fn spend(version: u32) -> SpendCircuit {
+    let params = CircuitParams::poseidon();
+    let (leaves, secrets) = generate_leaves(2, &mut StdRng::seed_from_u64(0));
+    let tree = params
+        .merkle_tree(leaves.iter().map(Vec::as_slice))
+        .unwrap();
+
+    params
+        .spend_circuit::<Mnt753Cycle>(
+            tree.root(),
+            tree.generate_proof(0).unwrap(),
+            Zeroizing::new(secrets[0]),
+            nullifier(&params.leaf, secrets[0], LEGACY_DOMAIN).unwrap(),
+        )
+        .with_version(version)
+}
+
+#[test]
+fn a_proof_is_rejected_under_another_versions_key() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let current = spend(CIRCUIT_VERSION);
+    let (pk, vk) = setup(current.clone(), &mut rng).unwrap();
+    let (_, previous_vk) = setup(spend(CIRCUIT_VERSION - 1), &mut rng).unwrap();
+
+    let public_inputs = current.public_inputs();
+    assert_eq!(
+        public_inputs.last(),
+        Some(&MNT4BigFr::from(CIRCUIT_VERSION))
+    );
+    let proof = prove(&pk, current, &mut rng).unwrap();
+
+    verify_public_inputs(&vk, &public_inputs, &proof).unwrap();
+    assert!(matches!(
+        verify_public_inputs(&previous_vk, &public_inputs, &proof),
+        Err(VerifyError::Rejected)
+    ));
+}
@@ -0,0 +1,138 @@
+//! Spends built from a secret and the leaves alone, with the tree padded and
+//! the secret canonicalized as a strict circuit needs.
+#![cfg(feature = "prover")]
+
+use ark_mnt4_753::Fr as MNT4BigFr;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+use puzzle_gamma_ray::{
+    check_satisfied, fr_to_scalar, generate_leaves, leaf_at, nullifier, prove, scalar_to_fr, setup,
+    validate_witness, verify_spend, CircuitParams, SpendCircuit, WitnessError, Zeroizing,
+    LEGACY_DOMAIN,
+};
+
+/// `r - secret`, which owns the same leaf as `secret`.
+fn negated(secret: MNT4BigFr) -> MNT4BigFr {
+    scalar_to_fr(-fr_to_scalar(secret).unwrap())
+}
+
+#[test]
+fn from_secret_proves_an_upper_half_key() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let params = CircuitParams::poseidon();
+    // Three leaves, which the arkworks tree can't hold unpadded
+    let (leaves, secrets) = generate_leaves(3, &mut rng);
+    let upper = negated(secrets[1]);
+
+    let circuit = SpendCircuit::from_secret(&leaves, Zeroizing::new(upper), &params).unwrap();
+    assert_eq!(*circuit.secret, secrets[1]);
+    assert_eq!(
+        circuit.nullifier,
+        nullifier(&params.leaf, secrets[1], LEGACY_DOMAIN).unwrap()
+    );
+    assert!(check_satisfied(&circuit).unwrap());
+
+    let (pk, vk) = setup(circuit.clone(), &mut rng).unwrap();
+    let (root, nullifier) = (circuit.root, circuit.nullifier);
+    let proof = prove(&pk, circuit, &mut rng).unwrap();
+    verify_spend(&vk, &root, &nullifier, &proof).unwrap();
+}
+
+#[test]
+fn from_secret_spends_a_single_leaf() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let params = CircuitParams::poseidon();
+    let (leaves, secrets) = generate_leaves(1, &mut rng);
+
+    let circuit = SpendCircuit::from_secret(&leaves, Zeroizing::new(secrets[0]), &params).unwrap();
+    assert!(check_satisfied(&circuit).unwrap());
+}
+
+#[test]
+fn validate_witness_accepts_a_consistent_triple() {
+    let params = CircuitParams::poseidon();
+    let (leaves, secrets) = generate_leaves(4, &mut StdRng::seed_from_u64(2));
+    let tree = params
+        .merkle_tree(leaves.iter().map(Vec::as_slice))
+        .unwrap();
+
+    let path = validate_witness(
+        &params.leaf,
+        &params.two_to_one,
+        &tree,
+        &leaves,
+        2,
+        secrets[2],
+    )
+    .unwrap();
+    assert_eq!(path.leaf_index, 2);
+    assert!(path
+        .verify(
+            &params.leaf,
+            &params.two_to_one,
+            &tree.root(),
+            leaves[2].as_slice()
+        )
+        .unwrap());
+}
+
+#[test]
+fn validate_witness_rejects_a_mismatched_index() {
+    let params = CircuitParams::poseidon();
+    let (leaves, secrets) = generate_leaves(4, &mut StdRng::seed_from_u64(3));
+    let tree = params
+        .merkle_tree(leaves.iter().map(Vec::as_slice))
+        .unwrap();
+    let validate = |tree, index| {
+        validate_witness(
+            &params.leaf,
+            &params.two_to_one,
+            tree,
+            &leaves,
+            index,
+            secrets[2],
+        )
+    };
+
+    assert!(matches!(
+        validate(&tree, 1),
+        Err(WitnessError::LeafMismatch { index: 1 })
+    ));
+    assert!(matches!(
+        validate(&tree, 4),
+        Err(WitnessError::IndexOutOfRange {
+            index: 4,
+            leaves: 4
+        })
+    ));
+
+    // The right leaf, in a tree that holds another one in its place
+    let mut other = leaves.clone();
+    other[2] = generate_leaves(1, &mut StdRng::seed_from_u64(4)).0[0].clone();
+    let other = params.merkle_tree(other.iter().map(Vec::as_slice)).unwrap();
+    assert!(matches!(
+        validate(&other, 2),
+        Err(WitnessError::PathMismatch { index: 2 })
+    ));
+}
+
+#[test]
+fn leaf_at_is_bounded_by_the_fixture() {
+    let (leaves, _) = generate_leaves(3, &mut StdRng::seed_from_u64(0));
+
+    assert_eq!(leaf_at(&leaves, 2).unwrap(), leaves[2].as_slice());
+    assert!(matches!(
+        leaf_at(&leaves, 3),
+        Err(WitnessError::IndexOutOfRange {
+            index: 3,
+            leaves: 3
+        })
+    ));
+    assert!(matches!(
+        leaf_at(&[], 0),
+        Err(WitnessError::IndexOutOfRange {
+            index: 0,
+            leaves: 0
+        })
+    ));
+}